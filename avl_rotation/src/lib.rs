@@ -0,0 +1,197 @@
+//! The two rotations AVL trees rebalance with, written once for any kind
+//! of node. A tree only says how to reach a node's children and how to
+//! get between a node and the link holding it; whether that relinks
+//! nodes in place or rebuilds copies of shared ones is up to the tree.
+//!
+//! ```
+//! use avl_rotation::{rotate_left, Rotate};
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Node {
+//!     item: u32,
+//!     left: Option<Box<Node>>,
+//!     right: Option<Box<Node>>,
+//! }
+//!
+//! impl Rotate for Box<Node> {
+//!     type Link = Option<Box<Node>>;
+//!
+//!     fn left(&mut self) -> &mut Self::Link {
+//!         &mut self.left
+//!     }
+//!
+//!     fn right(&mut self) -> &mut Self::Link {
+//!         &mut self.right
+//!     }
+//!
+//!     fn from_link(link: Self::Link) -> Option<Self> {
+//!         link
+//!     }
+//!
+//!     fn into_link(self) -> Self::Link {
+//!         Some(self)
+//!     }
+//!
+//!     fn update_height(&mut self) {}
+//! }
+//!
+//! let leaf = |item| Some(Box::new(Node { item, left: None, right: None }));
+//! let root = Box::new(Node { item: 1, left: None, right: leaf(2) });
+//!
+//! let rotated = rotate_left(root);
+//! assert_eq!(rotated.item, 2);
+//! assert_eq!(rotated.left, leaf(1));
+//! ```
+
+use std::mem;
+
+/// A node that can be rotated, i.e. taken out of and put back into the
+/// links between nodes.
+pub trait Rotate: Sized {
+    /// What a child hangs from, empty by default.
+    type Link: Default;
+
+    fn left(&mut self) -> &mut Self::Link;
+
+    fn right(&mut self) -> &mut Self::Link;
+
+    /// The node a link holds, if any, to change as its own.
+    fn from_link(link: Self::Link) -> Option<Self>;
+
+    fn into_link(self) -> Self::Link;
+
+    /// From the children's heights, which are already right whenever
+    /// this is called.
+    fn update_height(&mut self);
+}
+
+/*
+ *          P
+ *        /   \
+ *       /     \
+ *      T1      X
+ *             / \
+ *            /   \
+ *           Y    New
+ *
+ *
+ *          X
+ *        /   \
+ *       /     \
+ *      P      New
+ *     / \
+ *    T1  Y
+ *
+ * Only P and X change height, and both are updated here. T1, Y and New
+ * move as links, without being looked into.
+ */
+/// Lifts the right child into `pivot`'s place, handing back whichever
+/// node ends up on top: `pivot` itself if it has no right child.
+pub fn rotate_left<N: Rotate>(mut pivot: N) -> N {
+    let Some(mut x) = N::from_link(mem::take(pivot.right())) else {
+        return pivot;
+    };
+
+    *pivot.right() = mem::take(x.left());
+    pivot.update_height();
+
+    *x.left() = pivot.into_link();
+    x.update_height();
+    x
+}
+
+/// The mirror image of [`rotate_left`].
+pub fn rotate_right<N: Rotate>(mut pivot: N) -> N {
+    let Some(mut x) = N::from_link(mem::take(pivot.left())) else {
+        return pivot;
+    };
+
+    *pivot.left() = mem::take(x.right());
+    pivot.update_height();
+
+    *x.right() = pivot.into_link();
+    x.update_height();
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Node {
+        item: u32,
+        height: usize,
+        left: Option<Box<Node>>,
+        right: Option<Box<Node>>,
+    }
+
+    impl Rotate for Box<Node> {
+        type Link = Option<Box<Node>>;
+
+        fn left(&mut self) -> &mut Self::Link {
+            &mut self.left
+        }
+
+        fn right(&mut self) -> &mut Self::Link {
+            &mut self.right
+        }
+
+        fn from_link(link: Self::Link) -> Option<Self> {
+            link
+        }
+
+        fn into_link(self) -> Self::Link {
+            Some(self)
+        }
+
+        fn update_height(&mut self) {
+            let height = |link: &Option<Box<Node>>| link.as_ref().map_or(0, |node| node.height);
+            self.height = 1 + height(&self.left).max(height(&self.right));
+        }
+    }
+
+    fn node(item: u32, left: Option<Box<Node>>, right: Option<Box<Node>>) -> Option<Box<Node>> {
+        let mut node = Box::new(Node {
+            item,
+            height: 0,
+            left,
+            right,
+        });
+        node.update_height();
+        Some(node)
+    }
+
+    #[test]
+    fn rotating_both_ways() {
+        // 1 (T1) under 2 (P), with 4 (X) over 3 (Y) and 5 (New)
+        let leaning = node(
+            2,
+            node(1, None, None),
+            node(4, node(3, None, None), node(5, None, None)),
+        );
+        let rotated = node(
+            4,
+            node(2, node(1, None, None), node(3, None, None)),
+            node(5, None, None),
+        );
+
+        let left = rotate_left(leaning.clone().unwrap());
+        assert_eq!(Some(left), rotated);
+
+        let right = rotate_right(rotated.unwrap());
+        assert_eq!(Some(right), leaning);
+    }
+
+    #[test]
+    fn rotating_without_a_child() {
+        let leaf = node(1, None, None).unwrap();
+        assert_eq!(rotate_left(leaf).item, 1);
+
+        let only_left = node(2, node(1, None, None), None).unwrap();
+        assert_eq!(
+            rotate_left(only_left),
+            node(2, node(1, None, None), None).unwrap()
+        );
+    }
+}
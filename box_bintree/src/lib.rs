@@ -1,7 +1,13 @@
 use std::cmp::Ordering;
-use std::fmt::Debug;
-use std::iter::{FromIterator, IntoIterator};
+use std::error::Error;
+use std::fmt::{self, Debug};
+use std::iter::{FromIterator, IntoIterator, Peekable};
 use std::mem;
+use std::ops::RangeBounds;
+
+use avl_rotation::Rotate;
+use ordered_set::OrderedSet;
+use tree_view::{Node, Shape, TreeView};
 
 #[derive(PartialEq, Eq, Clone, Debug)]
 struct TreeNode<T: Copy + Ord + Debug> {
@@ -23,6 +29,42 @@ impl<T: Copy + Ord + Debug> TreeNode<T> {
             height: 1,
         }
     }
+
+    // From the children's heights, which must already be right
+    #[inline]
+    fn update_height(&mut self) {
+        self.height = 1 + self.left.height().max(self.right.height());
+    }
+}
+
+// Rotations relink the boxes in place
+impl<T: Copy + Ord + Debug> Rotate for Box<TreeNode<T>> {
+    type Link = Tree<T>;
+
+    #[inline]
+    fn left(&mut self) -> &mut Tree<T> {
+        &mut self.left
+    }
+
+    #[inline]
+    fn right(&mut self) -> &mut Tree<T> {
+        &mut self.right
+    }
+
+    #[inline]
+    fn from_link(link: Tree<T>) -> Option<Self> {
+        link.0
+    }
+
+    #[inline]
+    fn into_link(self) -> Tree<T> {
+        Tree(Some(self))
+    }
+
+    #[inline]
+    fn update_height(&mut self) {
+        TreeNode::update_height(self);
+    }
 }
 
 impl<T: Copy + Ord + Debug> From<TreeNode<T>> for Tree<T> {
@@ -50,211 +92,431 @@ impl<T: Copy + Ord + Debug> Tree<T> {
         }
     }
 
-    fn add_child(&mut self, new_item: T) {
-        match self.0.as_deref_mut() {
-            None => *self = TreeNode::new(new_item).into(),
+    // Returns whether `new_item` was added, i.e. wasn't already there
+    fn add_child(&mut self, new_item: T) -> bool {
+        let added = match self.0.as_deref_mut() {
+            None => {
+                *self = TreeNode::new(new_item).into();
+                true
+            }
 
             Some(node) => {
-                match new_item.cmp(&node.item) {
+                let added = match new_item.cmp(&node.item) {
                     Ordering::Less => node.left.add_child(new_item),
                     Ordering::Greater => node.right.add_child(new_item),
-                    Ordering::Equal => {}
+                    Ordering::Equal => false,
                 };
 
-                self.calculate_height();
+                node.update_height();
+                added
             }
-        }
+        };
 
         match self.calculate_balance() {
             -2 => {
-                println!(
-                    "Right rotation, {:?} pivot",
-                    self.0.as_deref().unwrap().item
-                );
-
                 // Left-right case
-                if self
-                    .0
-                    .as_deref()
-                    .map(|n| n.left.0.as_deref())
-                    .flatten()
-                    .filter(|n| n.item < new_item)
-                    .is_some()
-                {
-                    self.0.as_deref_mut().map(|n| {
-                        n.left.rotate_left();
-                        n
-                    });
+                if let Some(node) = self.0.as_deref_mut() {
+                    if node.left.0.as_deref().is_some_and(|n| n.item < new_item) {
+                        node.left.rotate_left();
+                    }
                 }
 
                 self.rotate_right();
-                self.calculate_height();
             }
 
             2 => {
-                println!("Left rotation, {:?} pivot", self.0.as_deref().unwrap().item);
-
                 // Right-left case
-                if self
-                    .0
-                    .as_deref()
-                    .map(|n| n.right.0.as_deref())
-                    .flatten()
-                    .filter(|n| n.item > new_item)
-                    .is_some()
-                {
-                    println!("Right-left case!");
-                    self.0.as_deref_mut().map(|n| {
-                        n.right.rotate_right();
-                        n
-                    });
+                if let Some(node) = self.0.as_deref_mut() {
+                    if node.right.0.as_deref().is_some_and(|n| n.item > new_item) {
+                        node.right.rotate_right();
+                    }
                 }
 
                 self.rotate_left();
-                self.calculate_height();
             }
 
             (-1..=1) => {}
 
             _ => unreachable!(),
         }
+
+        added
     }
 
-    fn remove_leftmost_child(&mut self) -> Option<Box<TreeNode<T>>> {
-        let pruned = match self.0.as_deref_mut() {
-            None => None,
+    // Unlinks the node holding `target`, rebalancing on the way back up
+    fn remove_child(&mut self, target: &T) -> Option<T> {
+        let node = self.0.as_deref_mut()?;
+
+        let removed = match target.cmp(&node.item) {
+            Ordering::Less => node.left.remove_child(target),
+            Ordering::Greater => node.right.remove_child(target),
+            Ordering::Equal => Some(self.remove_root()),
+        };
 
-            Some(TreeNode {
-                left: l @ Tree(Some(_)),
-                ..
-            }) => l.remove_leftmost_child(),
-
-            Some(TreeNode {
-                left: Tree(None),
-                right: Tree(None),
-                ..
-            }) => self.0.take(),
-
-            Some(TreeNode {
-                left: Tree(None),
-                right: r @ Tree(Some(_)),
-                ..
-            }) => {
-                let right_child = r.0.take();
-                mem::replace(&mut self.0, right_child)
+        self.rebalance();
+        removed
+    }
+
+    // Expects a non-empty tree
+    fn remove_root(&mut self) -> T {
+        let node = self
+            .0
+            .as_deref_mut()
+            .expect("only called on a present node");
+
+        match (&node.left, &node.right) {
+            // Keep the node where it is and trade its item for its successor's
+            (Tree(Some(_)), Tree(Some(_))) => {
+                let successor = node
+                    .right
+                    .remove_leftmost_child()
+                    .expect("right subtree is present");
+                mem::replace(&mut node.item, successor)
+            }
+
+            // Otherwise let its only child (if any) move up
+            (Tree(Some(_)), Tree(None)) => {
+                let orphan = mem::take(&mut node.left);
+                mem::replace(self, orphan).into_item()
+            }
+
+            (Tree(None), _) => {
+                let orphan = mem::take(&mut node.right);
+                mem::replace(self, orphan).into_item()
+            }
+        }
+    }
+
+    fn remove_leftmost_child(&mut self) -> Option<T> {
+        let node = self.0.as_deref_mut()?;
+
+        let removed = match node.left.0 {
+            Some(_) => node.left.remove_leftmost_child(),
+
+            None => {
+                let orphan = mem::take(&mut node.right);
+                return Some(mem::replace(self, orphan).into_item());
             }
         };
 
-        pruned
-    }
-
-    /*
-     *          P
-     *        /   \
-     *       /     \
-     *      T1      X
-     *             / \
-     *            /   \
-     *           Y    New
-     *
-     *
-     *          X
-     *        /   \
-     *       /     \
-     *      P      New
-     *     / \
-     *    T1  Y
-     */
-    fn rotate_left(&mut self) {
-        let mut pivot = self.0.take();
-        let mut x: Tree<T> = Tree::default();
-        let mut y: Tree<T> = Tree::default();
+        self.rebalance();
+        removed
+    }
 
-        pivot.as_deref_mut().map(|p| {
-            let mut right_child = p.right.0.take();
+    // Expects a single node, whose children have already been moved out
+    fn into_item(self) -> T {
+        self.0.expect("only called on a present node").item
+    }
 
-            let left_of_right = right_child
-                .as_deref_mut()
-                .map(|r| r.left.0.take())
-                .flatten()
-                .take();
+    // Brings the balance back within [-1, 1] after one subtree lost a
+    // level, going by the balance of the taller child since there's no
+    // new item to compare against
+    fn rebalance(&mut self) {
+        let Some(node) = self.0.as_deref_mut() else {
+            return;
+        };
 
-            x = Tree(right_child);
-            y = Tree(left_of_right);
+        node.update_height();
 
-            p.right = y;
-            *self = x;
+        match self.calculate_balance() {
+            -2 => {
+                if let Some(node) = self.0.as_deref_mut() {
+                    if node.left.calculate_balance() > 0 {
+                        node.left.rotate_left();
+                    }
+                }
+                self.rotate_right();
+            }
 
-            p
-        });
+            2 => {
+                if let Some(node) = self.0.as_deref_mut() {
+                    if node.right.calculate_balance() < 0 {
+                        node.right.rotate_right();
+                    }
+                }
+                self.rotate_left();
+            }
 
-        self.0.as_deref_mut().map(|x| {
-            x.left = Tree(pivot);
-            x
-        });
+            _ => {}
+        }
+    }
+
+    // See avl_rotation for the picture
+    fn rotate_left(&mut self) {
+        if let Some(pivot) = self.0.take() {
+            self.0 = Some(avl_rotation::rotate_left(pivot));
+        }
     }
 
     fn rotate_right(&mut self) {
-        let mut pivot = self.0.take();
-        let mut x: Tree<T> = Tree::default();
-        let mut y: Tree<T> = Tree::default();
+        if let Some(pivot) = self.0.take() {
+            self.0 = Some(avl_rotation::rotate_right(pivot));
+        }
+    }
+
+    #[inline]
+    fn height(&self) -> usize {
+        self.0.as_deref().map_or(0, |node| node.height)
+    }
+
+    fn calculate_balance(&self) -> i8 {
+        self.0.as_deref().map_or(0, |node| {
+            node.right.height() as i8 - node.left.height() as i8
+        })
+    }
 
-        pivot.as_deref_mut().map(|p| {
-            let mut left_child = p.left.0.take();
+    // Recomputes every height in the tree, for trees built some other way
+    // than by adding and removing children
+    fn calculate_height(&mut self) -> usize {
+        self.0.as_deref_mut().map_or(0, |node| {
+            node.left.calculate_height();
+            node.right.calculate_height();
+            node.update_height();
+            node.height
+        })
+    }
+}
 
-            let right_of_left = left_child
-                .as_deref_mut()
-                .map(|l| l.right.0.take())
-                .flatten()
-                .take();
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraversalError {
+    /// The two sequences don't have the same number of items.
+    LengthMismatch { order: usize, inorder: usize },
+    /// No tree has both sequences as its traversals.
+    Inconsistent,
+    /// The inorder sequence isn't strictly increasing.
+    Unordered,
+    /// The traversals describe a tree, but not a height-balanced one.
+    Unbalanced,
+}
 
-            x = Tree(left_child);
-            y = Tree(right_of_left);
+impl fmt::Display for TraversalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LengthMismatch { order, inorder } => write!(
+                f,
+                "traversals differ in length ({} items vs. {} inorder)",
+                order, inorder
+            ),
+            Self::Inconsistent => write!(f, "traversals don't describe the same tree"),
+            Self::Unordered => write!(f, "inorder traversal isn't strictly increasing"),
+            Self::Unbalanced => write!(f, "traversals describe an unbalanced tree"),
+        }
+    }
+}
 
-            p.left = y;
-            *self = x;
+impl Error for TraversalError {}
+
+/// Which of the AVL rules (or plain BST rules) a tree breaks, as reported
+/// by [`BinTree::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    /// Some node's items aren't between those of its ancestors.
+    OutOfOrder,
+    /// Some node's stored height isn't one more than its taller child's.
+    WrongHeight,
+    /// Some node's subtrees differ in height by more than one.
+    Unbalanced,
+    /// The tree's size doesn't match how many nodes it has.
+    WrongSize,
+}
 
-            p
-        });
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfOrder => write!(f, "items are not in search order"),
+            Self::WrongHeight => write!(f, "a stored height is out of date"),
+            Self::Unbalanced => write!(f, "subtree heights differ by more than one"),
+            Self::WrongSize => write!(f, "size doesn't match the number of nodes"),
+        }
+    }
+}
 
-        self.0.as_deref_mut().map(|x| {
-            x.right = Tree(pivot);
-            x
-        });
+impl Error for Violation {}
+
+impl<T: Copy + Ord + Debug> Tree<T> {
+    fn preorder(&self, items: &mut Vec<T>) {
+        if let Some(node) = self.0.as_deref() {
+            items.push(node.item);
+            node.left.preorder(items);
+            node.right.preorder(items);
+        }
     }
 
-    fn calculate_balance(&self) -> i8 {
-        if let Some(TreeNode { left, right, .. }) = self.0.as_deref() {
-            right.0.as_deref().map_or(0, |n| (n.height) as i8)
-                - left.0.as_deref().map_or(0, |n| (n.height) as i8)
-        } else {
-            0
+    fn inorder(&self, items: &mut Vec<T>) {
+        if let Some(node) = self.0.as_deref() {
+            node.left.inorder(items);
+            items.push(node.item);
+            node.right.inorder(items);
         }
     }
 
-    fn calculate_height(&mut self) -> usize {
-        let height = self.0.as_deref_mut().map_or(0, |node| {
-            let l_subtree_height = 1 + node.left.calculate_height();
+    fn postorder(&self, items: &mut Vec<T>) {
+        if let Some(node) = self.0.as_deref() {
+            node.left.postorder(items);
+            node.right.postorder(items);
+            items.push(node.item);
+        }
+    }
 
-            let r_subtree_height = 1 + node.right.calculate_height();
+    // Returns how many nodes the tree has, once every one of them checks out
+    fn validate(&self, lower: Option<&T>, upper: Option<&T>) -> Result<usize, Violation> {
+        let Some(node) = self.0.as_deref() else {
+            return Ok(0);
+        };
 
-            match l_subtree_height.cmp(&r_subtree_height) {
-                Ordering::Less => {
-                    node.height = r_subtree_height;
-                    r_subtree_height
-                }
+        if lower.is_some_and(|l| node.item <= *l) || upper.is_some_and(|u| node.item >= *u) {
+            return Err(Violation::OutOfOrder);
+        }
 
-                Ordering::Greater | Ordering::Equal => {
-                    node.height = l_subtree_height;
-                    l_subtree_height
-                }
-            }
-        });
+        let left = node.left.validate(lower, Some(&node.item))?;
+        let right = node.right.validate(Some(&node.item), upper)?;
+
+        if node.height != 1 + node.left.height().max(node.right.height()) {
+            return Err(Violation::WrongHeight);
+        }
 
-        height
+        if !(-1..=1).contains(&self.calculate_balance()) {
+            return Err(Violation::Unbalanced);
+        }
+
+        Ok(1 + left + right)
+    }
+
+    fn is_balanced(&self) -> bool {
+        self.0.as_deref().is_none_or(|node| {
+            (-1..=1).contains(&self.calculate_balance())
+                && node.left.is_balanced()
+                && node.right.is_balanced()
+        })
+    }
+
+    // Rebuilds exactly the shape the traversals describe, heights included,
+    // with no regard for ordering or balance, giving up as `Unbalanced` on
+    // any shape taller than `max_height`
+    fn from_preorder_inorder(
+        preorder: &[T],
+        inorder: &[T],
+        max_height: usize,
+    ) -> Result<Self, TraversalError> {
+        Self::check_lengths(preorder, inorder)?;
+
+        let mut tree = Self::from_traversals(
+            &mut preorder.iter(),
+            &mut inorder.iter().peekable(),
+            None,
+            true,
+            max_height,
+        )?;
+        tree.calculate_height();
+
+        let (mut pre, mut ino) = (vec![], vec![]);
+        tree.preorder(&mut pre);
+        tree.inorder(&mut ino);
+
+        match pre == preorder && ino == inorder {
+            true => Ok(tree),
+            false => Err(TraversalError::Inconsistent),
+        }
+    }
+
+    fn from_postorder_inorder(
+        postorder: &[T],
+        inorder: &[T],
+        max_height: usize,
+    ) -> Result<Self, TraversalError> {
+        Self::check_lengths(postorder, inorder)?;
+
+        let mut tree = Self::from_traversals(
+            &mut postorder.iter().rev(),
+            &mut inorder.iter().rev().peekable(),
+            None,
+            false,
+            max_height,
+        )?;
+        tree.calculate_height();
+
+        let (mut post, mut ino) = (vec![], vec![]);
+        tree.postorder(&mut post);
+        tree.inorder(&mut ino);
+
+        match post == postorder && ino == inorder {
+            true => Ok(tree),
+            false => Err(TraversalError::Inconsistent),
+        }
+    }
+
+    fn check_lengths(order: &[T], inorder: &[T]) -> Result<(), TraversalError> {
+        match order.len() == inorder.len() {
+            true => Ok(()),
+            false => Err(TraversalError::LengthMismatch {
+                order: order.len(),
+                inorder: inorder.len(),
+            }),
+        }
+    }
+
+    // `order` visits a node before its subtrees, nearest side first, and
+    // `inorder` starts from that same side. The subtree rooted at the next
+    // item of `order` ends where `inorder` reaches `stop`, the item of
+    // its closest ancestor on the far side, so every item is handled once.
+    // Stopping at `max_height` also keeps the recursion from going as deep
+    // as a long chain would take it.
+    fn from_traversals<'a, O, I>(
+        order: &mut O,
+        inorder: &mut Peekable<I>,
+        stop: Option<&T>,
+        left_first: bool,
+        max_height: usize,
+    ) -> Result<Self, TraversalError>
+    where
+        T: 'a,
+        O: Iterator<Item = &'a T>,
+        I: Iterator<Item = &'a T>,
+    {
+        if inorder.peek().copied() == stop {
+            return Ok(Tree(None));
+        }
+
+        let Some(&item) = order.next() else {
+            return Ok(Tree(None));
+        };
+
+        let Some(below) = max_height.checked_sub(1) else {
+            return Err(TraversalError::Unbalanced);
+        };
+
+        let near = Self::from_traversals(order, inorder, Some(&item), left_first, below)?;
+        inorder.next();
+        let far = Self::from_traversals(order, inorder, stop, left_first, below)?;
+
+        let (left, right) = match left_first {
+            true => (near, far),
+            false => (far, near),
+        };
+
+        Ok(TreeNode {
+            item,
+            left,
+            right,
+            height: 1,
+        }
+        .into())
     }
 }
 
+// The tallest an AVL tree of `size` nodes can be, going by the fewest
+// nodes each height needs: one more than the two heights below it
+fn max_avl_height(size: usize) -> usize {
+    let (mut height, mut fewest, mut fewest_below) = (0, 0_usize, 0_usize);
+
+    while fewest + fewest_below < size {
+        (fewest, fewest_below) = (fewest + fewest_below + 1, fewest);
+        height += 1;
+    }
+
+    height
+}
+
 #[derive(Default, PartialEq, Eq, Clone, Debug)]
 pub struct BinTree<T: Copy + Ord + Debug> {
     root: Tree<T>,
@@ -275,6 +537,43 @@ impl<T: Copy + Ord + Debug> BinTree<T> {
         self.size
     }
 
+    /// Rebuilds the tree with these traversals in O(n). The inorder
+    /// sequence must be strictly increasing and the tree it describes
+    /// height-balanced, and building stops as soon as it gets taller than
+    /// a balanced tree could be.
+    pub fn from_preorder_inorder(preorder: &[T], inorder: &[T]) -> Result<Self, TraversalError> {
+        Self::checked(inorder, |max_height| {
+            Tree::from_preorder_inorder(preorder, inorder, max_height)
+        })
+    }
+
+    /// Like `from_preorder_inorder`, but with a postorder sequence.
+    pub fn from_postorder_inorder(postorder: &[T], inorder: &[T]) -> Result<Self, TraversalError> {
+        Self::checked(inorder, |max_height| {
+            Tree::from_postorder_inorder(postorder, inorder, max_height)
+        })
+    }
+
+    fn checked(
+        inorder: &[T],
+        build: impl FnOnce(usize) -> Result<Tree<T>, TraversalError>,
+    ) -> Result<Self, TraversalError> {
+        if inorder.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(TraversalError::Unordered);
+        }
+
+        let root = build(max_avl_height(inorder.len()))?;
+
+        if !root.is_balanced() {
+            return Err(TraversalError::Unbalanced);
+        }
+
+        Ok(Self {
+            root,
+            size: inorder.len(),
+        })
+    }
+
     #[inline]
     pub fn clear(&mut self) {
         _ = self.root.0.take();
@@ -286,20 +585,36 @@ impl<T: Copy + Ord + Debug> BinTree<T> {
         self.root.search(target).map(|node| &node.item)
     }
 
+    /// Adds `new_item` unless the tree already holds it, returning whether
+    /// it was added.
+    #[inline]
+    pub fn insert(&mut self, new_item: T) -> bool {
+        let added = self.root.add_child(new_item);
+        self.size += added as usize;
+        added
+    }
+
+    /// Removes `target`, rebalancing on the way back up, in O(log n).
     #[inline]
-    pub fn insert(&mut self, new_item: T) {
-        self.root.add_child(new_item);
-        self.size += 1;
+    pub fn remove(&mut self, target: &T) -> Option<T> {
+        let removed = self.root.remove_child(target)?;
+        self.size -= 1;
+        Some(removed)
     }
 
     #[inline]
     pub fn remove_inorder(&mut self) -> Option<T> {
-        let removed = self.root.remove_leftmost_child().map(|n| {
-            self.size -= 1;
-            n.item
-        });
+        let removed = self.root.remove_leftmost_child()?;
+        self.size -= 1;
+        Some(removed)
+    }
 
-        removed
+    /// Checks every AVL invariant, in O(n).
+    pub fn validate(&self) -> Result<(), Violation> {
+        match self.root.validate(None, None)? == self.size {
+            true => Ok(()),
+            false => Err(Violation::WrongSize),
+        }
     }
 }
 
@@ -325,6 +640,25 @@ impl<'tree, T: Copy + Ord + Debug> BinTree<T> {
             node_stack: vec![],
         }
     }
+
+    /// Yields the items within `range` in ascending order, seeking the first in O(log n).
+    pub fn range<R>(&'tree self, range: R) -> ordered_set::Range<InorderIter<'tree, T>, R>
+    where
+        R: RangeBounds<T>,
+    {
+        let node_stack = ordered_set::seek(
+            self.root.0.as_deref(),
+            |node| [node.left.0.as_deref(), node.right.0.as_deref()],
+            |node| &node.item,
+            range.start_bound(),
+        );
+
+        let iter = InorderIter {
+            curr_node: None,
+            node_stack,
+        };
+        ordered_set::Range::new(iter, range)
+    }
 }
 
 impl<'tree, T: Copy + Ord + Debug> Iterator for InorderIter<'tree, T> {
@@ -357,14 +691,90 @@ impl<T: Copy + Ord + Debug> IntoIterator for BinTree<T> {
 impl<T: Copy + Ord + Debug> FromIterator<T> for BinTree<T> {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
         let mut tree: BinTree<T> = Self::new();
-        iter.into_iter().for_each(|item| tree.insert(item));
+        iter.into_iter().for_each(|item| {
+            tree.insert(item);
+        });
         tree
     }
 }
 
+impl<T: Copy + Ord + Debug> OrderedSet for BinTree<T> {
+    type Item = T;
+    type ItemRef<'set>
+        = &'set T
+    where
+        Self: 'set;
+    type Iter<'set>
+        = InorderIter<'set, T>
+    where
+        Self: 'set;
+    type Range<'set, R>
+        = ordered_set::Range<InorderIter<'set, T>, R>
+    where
+        Self: 'set,
+        R: RangeBounds<T>;
+
+    #[inline]
+    fn insert(&mut self, item: T) -> bool {
+        BinTree::insert(self, item)
+    }
+
+    #[inline]
+    fn remove(&mut self, item: &T) -> Option<T> {
+        BinTree::remove(self, item)
+    }
+
+    #[inline]
+    fn contains(&self, item: &T) -> bool {
+        self.search(item).is_some()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.size()
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+
+    #[inline]
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_, R> {
+        self.range(range)
+    }
+}
+
+impl<T: Copy + Ord + Debug> TreeView for BinTree<T> {
+    fn shape(&self) -> Shape {
+        Shape::new(
+            self.root.0.as_deref(),
+            |node| [node.left.0.as_deref(), node.right.0.as_deref()],
+            |node| {
+                let balance = node.right.height() as i8 - node.left.height() as i8;
+                Node::new(&node.item)
+                    .note("h", node.height)
+                    .note("bf", balance)
+            },
+        )
+    }
+}
+
+/// Draws the tree sideways, or top down with `{:#}`, see [`tree_view::pretty`].
+impl<T: Copy + Ord + Debug> fmt::Display for BinTree<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.pretty(), f)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    use std::collections::BTreeSet;
+
+    ordered_set::conformance_suite!(BinTree<u32>);
 
     impl<T: Copy + Ord + Debug> BinTree<T> {
         fn from_raw_tree_unchecked(tree: Tree<T>) -> Self {
@@ -396,26 +806,26 @@ mod tests {
         fn add_subtree_left(&mut self, mut subtree: Self) {
             let subtree_height = subtree.calculate_height();
 
-            self.0.as_deref_mut().map(|n| {
+            if let Some(n) = self.0.as_deref_mut() {
                 n.left = subtree;
 
                 if n.height < subtree_height + 1 {
                     n.height = subtree_height + 1;
                 }
-            });
+            }
         }
 
         #[allow(unused)]
         fn add_subtree_right(&mut self, mut subtree: Self) {
             let subtree_height = subtree.calculate_height();
 
-            self.0.as_deref_mut().map(|n| {
+            if let Some(n) = self.0.as_deref_mut() {
                 n.right = subtree;
 
                 if n.height < subtree_height + 1 {
                     n.height = subtree_height + 1;
                 }
-            });
+            }
         }
     }
 
@@ -576,6 +986,11 @@ mod tests {
 
         assert_eq!(tree.size(), 3);
         assert_eq!(tree, tree.clone());
+
+        // Duplicates are turned away, and not counted
+        assert!(!tree.insert(1));
+        assert!(tree.insert(4));
+        assert_eq!(tree.size(), 4);
     }
 
     #[test]
@@ -618,6 +1033,71 @@ mod tests {
         assert_eq!(tree.remove_inorder(), Some(75));
     }
 
+    #[test]
+    fn removing_with_established_balance() {
+        let mut tree: BinTree<u32> = (0..64).map(|i| (i * 37) % 64).collect();
+
+        assert!(!tree.insert(5));
+        assert_eq!(tree.size(), 64);
+
+        for (removed, target) in (0..64).map(|i| (i * 13) % 64).enumerate() {
+            assert_eq!(tree.remove(&target), Some(target));
+            assert_eq!(tree.remove(&target), None);
+            assert_eq!(tree.size(), 63 - removed);
+
+            assert_eq!(tree.validate(), Ok(()));
+        }
+
+        assert_eq!(tree, BinTree::new());
+    }
+
+    #[test]
+    fn validating() {
+        let balanced = tree! { 2, L 1, R 3 };
+        let unbalanced = tree! { 1, L {}, R {2, L {}, R 3} };
+        let unordered = tree! { 2, L 3, R 1 };
+
+        assert_eq!(
+            BinTree::from_raw_tree_unchecked(balanced.clone()).validate(),
+            Err(Violation::WrongSize)
+        );
+        assert_eq!(
+            BinTree {
+                root: balanced.clone(),
+                size: 3
+            }
+            .validate(),
+            Ok(())
+        );
+        assert_eq!(
+            BinTree {
+                root: unbalanced,
+                size: 3
+            }
+            .validate(),
+            Err(Violation::Unbalanced)
+        );
+        assert_eq!(
+            BinTree {
+                root: unordered,
+                size: 3
+            }
+            .validate(),
+            Err(Violation::OutOfOrder)
+        );
+
+        let mut stale = balanced;
+        stale.0.as_deref_mut().unwrap().height = 3;
+        assert_eq!(
+            BinTree {
+                root: stale,
+                size: 3
+            }
+            .validate(),
+            Err(Violation::WrongHeight)
+        );
+    }
+
     #[test]
     fn clearing() {
         let mut bintree = BinTree::from_raw_tree_unchecked(tree! {
@@ -885,6 +1365,114 @@ mod tests {
         )
     }
 
+    #[test]
+    fn rebuilding_from_traversals() {
+        let shapes: [Tree<u8>; 4] = [
+            tree! {},
+            tree! {1},
+            tree! {
+                5, L {3, L {}, R 4}, R {7, L 6, R {9, L 8, R {}}}
+            },
+            // Not a search tree, nor balanced
+            tree! {
+                1, L {2, L {3, L 4, R {}}, R {}}, R 0
+            },
+        ];
+
+        for shape in shapes {
+            let (mut pre, mut ino, mut post) = (vec![], vec![], vec![]);
+            shape.preorder(&mut pre);
+            shape.inorder(&mut ino);
+            shape.postorder(&mut post);
+
+            let max = usize::MAX;
+            assert_eq!(
+                Tree::from_preorder_inorder(&pre, &ino, max),
+                Ok(shape.clone())
+            );
+            assert_eq!(Tree::from_postorder_inorder(&post, &ino, max), Ok(shape));
+        }
+
+        let chain = tree! {1, L {2, L {3, L 4, R {}}, R {}}, R {}};
+        let (mut pre, mut ino) = (vec![], vec![]);
+        chain.preorder(&mut pre);
+        chain.inorder(&mut ino);
+        assert_eq!(
+            Tree::from_preorder_inorder(&pre, &ino, 3),
+            Err(TraversalError::Unbalanced)
+        );
+        assert_eq!(Tree::from_preorder_inorder(&pre, &ino, 4), Ok(chain));
+
+        assert_eq!(
+            Tree::from_preorder_inorder(&[1, 2], &[1], usize::MAX),
+            Err(TraversalError::LengthMismatch {
+                order: 2,
+                inorder: 1
+            })
+        );
+        assert_eq!(
+            Tree::from_preorder_inorder(&[1, 2, 3], &[3, 1, 2], usize::MAX),
+            Err(TraversalError::Inconsistent)
+        );
+        assert_eq!(
+            Tree::from_postorder_inorder(&[1, 2, 3], &[1, 2, 4], usize::MAX),
+            Err(TraversalError::Inconsistent)
+        );
+    }
+
+    #[test]
+    fn constructing_from_traversals() {
+        let tree: BinTree<u32> = (1..=20).collect();
+        let (mut pre, mut ino, mut post) = (vec![], vec![], vec![]);
+        tree.root.preorder(&mut pre);
+        tree.root.inorder(&mut ino);
+        tree.root.postorder(&mut post);
+
+        assert_eq!(BinTree::from_preorder_inorder(&pre, &ino), Ok(tree.clone()));
+        assert_eq!(BinTree::from_postorder_inorder(&post, &ino), Ok(tree));
+
+        assert_eq!(
+            BinTree::from_preorder_inorder(&[2, 3, 1], &[3, 2, 1]),
+            Err(TraversalError::Unordered)
+        );
+        assert_eq!(
+            BinTree::from_preorder_inorder(&[1, 1], &[1, 1]),
+            Err(TraversalError::Unordered)
+        );
+        assert_eq!(
+            BinTree::from_preorder_inorder(&[1, 2, 3], &[1, 2, 3]),
+            Err(TraversalError::Unbalanced)
+        );
+        assert_eq!(
+            BinTree::from_postorder_inorder(&[1, 3, 2], &[1, 2, 3]).map(|t| t.size()),
+            Ok(3)
+        );
+
+        // A chain far too deep to build recursively, turned away long before
+        let sorted: Vec<u32> = (0..200_000).collect();
+        let reversed: Vec<u32> = sorted.iter().rev().copied().collect();
+        assert_eq!(
+            BinTree::from_preorder_inorder(&sorted, &sorted),
+            Err(TraversalError::Unbalanced)
+        );
+        assert_eq!(
+            BinTree::from_postorder_inorder(&reversed, &sorted),
+            Err(TraversalError::Unbalanced)
+        );
+    }
+
+    #[test]
+    fn bounding_avl_heights() {
+        let heights: Vec<usize> = [0, 1, 2, 3, 4, 6, 7, 11, 12, 20].map(max_avl_height).into();
+        assert_eq!(heights, [0, 1, 2, 2, 3, 3, 4, 4, 5, 6]);
+
+        // Every tree built by inserting stays within the bound
+        for size in [1, 10, 100, 1000] {
+            let tree: BinTree<u32> = (0..size).collect();
+            assert!(tree.root.height() <= max_avl_height(size as usize));
+        }
+    }
+
     #[test]
     fn send_sync() {
         fn is_send<T: Send>() {}
@@ -893,4 +1481,123 @@ mod tests {
         is_send::<BinTree<i32>>();
         is_sync::<BinTree<i32>>();
     }
+
+    // Inserts (true) and removes (false), over few enough values that they
+    // keep running into each other
+    fn operations() -> impl Strategy<Value = Vec<(bool, u8)>> {
+        prop::collection::vec((any::<bool>(), 0u8..64), 0..200)
+    }
+
+    // Trees of any shape, not just balanced ones
+    fn unbalanced_trees() -> impl Strategy<Value = Tree<u8>> {
+        prop::collection::vec(any::<u8>(), 0..64).prop_map(|items| {
+            let mut tree = Tree::default();
+            items
+                .into_iter()
+                .for_each(|item| tree.add_child_unbalanced(item));
+            tree
+        })
+    }
+
+    fn inorder(tree: &Tree<u8>) -> Vec<u8> {
+        let mut items = vec![];
+        tree.inorder(&mut items);
+        items
+    }
+
+    fn heights_exact(tree: &Tree<u8>) -> bool {
+        let mut recalculated = tree.clone();
+        recalculated.calculate_height();
+        recalculated == *tree
+    }
+
+    proptest! {
+        #[test]
+        fn keeping_order_and_balance(ops in operations()) {
+            let mut tree = BinTree::new();
+            let mut model = BTreeSet::new();
+
+            for (insert, val) in ops {
+                match insert {
+                    true => prop_assert_eq!(tree.insert(val), model.insert(val)),
+                    false => prop_assert_eq!(tree.remove(&val), model.take(&val)),
+                }
+
+                // Drawn on failure, to see where the heights or balance went wrong
+                prop_assert!(heights_exact(&tree.root), "{}", tree.to_dot());
+                prop_assert!(tree.root.is_balanced(), "{}", tree.to_dot());
+            }
+
+            prop_assert_eq!(inorder(&tree.root), model.into_iter().collect::<Vec<_>>());
+            prop_assert_eq!(tree.validate(), Ok(()));
+        }
+
+        #[test]
+        fn rotations_are_inverses(tree in unbalanced_trees()) {
+            let items = inorder(&tree);
+            let (has_left, has_right) = tree
+                .0
+                .as_deref()
+                .map_or((false, false), |node| (node.left.0.is_some(), node.right.0.is_some()));
+
+            let mut rotated = tree.clone();
+            rotated.rotate_left();
+            prop_assert_eq!(inorder(&rotated), items.clone());
+            prop_assert!(heights_exact(&rotated));
+
+            if has_right {
+                rotated.rotate_right();
+                prop_assert_eq!(&rotated, &tree);
+            }
+
+            let mut rotated = tree.clone();
+            rotated.rotate_right();
+            prop_assert_eq!(inorder(&rotated), items);
+            prop_assert!(heights_exact(&rotated));
+
+            if has_left {
+                rotated.rotate_left();
+                prop_assert_eq!(&rotated, &tree);
+            }
+        }
+    }
+
+    #[test]
+    fn exporting_dot() {
+        let tree: BinTree<u8> = [1, 2].into_iter().collect();
+
+        assert_eq!(
+            tree.to_dot(),
+            "digraph tree {
+    node [shape=ellipse, fontname=\"monospace\"];
+    n0 [label=\"1\\nh=2 bf=1\"];
+    n1 [shape=point];
+    n0:sw -> n1;
+    n2 [label=\"2\\nh=1 bf=0\"];
+    n0:se -> n2;
+    n3 [shape=point];
+    n2:sw -> n3;
+    n4 [shape=point];
+    n2:se -> n4;
+}
+"
+        );
+    }
+
+    #[test]
+    fn drawing() {
+        let tree: BinTree<u8> = [2, 1, 3, 4].into_iter().collect();
+
+        assert_eq!(
+            tree.to_string(),
+            "\
+2 (h=3 bf=1)
+├── 1 (h=1 bf=0)
+└── 3 (h=2 bf=1)
+    ├── ·
+    └── 4 (h=1 bf=0)"
+        );
+        assert_eq!(format!("{:#12}", tree).lines().next(), Some("      2 (h=…"));
+        assert_eq!(tree.pretty().depth(0).notes(false).to_string(), "2\n└── …");
+    }
 }
@@ -1,16 +1,23 @@
+#![warn(clippy::undocumented_unsafe_blocks)]
+
 use std::cmp::Ordering;
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 use std::iter::{FromIterator, IntoIterator};
 use std::marker::PhantomData;
-use std::mem;
-use std::ptr::{null, NonNull};
+use std::ops::RangeBounds;
+use std::ptr::NonNull;
+
+use ordered_set::OrderedSet;
+use tree_view::{Node, Shape, TreeView};
+
+#[cfg(test)]
+mod unsafe_paths;
 
 #[derive(PartialEq, Eq, Clone, Debug)]
 struct TreeNode<T: Ord> {
     item: T,
     left: Tree<T>,
     right: Tree<T>,
-    height: usize,
 }
 
 type TreePtr<T> = Option<Box<TreeNode<T>>>;
@@ -24,52 +31,55 @@ impl<T: Ord> TreeNode<T> {
             item,
             left: Tree(None),
             right: Tree(None),
-            height: 1,
         }
     }
 }
 
+// Walks a `&mut` cursor down from one link to a child's, which NLL won't
+// let a plain loop do when the cursor is used after the loop
 struct TreeWalker<'tree, T: Ord> {
-    curr_ptr: NonNull<TreeNode<T>>,
-    _phantom: PhantomData<&'tree mut TreeNode<T>>
+    curr_ptr: NonNull<Tree<T>>,
+    _phantom: PhantomData<&'tree mut Tree<T>>,
 }
 
 impl<'tree, T: Ord> TreeWalker<'tree, T> {
-    fn new(node_ref: &'tree mut TreeNode<T>) -> Self {
+    fn new(tree_ref: &'tree mut Tree<T>) -> Self {
         Self {
-            curr_ptr: NonNull::from(node_ref),
-            _phantom: PhantomData
+            curr_ptr: NonNull::from(tree_ref),
+            _phantom: PhantomData,
         }
     }
 
+    // Steps towards where `val` is or would be, unless already there
     fn walk_to_leaf_by_val(&mut self, val: &T) -> Option<()> {
-        let curr_node = unsafe { self.curr_ptr.as_mut() };
-
-        match val.cmp(&curr_node.item) {
-
-            Ordering::Greater => {
-                if let Some(next) = curr_node.right.0.as_deref_mut() {
-                    self.curr_ptr = NonNull::from(next);
-                    Some(())
-                } else {
-                    None
-                }
-            }
+        // SAFETY: `curr_ptr` was derived from the `&'tree mut` the walker
+        // was made from, and the walker is the only one using it until
+        // `into_inner`. The `&mut` made here ends before the next step.
+        let curr_node = unsafe { self.curr_ptr.as_mut() }.0.as_deref_mut()?;
+
+        let next = match val.cmp(&curr_node.item) {
+            Ordering::Less => &mut curr_node.left,
+            Ordering::Greater => &mut curr_node.right,
+            Ordering::Equal => return None,
+        };
+
+        self.curr_ptr = NonNull::from(next);
+        Some(())
+    }
 
-            Ordering::Less => {
-                if let Some(next) = curr_node.left.0.as_deref_mut() {
-                    self.curr_ptr = NonNull::from(next);
-                    Some(())
-                } else {
-                    None
-                }
-            }
+    // Steps to the left child, unless there isn't one
+    fn walk_left(&mut self) -> Option<()> {
+        // SAFETY: as in `walk_to_leaf_by_val`
+        let curr_node = unsafe { self.curr_ptr.as_mut() }.0.as_deref_mut()?;
 
-            Ordering::Equal => None,
-        }
+        curr_node.left.0.as_ref()?;
+        self.curr_ptr = NonNull::from(&mut curr_node.left);
+        Some(())
     }
 
-    fn into_inner(mut self) -> &'tree mut TreeNode<T> {
+    fn into_inner(mut self) -> &'tree mut Tree<T> {
+        // SAFETY: consuming the walker hands its exclusive access back as
+        // the one reference left for the rest of 'tree
         unsafe { self.curr_ptr.as_mut() }
     }
 }
@@ -101,41 +111,64 @@ impl<T: Ord> Tree<T> {
         tree.0.as_deref()
     }
 
-    fn next_leaf(&mut self, next_item: &T) -> &mut Tree<T> {
+    // The link holding `target`, or the empty one it would go in
+    fn next_leaf(&mut self, target: &T) -> &mut Tree<T> {
+        let mut walker = TreeWalker::new(self);
 
-        let mut walker = TreeWalker::new(&mut self.0.as_deref_mut().unwrap());
+        while let Some(()) = walker.walk_to_leaf_by_val(target) {}
 
-        while let Some(()) = walker.walk_to_leaf_by_val(&next_item) {}
+        walker.into_inner()
+    }
 
-        let leaf = walker.into_inner();
+    // Returns whether `new_item` was added, i.e. wasn't already there
+    fn add_child(&mut self, new_item: T) -> bool {
+        let leaf = self.next_leaf(&new_item);
 
-        while let Some(node) = tree.0.as_deref_mut() {
-            match next_item.cmp(&node.item) {
-                Ordering::Greater => tree = &mut node.right,
-                Ordering::Less => tree = &mut node.left,
-                Ordering::Equal => break,
-            }
+        if leaf.0.is_some() {
+            return false;
         }
 
-        tree
+        *leaf = TreeNode::new(new_item).into();
+        true
+    }
+
+    fn remove_child(&mut self, target: &T) -> Option<T> {
+        self.next_leaf(target).remove_root()
     }
 
-    fn add_child(&mut self, new_item: T) {
-        let mut walker = TreeWalker::new(&mut self.0.as_deref_mut().unwrap());
+    fn remove_leftmost_child(&mut self) -> Option<T> {
+        let mut walker = TreeWalker::new(self);
 
-        while let Some(()) = walker.walk_to_leaf_by_val(&next_item) {}
+        while let Some(()) = walker.walk_left() {}
 
-        let leaf = walker.into_inner();
+        walker.into_inner().remove_root()
+    }
 
-        match new_item.cmp(&new_item) {
-            Ordering::Equal => {},
+    fn remove_root(&mut self) -> Option<T> {
+        let mut node = self.0.take()?;
 
-            Ordering::Greater => {
-                leaf.right.0.insert(TreeNode::new(new_item).into());
+        match (node.left.0.is_some(), node.right.0.is_some()) {
+            // Keep the node where it is and trade its item for its successor's
+            (true, true) => {
+                let successor = node
+                    .right
+                    .remove_leftmost_child()
+                    .expect("right subtree is present");
+                let removed = std::mem::replace(&mut node.item, successor);
+
+                self.0 = Some(node);
+                Some(removed)
+            }
+
+            // Otherwise let its only child (if any) move up
+            (true, false) => {
+                *self = std::mem::take(&mut node.left);
+                Some(node.item)
             }
 
-            Ordering::Less => {
-                leaf.right.0.insert(TreeNode::new(new_item).into());
+            (false, _) => {
+                *self = std::mem::take(&mut node.right);
+                Some(node.item)
             }
         }
     }
@@ -161,9 +194,16 @@ impl<T: Ord> BinTree<T> {
         self.size
     }
 
-    #[inline]
+    /// Frees the nodes one at a time, so that not even a tree degenerated
+    /// into a long chain can overflow the stack.
     pub fn clear(&mut self) {
-        _ = self.root.0.take();
+        let mut nodes: Vec<_> = self.root.0.take().into_iter().collect();
+
+        while let Some(mut node) = nodes.pop() {
+            nodes.extend(node.left.0.take());
+            nodes.extend(node.right.0.take());
+        }
+
         self.size = 0;
     }
 
@@ -171,4 +211,284 @@ impl<T: Ord> BinTree<T> {
     pub fn search(&self, target: &T) -> Option<&T> {
         self.root.search(target).map(|node| &node.item)
     }
-}
\ No newline at end of file
+
+    /// Adds `new_item` unless the tree already holds it, returning whether
+    /// it was added.
+    #[inline]
+    pub fn insert(&mut self, new_item: T) -> bool {
+        let added = self.root.add_child(new_item);
+        self.size += added as usize;
+        added
+    }
+
+    #[inline]
+    pub fn remove(&mut self, target: &T) -> Option<T> {
+        let removed = self.root.remove_child(target)?;
+        self.size -= 1;
+        Some(removed)
+    }
+
+    #[inline]
+    pub fn remove_inorder(&mut self) -> Option<T> {
+        let removed = self.root.remove_leftmost_child()?;
+        self.size -= 1;
+        Some(removed)
+    }
+}
+
+impl<T: Ord> Drop for BinTree<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+pub struct InorderIntoIter<T: Ord>(BinTree<T>);
+
+impl<T: Ord> Iterator for InorderIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.remove_inorder()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.size, Some(self.0.size))
+    }
+}
+
+impl<T: Ord> ExactSizeIterator for InorderIntoIter<T> {}
+
+pub struct InorderIter<'tree, T: Ord> {
+    walk: RangeIter<'tree, T>,
+    remaining: usize,
+}
+
+/// An in-order walk that can start partway through, so without the exact
+/// count of items left that [`InorderIter`] keeps.
+pub struct RangeIter<'tree, T: Ord> {
+    curr_node: Option<&'tree TreeNode<T>>,
+    node_stack: Vec<&'tree TreeNode<T>>,
+}
+
+impl<'tree, T: Ord> BinTree<T> {
+    pub fn iter(&'tree self) -> InorderIter<'tree, T> {
+        InorderIter {
+            walk: RangeIter {
+                curr_node: self.root.0.as_deref(),
+                node_stack: vec![],
+            },
+            remaining: self.size,
+        }
+    }
+
+    /// Yields the items within `range` in ascending order, walking down to
+    /// the first of them like `search` would.
+    pub fn range<R>(&'tree self, range: R) -> ordered_set::Range<RangeIter<'tree, T>, R>
+    where
+        R: RangeBounds<T>,
+    {
+        let node_stack = ordered_set::seek(
+            self.root.0.as_deref(),
+            |node| [node.left.0.as_deref(), node.right.0.as_deref()],
+            |node| &node.item,
+            range.start_bound(),
+        );
+
+        let walk = RangeIter {
+            curr_node: None,
+            node_stack,
+        };
+        ordered_set::Range::new(walk, range)
+    }
+}
+
+impl<'tree, T: Ord> Iterator for RangeIter<'tree, T> {
+    type Item = &'tree T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.curr_node {
+            self.curr_node = node.left.0.as_deref();
+            self.node_stack.push(node);
+        }
+
+        let popped_node = self.node_stack.pop()?;
+        self.curr_node = popped_node.right.0.as_deref();
+        Some(&popped_node.item)
+    }
+}
+
+impl<'tree, T: Ord> Iterator for InorderIter<'tree, T> {
+    type Item = &'tree T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.walk.next()?;
+        self.remaining -= 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'tree, T: Ord> ExactSizeIterator for InorderIter<'tree, T> {}
+
+impl<T: Ord> IntoIterator for BinTree<T> {
+    type Item = T;
+    type IntoIter = InorderIntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        InorderIntoIter(self)
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BinTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        iter.into_iter().for_each(|item| {
+            tree.insert(item);
+        });
+        tree
+    }
+}
+
+impl<T: Ord> OrderedSet for BinTree<T> {
+    type Item = T;
+    type ItemRef<'set>
+        = &'set T
+    where
+        Self: 'set;
+    type Iter<'set>
+        = InorderIter<'set, T>
+    where
+        Self: 'set;
+    type Range<'set, R>
+        = ordered_set::Range<RangeIter<'set, T>, R>
+    where
+        Self: 'set,
+        R: RangeBounds<T>;
+
+    #[inline]
+    fn insert(&mut self, item: T) -> bool {
+        BinTree::insert(self, item)
+    }
+
+    #[inline]
+    fn remove(&mut self, item: &T) -> Option<T> {
+        BinTree::remove(self, item)
+    }
+
+    #[inline]
+    fn contains(&self, item: &T) -> bool {
+        self.search(item).is_some()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.size()
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+
+    #[inline]
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_, R> {
+        self.range(range)
+    }
+}
+
+impl<T: Ord + Debug> TreeView for BinTree<T> {
+    fn shape(&self) -> Shape {
+        Shape::new(
+            self.root.0.as_deref(),
+            |node| [node.left.0.as_deref(), node.right.0.as_deref()],
+            |node| Node::new(&node.item),
+        )
+    }
+}
+
+impl<T: Ord + Debug> fmt::Display for BinTree<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.pretty(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ordered_set::conformance_suite!(BinTree<u32>);
+
+    #[test]
+    fn inserting_and_searching() {
+        let mut tree: BinTree<u32> = [50, 25, 75, 10, 30].into_iter().collect();
+
+        assert_eq!(tree.size(), 5);
+        assert!(!tree.insert(30));
+        assert!(tree.insert(60));
+        assert_eq!(tree.size(), 6);
+
+        assert_eq!(tree.search(&60), Some(&60));
+        assert_eq!(tree.search(&30), Some(&30));
+        assert_eq!(tree.search(&40), None);
+        assert!(tree.iter().copied().eq([10, 25, 30, 50, 60, 75]));
+    }
+
+    #[test]
+    fn ranging() {
+        let tree: BinTree<u32> = [50, 25, 75, 10, 30, 60].into_iter().collect();
+
+        assert!(tree.range(26..=60).copied().eq([30, 50, 60]));
+        assert!(tree.range(..25).copied().eq([10]));
+        assert!(tree.range(76..).next().is_none());
+
+        // Starting partway through, unlike `iter`, whose count stays exact
+        let mut iter = tree.iter();
+        iter.next();
+        assert_eq!(iter.len(), 5);
+    }
+
+    #[test]
+    fn removing() {
+        let mut tree: BinTree<u32> = [50, 25, 75, 10, 30, 60, 80, 27].into_iter().collect();
+
+        // Two children, one child, then a leaf
+        assert_eq!(tree.remove(&25), Some(25));
+        assert_eq!(tree.remove(&75), Some(75));
+        assert_eq!(tree.remove(&80), Some(80));
+        assert_eq!(tree.remove(&80), None);
+        assert_eq!(tree.size(), 5);
+        assert!(tree.iter().copied().eq([10, 27, 30, 50, 60]));
+
+        assert_eq!(tree.remove(&50), Some(50));
+        assert_eq!(tree.remove_inorder(), Some(10));
+        assert!(tree.clone().into_iter().eq([27, 30, 60]));
+
+        tree.clear();
+        assert_eq!(tree, BinTree::new());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "too slow under Miri")]
+    fn degenerate_trees() {
+        // Every node hangs off the previous one's right
+        let mut tree: BinTree<u32> = (0..5_000).collect();
+
+        assert_eq!(tree.iter().len(), 5_000);
+        assert_eq!(tree.search(&4_999), Some(&4_999));
+        assert_eq!(tree.remove(&4_999), Some(4_999));
+        assert_eq!(tree.remove_inorder(), Some(0));
+    }
+
+    #[test]
+    fn exporting_dot() {
+        let dot = [2, 1, 3].into_iter().collect::<BinTree<u32>>().to_dot();
+
+        assert!(dot.contains("n0 [label=\"2\"];"));
+        assert!(dot.contains("n0:sw -> n1;\n    n2 [shape=point];"));
+        assert!(dot.contains("n4 [label=\"3\"];\n    n0:se -> n4;"));
+    }
+}
@@ -0,0 +1,142 @@
+//! Tests walking every path through `TreeWalker`, the crate's only unsafe
+//! code, both directly and through the tree operations built on it. They're
+//! kept small enough for Miri, under both of its aliasing models, and are
+//! meant for the address and leak sanitizers too:
+//!
+//! ```sh
+//! cargo +nightly miri test -p box_bintree_iterative unsafe_paths
+//! MIRIFLAGS=-Zmiri-tree-borrows cargo +nightly miri test -p box_bintree_iterative unsafe_paths
+//! RUSTFLAGS=-Zsanitizer=address cargo +nightly test -p box_bintree_iterative --lib \
+//!     --target x86_64-unknown-linux-gnu unsafe_paths
+//! RUSTFLAGS=-Zsanitizer=leak cargo +nightly test -p box_bintree_iterative --lib \
+//!     --target x86_64-unknown-linux-gnu unsafe_paths
+//! ```
+
+use super::*;
+
+//            50
+//          /    \
+//        20      80
+//       /  \    /  \
+//     10   30  60   90
+//            \
+//            40
+const SHAPED: [u32; 8] = [50, 20, 80, 10, 30, 60, 90, 40];
+
+fn shaped() -> Tree<String> {
+    let mut tree = Tree::default();
+
+    for key in SHAPED {
+        tree.add_child(key.to_string());
+    }
+
+    tree
+}
+
+fn item(tree: &Tree<String>) -> Option<&str> {
+    tree.0.as_ref().map(|node| node.item.as_str())
+}
+
+#[test]
+fn walking_an_empty_tree() {
+    let mut tree: Tree<String> = Tree::default();
+
+    let mut walker = TreeWalker::new(&mut tree);
+    assert_eq!(walker.walk_to_leaf_by_val(&"1".to_string()), None);
+    assert_eq!(walker.walk_left(), None);
+
+    // Handed back without having moved, and written through
+    *walker.into_inner() = TreeNode::new("1".to_string()).into();
+    assert_eq!(item(&tree), Some("1"));
+}
+
+#[test]
+fn walking_by_value() {
+    let mut tree = shaped();
+
+    // Found at the root, found further down, and the empty link a missing
+    // value would go in
+    for (target, steps, found) in [("50", 0, true), ("40", 3, true), ("35", 4, false)] {
+        let mut walker = TreeWalker::new(&mut tree);
+        let mut taken = 0;
+
+        while let Some(()) = walker.walk_to_leaf_by_val(&target.to_string()) {
+            taken += 1;
+        }
+
+        assert_eq!(taken, steps, "{}", target);
+        assert_eq!(item(walker.into_inner()).is_some(), found, "{}", target);
+    }
+
+    // A walker dropped part of the way down leaves the tree usable
+    {
+        let mut walker = TreeWalker::new(&mut tree);
+        walker.walk_to_leaf_by_val(&"90".to_string());
+    }
+    assert!(tree.search(&"90".to_string()).is_some());
+}
+
+#[test]
+fn walking_left() {
+    let mut tree = shaped();
+    let mut walker = TreeWalker::new(&mut tree);
+
+    assert_eq!(walker.walk_left(), Some(()));
+    assert_eq!(walker.walk_left(), Some(()));
+    assert_eq!(walker.walk_left(), None);
+
+    // The link handed back is the leftmost node's own, still in the tree
+    let leftmost = walker.into_inner();
+    assert_eq!(leftmost.remove_root().as_deref(), Some("10"));
+    assert_eq!(tree.search(&"10".to_string()), None);
+
+    // A root without a left child stays put
+    let mut tree = Tree::from(TreeNode::new("1".to_string()));
+    tree.add_child("2".to_string());
+    let mut walker = TreeWalker::new(&mut tree);
+    assert_eq!(walker.walk_left(), None);
+    assert_eq!(item(walker.into_inner()), Some("1"));
+}
+
+#[test]
+fn inserting_and_removing() {
+    let mut tree: BinTree<String> = SHAPED.iter().map(u32::to_string).collect();
+
+    // Duplicates stop the walk at the node already there
+    assert!(!tree.insert("30".to_string()));
+    assert!(tree.insert("35".to_string()));
+
+    // A node with a right child only, one with a left child only, two
+    // children with the successor right below, then further down, then
+    // a leaf and the root with two children, one and none
+    let order = ["30", "40", "20", "50", "90", "60", "80", "35", "10"];
+
+    for (removed, key) in order.into_iter().enumerate() {
+        assert_eq!(tree.remove(&key.to_string()).as_deref(), Some(key));
+        assert_eq!(tree.remove(&key.to_string()), None);
+        assert_eq!(tree.size(), order.len() - removed - 1);
+        assert_eq!(tree.iter().count(), tree.size());
+    }
+
+    assert_eq!(tree, BinTree::new());
+    assert_eq!(tree.remove_inorder(), None);
+}
+
+#[test]
+fn consuming_and_dropping() {
+    let tree: BinTree<String> = SHAPED.iter().map(u32::to_string).collect();
+    let mut sorted: Vec<String> = tree.iter().cloned().collect();
+
+    // Every item freed once, whether taken out or left behind
+    let mut iter = tree.clone().into_iter();
+    assert_eq!(iter.next(), Some(sorted.remove(0)));
+    assert_eq!(iter.len(), sorted.len());
+    drop(iter);
+
+    assert!(tree.clone().into_iter().skip(1).eq(sorted));
+
+    let mut cleared = tree;
+    cleared.clear();
+    assert_eq!(cleared.iter().next(), None);
+    assert!(cleared.insert("1".to_string()));
+}
@@ -0,0 +1,175 @@
+//! Differential fuzzing of every tree in the workspace against `BTreeSet`.
+//!
+//! The input is decoded into a sequence of operations, which are applied to
+//! each tree alongside a `BTreeSet`. After every step the two have to agree
+//! on the operation's result, their lengths, their first and last items and
+//! their whole iteration order, and trees that can check their own
+//! invariants (the AVL and red-black ones) have to pass them.
+//!
+//! With cargo-fuzz, from the repository's root:
+//!
+//! ```sh
+//! cargo +nightly fuzz run differential
+//! ```
+//!
+//! Without it, e.g. offline or on stable, the same checks run over a fixed
+//! batch of generated inputs:
+//!
+//! ```sh
+//! cd fuzz && cargo test --release
+//! ```
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use ordered_set::OrderedSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Insert(u8),
+    Remove(u8),
+    Contains(u8),
+    // Both ends included
+    Range(u8, u8),
+}
+
+impl Op {
+    /// Two bytes per operation: which one it is, then its value. Values are
+    /// kept to a byte so that inserts and removes keep running into each
+    /// other. A trailing odd byte is ignored.
+    pub fn decode(data: &[u8]) -> impl Iterator<Item = Op> + '_ {
+        data.chunks_exact(2).map(|pair| match pair[0] % 4 {
+            0 => Op::Insert(pair[1]),
+            1 => Op::Remove(pair[1]),
+            2 => Op::Contains(pair[1]),
+            _ => Op::Range(pair[1], pair[1].saturating_add(pair[0] >> 2)),
+        })
+    }
+}
+
+/// Runs `data` against every tree, panicking at the first disagreement.
+pub fn run(data: &[u8]) {
+    let ops: Vec<Op> = Op::decode(data).collect();
+
+    check::<box_bintree::BinTree<u8>>("box_bintree", &ops, |tree| {
+        assert_eq!(tree.validate(), Ok(()));
+    });
+    check::<box_bintree_iterative::BinTree<u8>>("box_bintree_iterative", &ops, |_| {});
+    check::<raw_bintree::BinaryTree<u8>>("raw_bintree", &ops, |_| {});
+    check::<leetcode_bintree::BinaryTree<u8>>("leetcode_bintree", &ops, |_| {});
+    check::<rb_bintree::RbTree<u8>>("rb_bintree", &ops, |tree| {
+        assert_eq!(tree.validate(), Ok(()));
+    });
+    check::<splay_bintree::SplayTree<u8>>("splay_bintree", &ops, |_| {});
+    check::<treap_bintree::Treap<u8>>("treap_bintree", &ops, |_| {});
+    check::<persistent_bintree::RcTree<u8>>("persistent_bintree", &ops, |_| {});
+}
+
+fn check<S>(name: &str, ops: &[Op], validate: impl Fn(&S))
+where
+    S: OrderedSet<Item = u8> + Default,
+{
+    let mut set = S::default();
+    let mut model = BTreeSet::new();
+
+    for (step, &op) in ops.iter().enumerate() {
+        let context = Context { name, step, op };
+
+        match op {
+            Op::Insert(val) => assert_eq!(set.insert(val), model.insert(val), "{}", context),
+            Op::Remove(val) => assert_eq!(set.remove(&val), model.take(&val), "{}", context),
+            Op::Contains(val) => {
+                assert_eq!(set.contains(&val), model.contains(&val), "{}", context)
+            }
+            Op::Range(lo, hi) => assert!(
+                set.range(lo..=hi)
+                    .map(|item| *item)
+                    .eq(model.range(lo..=hi).copied()),
+                "{}",
+                context
+            ),
+        }
+
+        assert_eq!(set.len(), model.len(), "{}", context);
+        assert_eq!(
+            set.first().map(|item| *item),
+            model.first().copied(),
+            "{}",
+            context
+        );
+        assert_eq!(
+            set.last().map(|item| *item),
+            model.last().copied(),
+            "{}",
+            context
+        );
+        assert!(
+            set.iter().map(|item| *item).eq(model.iter().copied()),
+            "{}",
+            context
+        );
+        validate(&set);
+    }
+}
+
+// Where a check failed, for the panic message
+struct Context<'a> {
+    name: &'a str,
+    step: usize,
+    op: Op,
+}
+
+impl fmt::Display for Context<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}, step {}: {:?}", self.name, self.step, self.op)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // xorshift, so that every run sees the same inputs
+    fn inputs(count: usize) -> impl Iterator<Item = Vec<u8>> {
+        let mut state = 0x9e37_79b9_u32;
+
+        (0..count).map(move |i| {
+            (0..(i % 64) * 16)
+                .map(|_| {
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    state as u8
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn decoding() {
+        let ops: Vec<Op> = Op::decode(&[0, 7, 1, 7, 2, 7, 3 | 4 << 2, 250, 0]).collect();
+
+        assert_eq!(
+            ops,
+            [
+                Op::Insert(7),
+                Op::Remove(7),
+                Op::Contains(7),
+                Op::Range(250, 254)
+            ]
+        );
+    }
+
+    #[test]
+    fn generated_inputs() {
+        inputs(500).for_each(|data| run(&data));
+    }
+
+    #[test]
+    fn filling_and_emptying() {
+        let fill = (0..=255).flat_map(|val| [0, val]);
+        let empty = (0..=255).rev().flat_map(|val| [1, val]);
+
+        run(&fill.chain(empty).collect::<Vec<u8>>());
+    }
+}
@@ -0,0 +1,397 @@
+//! The classic interview problems over LeetCode's tree shape.
+//!
+//! Everything here walks the tree with an explicit stack or queue, so
+//! degenerate (list-like) inputs don't overflow the call stack.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::iter::Sum;
+use std::mem;
+use std::rc::Rc;
+
+use crate::{Tree, TreeNode};
+
+type Link<T> = Rc<RefCell<TreeNode<T>>>;
+
+impl<T: Ord> Tree<T> {
+    // Folds the tree bottom up: `leaf` stands in for every missing child,
+    // and `f` combines a node's item with the results of its subtrees.
+    fn fold_postorder<R>(&self, leaf: impl Fn() -> R, mut f: impl FnMut(&T, R, R) -> R) -> R {
+        enum Step<T: Ord> {
+            Visit(Option<Link<T>>),
+            Combine(Link<T>),
+        }
+
+        let mut stack = vec![Step::Visit(self.0.clone())];
+        let mut results = Vec::new();
+
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Visit(None) => results.push(leaf()),
+
+                Step::Visit(Some(rc)) => {
+                    let node = rc.borrow();
+                    let (left, right) = (node.left.0.clone(), node.right.0.clone());
+                    drop(node);
+
+                    stack.push(Step::Combine(rc));
+                    stack.push(Step::Visit(right));
+                    stack.push(Step::Visit(left));
+                }
+
+                Step::Combine(rc) => {
+                    let right = results.pop().unwrap();
+                    let left = results.pop().unwrap();
+                    results.push(f(&rc.borrow().item, left, right));
+                }
+            }
+        }
+
+        results.pop().unwrap()
+    }
+
+    /// Number of nodes on the longest root-to-leaf path.
+    pub fn max_depth(&self) -> usize {
+        self.fold_postorder(|| 0, |_, left, right| 1 + left.max(right))
+    }
+
+    /// Number of edges on the longest path between any two nodes.
+    pub fn diameter(&self) -> usize {
+        let mut longest = 0;
+
+        self.fold_postorder(
+            || 0,
+            |_, left: usize, right: usize| {
+                longest = longest.max(left + right);
+                1 + left.max(right)
+            },
+        );
+
+        longest
+    }
+
+    /// Whether every node's subtrees differ in depth by at most one.
+    pub fn is_balanced(&self) -> bool {
+        self.fold_postorder(
+            || Some(0),
+            |_, left: Option<usize>, right: Option<usize>| {
+                let (left, right) = (left?, right?);
+                (left.abs_diff(right) <= 1).then(|| 1 + left.max(right))
+            },
+        )
+        .is_some()
+    }
+
+    /// Whether an inorder walk visits strictly increasing items.
+    pub fn is_valid_bst(&self) -> bool {
+        let mut stack: Vec<Link<T>> = Vec::new();
+        let mut next = self.0.clone();
+        let mut prev: Option<Link<T>> = None;
+
+        loop {
+            while let Some(rc) = next {
+                next = rc.borrow().left.0.clone();
+                stack.push(rc);
+            }
+
+            let Some(rc) = stack.pop() else {
+                return true;
+            };
+
+            if prev.is_some_and(|prev| prev.borrow().item >= rc.borrow().item) {
+                return false;
+            }
+
+            next = rc.borrow().right.0.clone();
+            prev = Some(rc);
+        }
+    }
+
+    /// Whether the tree is a mirror image of itself.
+    pub fn is_symmetric(&self) -> bool {
+        let Some(root) = self.0.as_ref() else {
+            return true;
+        };
+
+        let root = root.borrow();
+        let mut pairs = VecDeque::from([(root.left.0.clone(), root.right.0.clone())]);
+
+        while let Some(pair) = pairs.pop_front() {
+            match pair {
+                (None, None) => continue,
+
+                (Some(a), Some(b)) => {
+                    let (a, b) = (a.borrow(), b.borrow());
+
+                    if a.item != b.item {
+                        return false;
+                    }
+
+                    pairs.push_back((a.left.0.clone(), b.right.0.clone()));
+                    pairs.push_back((a.right.0.clone(), b.left.0.clone()));
+                }
+
+                _ => return false,
+            }
+        }
+
+        true
+    }
+
+    /// Mirrors the tree in place, swapping every node's children.
+    pub fn invert(&mut self) {
+        let mut stack: Vec<Link<T>> = self.0.iter().cloned().collect();
+
+        while let Some(rc) = stack.pop() {
+            let mut node = rc.borrow_mut();
+            let node = &mut *node;
+
+            mem::swap(&mut node.left, &mut node.right);
+            stack.extend(node.left.0.clone());
+            stack.extend(node.right.0.clone());
+        }
+    }
+}
+
+impl<T: Ord + Clone> Tree<T> {
+    /// Items level by level, top to bottom and left to right.
+    pub fn level_order(&self) -> Vec<Vec<T>> {
+        let mut levels = Vec::new();
+        let mut level: Vec<Link<T>> = self.0.iter().cloned().collect();
+
+        while !level.is_empty() {
+            let mut next = Vec::new();
+
+            levels.push(
+                level
+                    .iter()
+                    .map(|rc| {
+                        let node = rc.borrow();
+                        next.extend(node.left.0.clone());
+                        next.extend(node.right.0.clone());
+                        node.item.clone()
+                    })
+                    .collect(),
+            );
+
+            level = next;
+        }
+
+        levels
+    }
+
+    /// Like `level_order`, but every other level reads right to left.
+    pub fn zigzag_level_order(&self) -> Vec<Vec<T>> {
+        let mut levels = self.level_order();
+
+        for level in levels.iter_mut().skip(1).step_by(2) {
+            level.reverse();
+        }
+
+        levels
+    }
+
+    /// The last item of every level, i.e. what's visible from the right.
+    pub fn right_side_view(&self) -> Vec<T> {
+        self.level_order()
+            .into_iter()
+            .filter_map(|mut level| level.pop())
+            .collect()
+    }
+
+    /// The deepest item that has both `p` and `q` in its subtree (a node
+    /// counts as its own descendant), or `None` if either is missing.
+    pub fn lowest_common_ancestor(&self, p: &T, q: &T) -> Option<T> {
+        // (found p, found q, ancestor once both have been found)
+        let (_, _, ancestor) = self.fold_postorder(
+            || (false, false, None),
+            |item, left, right| {
+                if left.2.is_some() || right.2.is_some() {
+                    return (true, true, left.2.or(right.2));
+                }
+
+                let found_p = left.0 || right.0 || item == p;
+                let found_q = left.1 || right.1 || item == q;
+                let ancestor = (found_p && found_q).then(|| item.clone());
+
+                (found_p, found_q, ancestor)
+            },
+        );
+
+        ancestor
+    }
+
+    /// Every path from the root down to a leaf, leftmost first.
+    pub fn root_to_leaf_paths(&self) -> Vec<Vec<T>> {
+        let mut paths = Vec::new();
+        let mut stack: Vec<(Link<T>, usize)> = self.0.iter().map(|rc| (rc.clone(), 0)).collect();
+        let mut path = Vec::new();
+
+        while let Some((rc, depth)) = stack.pop() {
+            let node = rc.borrow();
+
+            path.truncate(depth);
+            path.push(node.item.clone());
+
+            if node.left.0.is_none() && node.right.0.is_none() {
+                paths.push(path.clone());
+            }
+
+            for child in [&node.right, &node.left] {
+                stack.extend(child.0.iter().map(|rc| (rc.clone(), depth + 1)));
+            }
+        }
+
+        paths
+    }
+
+    /// The root-to-leaf paths whose items add up to `target`.
+    pub fn paths_with_sum(&self, target: T) -> Vec<Vec<T>>
+    where
+        T: Sum,
+    {
+        self.root_to_leaf_paths()
+            .into_iter()
+            .filter(|path| path.iter().cloned().sum::<T>() == target)
+            .collect()
+    }
+
+    /// Whether any root-to-leaf path adds up to `target`.
+    #[inline]
+    pub fn has_path_sum(&self, target: T) -> bool
+    where
+        T: Sum,
+    {
+        !self.paths_with_sum(target).is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Tree;
+
+    fn tree(s: &str) -> Tree<i32> {
+        s.parse().unwrap()
+    }
+
+    fn chain(len: i32) -> Tree<i32> {
+        let items: Vec<String> = (0..len)
+            .map(|i| match i {
+                0 => "0".to_string(),
+                _ => format!("null,{}", i),
+            })
+            .collect();
+
+        tree(&format!("[{}]", items.join(",")))
+    }
+
+    #[test]
+    fn depth_and_diameter() {
+        assert_eq!(tree("[]").max_depth(), 0);
+        assert_eq!(tree("[1]").max_depth(), 1);
+        assert_eq!(tree("[3,9,20,null,null,15,7]").max_depth(), 3);
+
+        assert_eq!(tree("[]").diameter(), 0);
+        assert_eq!(tree("[1,2,3,4,5]").diameter(), 3);
+        // The longest path doesn't go through the root
+        assert_eq!(
+            tree("[1,2,null,3,4,5,null,null,6,7,null,null,8]").diameter(),
+            6
+        );
+
+        let deep = chain(50_000);
+        assert_eq!(deep.max_depth(), 50_000);
+        assert_eq!(deep.diameter(), 49_999);
+    }
+
+    #[test]
+    fn balance() {
+        assert!(tree("[]").is_balanced());
+        assert!(tree("[3,9,20,null,null,15,7]").is_balanced());
+        assert!(!tree("[1,2,2,3,3,null,null,4,4]").is_balanced());
+        // Both subtrees of the root have depth 3 but neither is balanced
+        assert!(!tree("[1,2,2,3,null,null,3,4,null,null,4]").is_balanced());
+        assert!(!chain(3).is_balanced());
+    }
+
+    #[test]
+    fn bst_validation() {
+        assert!(tree("[]").is_valid_bst());
+        assert!(tree("[2,1,3]").is_valid_bst());
+        assert!(!tree("[5,1,4,null,null,3,6]").is_valid_bst());
+        // Locally fine, but 3 sits in 5's right subtree
+        assert!(!tree("[5,4,6,null,null,3,7]").is_valid_bst());
+        // Duplicates aren't allowed
+        assert!(!tree("[2,2,2]").is_valid_bst());
+        assert!(chain(10_000).is_valid_bst());
+    }
+
+    #[test]
+    fn lowest_common_ancestors() {
+        let t = tree("[3,5,1,6,2,0,8,null,null,7,4]");
+
+        assert_eq!(t.lowest_common_ancestor(&5, &1), Some(3));
+        assert_eq!(t.lowest_common_ancestor(&5, &4), Some(5));
+        assert_eq!(t.lowest_common_ancestor(&7, &4), Some(2));
+        assert_eq!(t.lowest_common_ancestor(&6, &6), Some(6));
+        assert_eq!(t.lowest_common_ancestor(&6, &42), None);
+        assert_eq!(tree("[]").lowest_common_ancestor(&1, &1), None);
+    }
+
+    #[test]
+    fn paths() {
+        let t = tree("[5,4,8,11,null,13,4,7,2,null,null,5,1]");
+
+        assert_eq!(
+            t.root_to_leaf_paths(),
+            [
+                vec![5, 4, 11, 7],
+                vec![5, 4, 11, 2],
+                vec![5, 8, 13],
+                vec![5, 8, 4, 5],
+                vec![5, 8, 4, 1],
+            ]
+        );
+        assert_eq!(t.paths_with_sum(22), [vec![5, 4, 11, 2], vec![5, 8, 4, 5]]);
+        assert!(t.has_path_sum(26));
+        // 5 + 4 = 9 stops at an inner node, not a leaf
+        assert!(!t.has_path_sum(9));
+
+        assert!(tree("[]").root_to_leaf_paths().is_empty());
+        assert!(!tree("[]").has_path_sum(0));
+        assert!(tree("[-2,null,-3]").has_path_sum(-5));
+    }
+
+    #[test]
+    fn inverting_and_symmetry() {
+        let mut t = tree("[4,2,7,1,3,6,9]");
+        t.invert();
+        assert_eq!(t.to_string(), "[4,7,2,9,6,3,1]");
+
+        let mut t = tree("[1,2]");
+        t.invert();
+        assert_eq!(t.to_string(), "[1,null,2]");
+
+        assert!(tree("[]").is_symmetric());
+        assert!(tree("[1,2,2,3,4,4,3]").is_symmetric());
+        assert!(!tree("[1,2,2,null,3,null,3]").is_symmetric());
+        assert!(!tree("[1,2,3]").is_symmetric());
+    }
+
+    #[test]
+    fn level_views() {
+        let t = tree("[3,9,20,null,null,15,7]");
+
+        assert_eq!(t.level_order(), [vec![3], vec![9, 20], vec![15, 7]]);
+        assert_eq!(t.zigzag_level_order(), [vec![3], vec![20, 9], vec![15, 7]]);
+        assert_eq!(t.right_side_view(), [3, 20, 7]);
+
+        // A left-hanging node is still visible when nothing is to its right
+        assert_eq!(tree("[1,2,3,4]").right_side_view(), [1, 3, 4]);
+        assert_eq!(
+            tree("[1,2,3,4,null,null,5]").zigzag_level_order(),
+            [vec![1], vec![3, 2], vec![4, 5]]
+        );
+        assert!(tree("[]").level_order().is_empty());
+    }
+}
@@ -0,0 +1,253 @@
+//! LeetCode's level-order notation, e.g. `[3,9,20,null,null,15,7]`.
+//!
+//! Every present node consumes the next two tokens as its left and right
+//! children, `null` marks a missing child, and trailing `null`s are dropped
+//! when printing.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use crate::{Tree, TreeNode};
+
+const NULL: &str = "null";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseTreeError {
+    /// The input isn't wrapped in `[` and `]`.
+    MissingBrackets,
+    /// Nothing between two commas, e.g. `[1,,2]`.
+    EmptyToken { index: usize },
+    /// A token that isn't `null` and doesn't parse as an item.
+    InvalidValue { index: usize, token: String },
+    /// A token left over after every present node has had both children
+    /// assigned, e.g. `[null,1]` or `[1,null,null,2]`.
+    Orphan { index: usize },
+}
+
+impl fmt::Display for ParseTreeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingBrackets => write!(f, "tree must be enclosed in '[' and ']'"),
+            Self::EmptyToken { index } => write!(f, "empty token at position {}", index),
+            Self::InvalidValue { index, token } => {
+                write!(f, "invalid value {:?} at position {}", token, index)
+            }
+            Self::Orphan { index } => {
+                write!(f, "token at position {} has no parent to attach to", index)
+            }
+        }
+    }
+}
+
+impl Error for ParseTreeError {}
+
+impl<T: Ord + FromStr> FromStr for Tree<T> {
+    type Err = ParseTreeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let inner = s
+            .trim()
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+            .ok_or(ParseTreeError::MissingBrackets)?;
+
+        if inner.trim().is_empty() {
+            return Ok(Tree::default());
+        }
+
+        let mut tokens = inner.split(',').map(str::trim).enumerate();
+        let parse_token = |(index, token): (usize, &str)| match token {
+            "" => Err(ParseTreeError::EmptyToken { index }),
+            NULL => Ok(Tree::default()),
+            _ => token
+                .parse()
+                .map(|item| Tree::from(TreeNode::new(item)))
+                .map_err(|_| ParseTreeError::InvalidValue {
+                    index,
+                    token: token.to_string(),
+                }),
+        };
+
+        // split always yields at least one token
+        let root = parse_token(tokens.next().unwrap())?;
+        let mut parents = VecDeque::new();
+        parents.extend(root.0.clone());
+
+        while let Some(token) = tokens.next() {
+            let Some(parent_rc) = parents.pop_front() else {
+                return Err(ParseTreeError::Orphan { index: token.0 });
+            };
+            let mut parent = parent_rc.borrow_mut();
+
+            parent.left = parse_token(token)?;
+            parent.left.set_parent(Rc::downgrade(&parent_rc));
+            parents.extend(parent.left.0.clone());
+
+            if let Some(token) = tokens.next() {
+                parent.right = parse_token(token)?;
+                parent.right.set_parent(Rc::downgrade(&parent_rc));
+                parents.extend(parent.right.0.clone());
+            }
+        }
+
+        Ok(root)
+    }
+}
+
+impl<T: Ord + fmt::Display> fmt::Display for Tree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut slots: Vec<Option<Rc<RefCell<TreeNode<T>>>>> = vec![self.0.clone()];
+        let mut next = 0;
+
+        while next < slots.len() {
+            if let Some(node) = slots[next].clone() {
+                let node = node.borrow();
+                slots.push(node.left.0.clone());
+                slots.push(node.right.0.clone());
+            }
+            next += 1;
+        }
+
+        while let Some(None) = slots.last() {
+            slots.pop();
+        }
+
+        write!(f, "[")?;
+        for (i, slot) in slots.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            match slot {
+                Some(node) => write!(f, "{}", node.borrow().item)?,
+                None => write!(f, "{}", NULL)?,
+            }
+        }
+        write!(f, "]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BinaryTree;
+    use proptest::prelude::*;
+
+    fn parse(s: &str) -> Result<Tree<i32>, ParseTreeError> {
+        s.parse()
+    }
+
+    #[derive(Debug, Clone)]
+    enum Shape {
+        Leaf,
+        Node(i32, Box<Shape>, Box<Shape>),
+    }
+
+    impl Shape {
+        fn into_tree(self) -> Tree<i32> {
+            match self {
+                Shape::Leaf => Tree::default(),
+                Shape::Node(item, left, right) => {
+                    Tree::from_parts(item, left.into_tree(), right.into_tree())
+                }
+            }
+        }
+    }
+
+    fn shapes() -> impl Strategy<Value = Shape> {
+        Just(Shape::Leaf).prop_recursive(8, 64, 2, |inner| {
+            (any::<i32>(), inner.clone(), inner)
+                .prop_map(|(item, left, right)| Shape::Node(item, Box::new(left), Box::new(right)))
+        })
+    }
+
+    #[test]
+    fn parsing() {
+        let tree = parse("[3,9,20,null,null,15,7]").unwrap();
+        assert_eq!(tree.to_string(), "[3,9,20,null,null,15,7]");
+
+        let root = tree.0.as_ref().unwrap().borrow();
+        assert_eq!(root.item, 3);
+        assert!(root.left.0.as_ref().unwrap().borrow().left.0.is_none());
+        assert_eq!(
+            root.right
+                .0
+                .as_ref()
+                .unwrap()
+                .borrow()
+                .right
+                .0
+                .as_ref()
+                .unwrap()
+                .borrow()
+                .item,
+            7
+        );
+
+        assert_eq!(parse("[]").unwrap(), Tree::default());
+        assert_eq!(parse("[null]").unwrap(), Tree::default());
+        assert_eq!(
+            parse(" [ 1 , null , 2 ] ").unwrap().to_string(),
+            "[1,null,2]"
+        );
+    }
+
+    #[test]
+    fn printing_trims_trailing_nulls() {
+        assert_eq!(parse("[1,2,null,null,null]").unwrap().to_string(), "[1,2]");
+        assert_eq!(
+            parse("[1,null,2,null,null]").unwrap().to_string(),
+            "[1,null,2]"
+        );
+        assert_eq!(Tree::<i32>::default().to_string(), "[]");
+
+        let bt: BinaryTree<i32> = vec![2, 1, 3, 4].into_iter().collect();
+        assert_eq!(bt.as_tree().to_string(), "[2,1,3,null,null,null,4]");
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert_eq!(parse("1,2,3"), Err(ParseTreeError::MissingBrackets));
+        assert_eq!(parse("[1,2"), Err(ParseTreeError::MissingBrackets));
+        assert_eq!(
+            parse("[1,,2]"),
+            Err(ParseTreeError::EmptyToken { index: 1 })
+        );
+        assert_eq!(
+            parse("[1,two,3]"),
+            Err(ParseTreeError::InvalidValue {
+                index: 1,
+                token: "two".to_string()
+            })
+        );
+        assert_eq!(parse("[null,1]"), Err(ParseTreeError::Orphan { index: 1 }));
+        assert_eq!(
+            parse("[1,null,null,2]"),
+            Err(ParseTreeError::Orphan { index: 3 })
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn round_trips_through_strings(shape in shapes()) {
+            let tree = shape.into_tree();
+            let printed = tree.to_string();
+            let parsed = parse(&printed).unwrap();
+
+            prop_assert!(parsed.parent_links_consistent());
+            prop_assert_eq!(&parsed, &tree);
+            prop_assert_eq!(parsed.to_string(), printed);
+        }
+
+        #[test]
+        fn round_trips_search_trees(items in prop::collection::vec(-100i32..100, 0..64)) {
+            let bt: BinaryTree<i32> = items.into_iter().collect();
+            let printed = bt.as_tree().to_string();
+
+            prop_assert_eq!(&parse(&printed).unwrap(), bt.as_tree());
+        }
+    }
+}
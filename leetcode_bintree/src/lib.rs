@@ -1,30 +1,76 @@
-use std::borrow::Borrow;
-use std::cell::{Ref, RefCell, RefMut};
+use std::cell::{Ref, RefCell};
 use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::iter::{FromIterator, IntoIterator};
 use std::mem;
-use std::ops::Deref;
-use std::rc::Rc;
-use std::ptr;
+use std::ops::RangeBounds;
+use std::rc::{Rc, Weak};
 
-#[derive(PartialEq, Eq, Clone, Debug)]
+use ordered_set::OrderedSet;
+use tree_view::{Node, Shape, TreeView};
+
+mod algorithms;
+mod format;
+mod node;
+mod sync;
+mod traversal;
+
+pub use format::ParseTreeError;
+pub use node::{Ancestors, NodeRef};
+pub use sync::SyncBinaryTree;
+pub use traversal::TraversalError;
+
+#[derive(Debug)]
 struct TreeNode<T: Ord> {
     item: T,
     left: Tree<T>,
     right: Tree<T>,
+    // Dangling for the root. Weak so a parent and child don't keep each
+    // other alive.
+    parent: Weak<RefCell<TreeNode<T>>>,
 }
 
+// Two trees are equal when they hold the same items in the same shape,
+// wherever they happen to hang from
+impl<T: Ord> PartialEq for TreeNode<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.item == other.item && self.left == other.left && self.right == other.right
+    }
+}
+
+impl<T: Ord> Eq for TreeNode<T> {}
+
 impl<T: Ord> TreeNode<T> {
     fn new(item: T) -> Self {
         Self {
             item,
             left: Tree::default(),
             right: Tree::default(),
+            parent: Weak::new(),
+        }
+    }
+
+    // A new node with its parent link already pointing at `parent`
+    fn with_parent(item: T, parent: &Rc<RefCell<TreeNode<T>>>) -> Self {
+        Self {
+            parent: Rc::downgrade(parent),
+            ..Self::new(item)
+        }
+    }
+
+    #[inline]
+    fn child_mut(&mut self, left: bool) -> &mut Tree<T> {
+        match left {
+            true => &mut self.left,
+            false => &mut self.right,
         }
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Debug)]
-struct Tree<T: Ord>(Option<Rc<RefCell<TreeNode<T>>>>);
+/// A (sub)tree in the same `Option<Rc<RefCell<TreeNode>>>` shape LeetCode
+/// uses, with no ordering requirements on its own.
+#[derive(PartialEq, Eq, Debug)]
+pub struct Tree<T: Ord>(Option<Rc<RefCell<TreeNode<T>>>>);
 
 impl<T: Ord> Default for Tree<T> {
     #[inline]
@@ -43,82 +89,225 @@ impl<T: Ord> Tree<T> {
     fn take(&mut self) -> Self {
         Tree(self.0.take())
     }
+
+    fn set_parent(&self, parent: Weak<RefCell<TreeNode<T>>>) {
+        if let Some(rc) = self.0.as_ref() {
+            rc.borrow_mut().parent = parent;
+        }
+    }
+
+    // A root node over two existing subtrees
+    #[cfg(test)]
+    fn from_parts(item: T, left: Self, right: Self) -> Self {
+        let tree: Self = TreeNode::new(item).into();
+        let rc = tree.0.as_ref().unwrap();
+
+        left.set_parent(Rc::downgrade(rc));
+        right.set_parent(Rc::downgrade(rc));
+        rc.borrow_mut().left = left;
+        rc.borrow_mut().right = right;
+
+        tree
+    }
+
+    // Whether every node's parent link points at the node that owns it
+    #[cfg(test)]
+    fn parent_links_consistent(&self) -> bool {
+        let Some(root) = self.0.as_ref() else {
+            return true;
+        };
+
+        let mut stack = vec![Rc::clone(root)];
+
+        if root.borrow().parent.strong_count() != 0 {
+            return false;
+        }
+
+        while let Some(rc) = stack.pop() {
+            for child in [&rc.borrow().left, &rc.borrow().right] {
+                if let Some(child) = child.0.as_ref() {
+                    let parent = child.borrow().parent.upgrade();
+
+                    if !parent.is_some_and(|parent| Rc::ptr_eq(&parent, &rc)) {
+                        return false;
+                    }
+                    stack.push(Rc::clone(child));
+                }
+            }
+        }
+
+        true
+    }
+
+    // Every node belongs to exactly one parent link, so once that link
+    // has been taken the node can be moved out of its cell
+    fn into_node(mut self) -> Option<TreeNode<T>> {
+        let rc = self.0.take()?;
+
+        let cell = Rc::try_unwrap(rc)
+            .ok()
+            .expect("tree nodes are only ever owned by their parent link");
+
+        Some(cell.into_inner())
+    }
 }
 
 impl<'tree, T: Ord> Tree<T> {
+    // Hands out a reference to a node's cell that lives as long as the
+    // borrow of the whole tree. Sound because the node stays owned by its
+    // parent link, and links only change through `&mut` methods, which
+    // can't run while `'tree` is held.
+    #[inline]
+    fn cell_ref(rc: &Rc<RefCell<TreeNode<T>>>) -> &'tree RefCell<TreeNode<T>> {
+        unsafe { &*Rc::as_ptr(rc) }
+    }
+
+    fn search_cell(&'tree self, target: &T) -> Option<&'tree RefCell<TreeNode<T>>> {
+        let mut cell: &'tree RefCell<TreeNode<T>> = self.0.as_deref()?;
+
+        loop {
+            let node = cell.borrow();
+
+            let next = match target.cmp(&node.item) {
+                Ordering::Less => node.left.0.as_ref(),
+                Ordering::Greater => node.right.0.as_ref(),
+                Ordering::Equal => return Some(cell),
+            };
+
+            cell = Self::cell_ref(next?);
+        }
+    }
+
+    #[inline]
     fn search(&'tree self, target: &T) -> Option<Ref<'tree, TreeNode<T>>> {
-        let s = self.0
-            .as_deref()
-            .map(|cell| cell.borrow());
-
-        let next = {
-            match s.as_deref() {
-                Some(node_ref) => node_ref.left.0.as_deref().map(|cell| cell.borrow()),
-                None => None
-            }
+        self.search_cell(target).map(RefCell::borrow)
+    }
+
+    fn add_child(&mut self, new_item: T) -> bool {
+        let Some(root) = self.0.as_ref() else {
+            *self = TreeNode::new(new_item).into();
+            return true;
         };
 
-        next
+        let mut curr = Rc::clone(root);
+
+        loop {
+            let next = {
+                let mut node = curr.borrow_mut();
+
+                let child = match new_item.cmp(&node.item) {
+                    Ordering::Less => &mut node.left,
+                    Ordering::Greater => &mut node.right,
+                    Ordering::Equal => return false,
+                };
+
+                match child.0.as_ref() {
+                    Some(next) => Rc::clone(next),
+
+                    None => {
+                        *child = TreeNode::with_parent(new_item, &curr).into();
+                        return true;
+                    }
+                }
+            };
+
+            curr = next;
+        }
     }
 
-    fn add_child(&mut self, new_item: T) {
-        // let mut tree = self;
+    fn remove_leftmost_child(&mut self) -> Option<TreeNode<T>> {
+        let root = self.0.as_ref()?;
 
-        // while let Some(node) = tree.0.as_deref().map(|refcell| refcell.borrow_mut()) {
-        //     match new_item.cmp(&node.item) {
-        //         Ordering::Less => tree = &mut node.left,
-        //         Ordering::Greater => tree = &mut node.right,
-        //         Ordering::Equal => {
-        //             return;
-        //         }
-        //     }
-        // }
+        if root.borrow().left.0.is_none() {
+            let mut pruned = self.take().into_node()?;
+            *self = pruned.right.take();
+            self.set_parent(pruned.parent.clone());
+            return Some(pruned);
+        }
+
+        // Walk down to the parent of the leftmost node
+        let mut parent = Rc::clone(root);
+
+        loop {
+            let next = {
+                let node = parent.borrow();
+                let left = Rc::clone(node.left.0.as_ref()?);
+                let left_has_left = left.borrow().left.0.is_some();
+
+                match left_has_left {
+                    true => left,
+                    false => break,
+                }
+            };
+
+            parent = next;
+        }
+
+        let mut parent = parent.borrow_mut();
+        let mut pruned = parent.left.take().into_node()?;
 
-        // *tree = TreeNode::new(new_item).into();
+        parent.left = pruned.right.take();
+        parent.left.set_parent(pruned.parent.clone());
+        Some(pruned)
     }
 
-    // fn remove_leftmost_child(&mut self) -> Option<Rc<RefCell<TreeNode<T>>>> {
-    //     let mut tree = self;
-    //     let mut leftmost = Tree(None);
+    fn remove_child(&mut self, target: &T) -> Option<T> {
+        let mut parent: Option<Rc<RefCell<TreeNode<T>>>> = None;
+        let mut went_left = false;
+        let mut curr = Rc::clone(self.0.as_ref()?);
 
-    //     let pruned = { 
-    //         loop {
+        loop {
+            let (next, left) = {
+                let node = curr.borrow();
 
-    //             match tree.0.as_deref().map(|cell| cell.borrow_mut()).as_deref_mut() {
+                match target.cmp(&node.item) {
+                    Ordering::Less => (node.left.0.clone(), true),
+                    Ordering::Greater => (node.right.0.clone(), false),
+                    Ordering::Equal => break,
+                }
+            };
 
-    //                 None => break,
+            went_left = left;
+            parent = Some(curr);
+            curr = next?;
+        }
+
+        // With both children present the node stays put and trades its
+        // item for its successor's
+        {
+            let mut node = curr.borrow_mut();
 
-    //                 Some(TreeNode {
-    //                     left: Tree(None),
-    //                     right: Tree(None),
-    //                     ..
-    //                 }) => leftmost = tree.take(),
+            if node.left.0.is_some() && node.right.0.is_some() {
+                let successor = node.right.remove_leftmost_child()?;
+                return Some(mem::replace(&mut node.item, successor.item));
+            }
+        }
 
-    //                 Some(TreeNode {
-    //                     left: Tree(None),
-    //                     right: right @ Tree(Some(rn)),
-    //                     ..
-    //                 }) => {
-    //                     let moved_right = right.take();
-    //                     let leftmost = mem::replace(tree, moved_right);
-    //                     break;
-    //                 }
+        // Otherwise unhook it and let its only child (if any) move up
+        drop(curr);
 
-    //                 Some(node @ TreeNode {
-    //                     left: Tree(Some(_)),
-    //                     ..
-    //                 }) => tree = &mut node.left
-    //             }
+        let mut pruned = match parent.as_ref() {
+            None => self.take(),
+            Some(p) => p.borrow_mut().child_mut(went_left).take(),
+        }
+        .into_node()?;
+
+        let orphan = match pruned.left.0.is_some() {
+            true => pruned.left.take(),
+            false => pruned.right.take(),
+        };
+        orphan.set_parent(pruned.parent.clone());
 
-    //         } 
-    //         leftmost 
-    //     };
+        match parent.as_ref() {
+            None => *self = orphan,
+            Some(p) => *p.borrow_mut().child_mut(went_left) = orphan,
+        }
 
-    //     pruned.0
-    // }
+        Some(pruned.item)
+    }
 }
 
-#[derive(PartialEq, Eq, Clone, Debug, Default)]
+#[derive(PartialEq, Eq, Debug, Default)]
 pub struct BinaryTree<T: Ord> {
     root: Tree<T>,
     size: usize,
@@ -140,28 +329,456 @@ impl<T: Ord> BinaryTree<T> {
 
     #[inline]
     pub fn clear(&mut self) {
-        _ = self.root.0.take();
-        self.size = 0;
+        *self = Self::new();
+    }
+
+    /// The underlying shape, e.g. for printing in LeetCode format.
+    #[inline]
+    pub fn as_tree(&self) -> &Tree<T> {
+        &self.root
     }
 
     #[inline]
     pub fn search(&self, target: &T) -> Option<Ref<'_, T>> {
-        self.root.search(target).map(|node_ref| Ref::map(node_ref, |n| &n.item))
+        self.root
+            .search(target)
+            .map(|node_ref| Ref::map(node_ref, |n| &n.item))
+    }
+
+    /// Like `search`, but hands back the node itself, which can be walked
+    /// up and down the tree from.
+    #[inline]
+    pub fn search_node(&self, target: &T) -> Option<NodeRef<'_, T>> {
+        self.root.search_cell(target).map(NodeRef::new)
     }
 
     #[inline]
     pub fn insert(&mut self, new_item: T) {
-        self.root.add_child(new_item);
-        self.size += 1;
+        if self.root.add_child(new_item) {
+            self.size += 1;
+        }
+    }
+
+    #[inline]
+    pub fn remove(&mut self, target: &T) -> Option<T> {
+        let removed = self.root.remove_child(target)?;
+
+        self.size -= 1;
+        Some(removed)
+    }
+
+    #[inline]
+    pub fn remove_inorder(&mut self) -> Option<T> {
+        self.root.remove_leftmost_child().map(|n| {
+            self.size -= 1;
+            n.item
+        })
+    }
+}
+
+// Nothing keeps a plain BST (or a parsed shape) shallow, so unlink nodes
+// one at a time rather than letting a long chain of Rc drops recurse
+impl<T: Ord> Drop for Tree<T> {
+    fn drop(&mut self) {
+        let mut stack: Vec<_> = self.0.take().into_iter().collect();
+
+        while let Some(rc) = stack.pop() {
+            // A node still referenced elsewhere is left for its last owner
+            if let Ok(cell) = Rc::try_unwrap(rc) {
+                let mut node = cell.into_inner();
+                stack.extend(node.left.0.take());
+                stack.extend(node.right.0.take());
+            }
+        }
+    }
+}
+
+// A derived Clone would only clone the root Rc and leave two trees
+// sharing (and mutating) the same nodes, so copy node by node instead.
+impl<T: Ord + Clone> Clone for Tree<T> {
+    fn clone(&self) -> Self {
+        let copy_of = |rc: &Rc<RefCell<TreeNode<T>>>, parent: Weak<_>| {
+            let item = rc.borrow().item.clone();
+            Rc::new(RefCell::new(TreeNode {
+                parent,
+                ..TreeNode::new(item)
+            }))
+        };
+
+        let Some(root) = self.0.as_ref() else {
+            return Tree::default();
+        };
+
+        let copy = copy_of(root, Weak::new());
+        let mut stack = vec![(root.clone(), copy.clone())];
+
+        while let Some((original, copy_rc)) = stack.pop() {
+            let original = original.borrow();
+            let mut copy = copy_rc.borrow_mut();
+            let copy = &mut *copy;
+
+            for (from, to) in [
+                (&original.left, &mut copy.left),
+                (&original.right, &mut copy.right),
+            ] {
+                if let Some(child) = from.0.as_ref() {
+                    let child_copy = copy_of(child, Rc::downgrade(&copy_rc));
+                    *to = Tree(Some(child_copy.clone()));
+                    stack.push((child.clone(), child_copy));
+                }
+            }
+        }
+
+        Tree(Some(copy))
+    }
+}
+
+impl<T: Ord + Clone> Clone for BinaryTree<T> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            size: self.size,
+        }
+    }
+}
+
+pub struct InorderIntoIter<T: Ord>(BinaryTree<T>);
+
+impl<T: Ord> Iterator for InorderIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.remove_inorder()
+    }
+}
+
+pub struct InorderIter<'tree, T: Ord> {
+    curr_node: Option<&'tree RefCell<TreeNode<T>>>,
+    node_stack: Vec<&'tree RefCell<TreeNode<T>>>,
+}
+
+impl<'tree, T: Ord> BinaryTree<T> {
+    pub fn iter(&'tree self) -> InorderIter<'tree, T> {
+        InorderIter {
+            curr_node: self.root.0.as_deref(),
+            node_stack: vec![],
+        }
+    }
+}
+
+impl<'tree, T: Ord> Iterator for InorderIter<'tree, T> {
+    type Item = Ref<'tree, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(cell) = self.curr_node {
+            self.curr_node = cell.borrow().left.0.as_ref().map(Tree::cell_ref);
+            self.node_stack.push(cell);
+        }
+
+        let popped = self.node_stack.pop()?;
+        self.curr_node = popped.borrow().right.0.as_ref().map(Tree::cell_ref);
+
+        Some(Ref::map(popped.borrow(), |n| &n.item))
+    }
+}
+
+impl<T: Ord> IntoIterator for BinaryTree<T> {
+    type Item = T;
+    type IntoIter = InorderIntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        InorderIntoIter(self)
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BinaryTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree: BinaryTree<T> = Self::new();
+        iter.into_iter().for_each(|item| tree.insert(item));
+        tree
+    }
+}
+
+impl<T: Ord> OrderedSet for BinaryTree<T> {
+    type Item = T;
+    type ItemRef<'set>
+        = Ref<'set, T>
+    where
+        Self: 'set;
+    type Iter<'set>
+        = InorderIter<'set, T>
+    where
+        Self: 'set;
+    type Range<'set, R>
+        = ordered_set::Range<InorderIter<'set, T>, R>
+    where
+        Self: 'set,
+        R: RangeBounds<T>;
+
+    #[inline]
+    fn insert(&mut self, item: T) -> bool {
+        let size = self.size();
+        BinaryTree::insert(self, item);
+        self.size() != size
+    }
+
+    #[inline]
+    fn remove(&mut self, item: &T) -> Option<T> {
+        BinaryTree::remove(self, item)
+    }
+
+    #[inline]
+    fn contains(&self, item: &T) -> bool {
+        self.search(item).is_some()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.size()
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+
+    // Skips up to the start one item at a time, as `ordered_set::seek`
+    // needs plain references to items, which the cells only lend out
+    // through a guard
+    #[inline]
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_, R> {
+        ordered_set::Range::new(self.iter(), range)
+    }
+}
+
+impl<T: Ord + Debug> TreeView for BinaryTree<T> {
+    fn shape(&self) -> Shape {
+        Shape::new(
+            self.root.0.clone(),
+            |node| {
+                let node = node.borrow();
+                [node.left.0.clone(), node.right.0.clone()]
+            },
+            |node| Node::new(&node.borrow().item),
+        )
+    }
+}
+
+impl<T: Ord + Debug> fmt::Display for BinaryTree<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.pretty(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ordered_set::conformance_suite!(BinaryTree<u32>);
+
+    impl<T: Ord + Copy> BinaryTree<T> {
+        fn to_vec(&self) -> Vec<T> {
+            self.iter().map(|item| *item).collect()
+        }
     }
 
-    // #[inline]
-    // pub fn remove_inorder(&mut self) -> Option<T> {
-    //     let removed = self.root.remove_leftmost_child().map(|n| {
-    //         self.size -= 1;
-    //         n.item
-    //     });
+    #[test]
+    fn constructor() {
+        let tree: BinaryTree<i32> = BinaryTree::new();
+        assert_eq!(tree, BinaryTree::default());
 
-    //     removed
-    // }
+        let tree: BinaryTree<u16> = vec![20, 10, 30].into_iter().collect();
+        let cloned = tree.clone();
+        assert_eq!(tree, cloned);
+
+        // Clones don't share nodes
+        let mut cloned = cloned;
+        cloned.insert(40);
+        assert_eq!(tree.size(), 3);
+        assert_eq!(tree.to_vec(), [10, 20, 30]);
+
+        // Neither do copies of the underlying shape
+        let shape = cloned.as_tree().clone();
+        assert_eq!(&shape, cloned.as_tree());
+        assert_eq!(cloned.remove(&20), Some(20));
+        drop(cloned);
+        assert_eq!(shape.to_string(), "[20,10,30,null,null,null,40]");
+
+        for (tree_val, num) in tree.into_iter().zip([10, 20, 30]) {
+            assert_eq!(tree_val, num);
+        }
+    }
+
+    #[test]
+    fn inserting() {
+        let mut tree: BinaryTree<u32> = BinaryTree::new();
+
+        tree.insert(5);
+        tree.insert(3);
+        tree.insert(1);
+        tree.insert(2);
+        tree.insert(4);
+        tree.insert(3);
+
+        assert_eq!(tree.size(), 5);
+        assert_eq!(tree.to_vec(), [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn searching() {
+        let tree: BinaryTree<u32> = [50, 25, 75, 10, 40, 60, 90].into_iter().collect();
+
+        assert_eq!(tree.search(&75).as_deref(), Some(&75));
+        assert_eq!(tree.search(&10).as_deref(), Some(&10));
+        assert_eq!(tree.search(&40).as_deref(), Some(&40));
+        assert_eq!(tree.search(&100).as_deref(), None);
+        assert_eq!(BinaryTree::<u32>::new().search(&1).as_deref(), None);
+
+        // Several lookups can be held at once
+        let (a, b) = (tree.search(&60).unwrap(), tree.search(&90).unwrap());
+        assert!(*a < *b);
+    }
+
+    #[test]
+    fn removing_inorder() {
+        let mut tree: BinaryTree<u64> = [5, 3, 1, 2, 4].into_iter().collect();
+
+        for val in 1..=5 {
+            assert_eq!(tree.remove_inorder(), Some(val));
+            assert_eq!(tree.size(), 5 - val as usize);
+        }
+
+        assert_eq!(tree.remove_inorder(), None);
+        assert_eq!(tree.size(), 0);
+    }
+
+    #[test]
+    fn removing_by_key() {
+        let mut tree: BinaryTree<u32> = [50, 25, 75, 10, 40, 60, 90, 55, 65, 62]
+            .into_iter()
+            .collect();
+
+        // Leaf
+        assert_eq!(tree.remove(&10), Some(10));
+        // Single child
+        assert_eq!(tree.remove(&25), Some(25));
+        // Both children, successor further down
+        assert_eq!(tree.remove(&50), Some(50));
+        // Both children, successor directly right
+        assert_eq!(tree.remove(&60), Some(60));
+        // Missing
+        assert_eq!(tree.remove(&50), None);
+        assert!(tree.root.parent_links_consistent());
+
+        assert_eq!(tree.size(), 6);
+        assert_eq!(tree.to_vec(), [40, 55, 62, 65, 75, 90]);
+
+        while let Some(root) = tree.iter().next().map(|i| *i) {
+            assert_eq!(tree.remove(&root), Some(root));
+            assert!(tree.root.parent_links_consistent());
+        }
+
+        assert_eq!(tree.size(), 0);
+        assert_eq!(tree, BinaryTree::new());
+    }
+
+    #[test]
+    fn clearing() {
+        let mut tree: BinaryTree<char> = "binary".chars().collect();
+        assert_eq!(tree.size(), 6);
+
+        tree.clear();
+        assert_eq!(tree.size(), 0);
+        assert_eq!(tree.iter().count(), 0);
+    }
+
+    #[test]
+    fn maintaining_parent_links() {
+        let mut tree: BinaryTree<u32> = [50, 25, 75, 10, 40, 60, 90, 55, 65, 62]
+            .into_iter()
+            .collect();
+        assert!(tree.root.parent_links_consistent());
+
+        // 62 moves up to 60's old spot when 65's left link is cut
+        tree.remove(&60);
+        let node = tree.search_node(&65).unwrap();
+        assert_eq!(*node.parent().unwrap().item(), 62);
+
+        tree.remove_inorder();
+        tree.remove_inorder();
+        assert_eq!(*tree.search_node(&40).unwrap().parent().unwrap().item(), 50);
+        assert!(tree.root.parent_links_consistent());
+
+        let cloned = tree.clone();
+        assert!(cloned.root.parent_links_consistent());
+        for tree in [&tree, &cloned] {
+            let ancestors = tree.search_node(&65).unwrap().ancestors();
+            assert!(ancestors.map(|n| *n.item()).eq([62, 75, 50]));
+        }
+    }
+
+    #[test]
+    fn no_reference_cycles() {
+        fn all_nodes<T: Ord>(tree: &Tree<T>) -> Vec<Weak<RefCell<TreeNode<T>>>> {
+            let mut nodes = vec![];
+            let mut stack: Vec<_> = tree.0.iter().cloned().collect();
+
+            while let Some(rc) = stack.pop() {
+                nodes.push(Rc::downgrade(&rc));
+                stack.extend(rc.borrow().left.0.clone());
+                stack.extend(rc.borrow().right.0.clone());
+            }
+
+            nodes
+        }
+
+        let mut tree: BinaryTree<u32> = (0..64).map(|i| (i * 37) % 64).collect();
+        tree.remove(&10);
+        tree.remove_inorder();
+
+        let nodes = all_nodes(&tree.root);
+        assert_eq!(nodes.len(), 62);
+        assert!(nodes.iter().all(|weak| weak.strong_count() == 1));
+
+        tree.clear();
+        assert!(nodes.iter().all(|weak| weak.strong_count() == 0));
+
+        let shape: Tree<u32> = "[1,2,3,4,null,5]".parse().unwrap();
+        let nodes = all_nodes(&shape);
+        drop(shape);
+        assert!(nodes.iter().all(|weak| weak.upgrade().is_none()));
+    }
+
+    #[test]
+    fn deep_trees() {
+        // What sorted input turns a plain BST into, built directly since
+        // inserting it would take quadratic time
+        let mut chain = Tree::default();
+
+        for item in (0..100_000).rev() {
+            chain = Tree::from_parts(item, Tree::default(), chain);
+        }
+
+        let tree = BinaryTree {
+            root: chain,
+            size: 100_000,
+        };
+
+        assert_eq!(tree.search(&99_999).as_deref(), Some(&99_999));
+        assert_eq!(tree.iter().count(), 100_000);
+        drop(tree);
+
+        let tree: BinaryTree<u32> = (0..5_000).rev().collect();
+        assert!(tree.into_iter().eq(0..5_000));
+    }
+
+    #[test]
+    fn exporting_dot() {
+        let dot = [2, 1, 3].into_iter().collect::<BinaryTree<u32>>().to_dot();
+
+        assert!(dot.contains("n0 [label=\"2\"];"));
+        assert!(dot.contains("n1 [label=\"1\"];\n    n0:sw -> n1;"));
+        assert!(dot.contains("n4 [label=\"3\"];\n    n0:se -> n4;"));
+    }
 }
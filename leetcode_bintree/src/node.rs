@@ -0,0 +1,173 @@
+//! Borrowed handles on individual nodes, for walking a tree in any
+//! direction, including back up through the parent links.
+
+use std::cell::{Ref, RefCell};
+use std::collections::HashSet;
+use std::fmt;
+use std::iter::FusedIterator;
+
+use crate::{Tree, TreeNode};
+
+/// One node of a tree, borrowed for as long as the tree is.
+pub struct NodeRef<'tree, T: Ord>(&'tree RefCell<TreeNode<T>>);
+
+impl<'tree, T: Ord> NodeRef<'tree, T> {
+    #[inline]
+    pub(crate) fn new(cell: &'tree RefCell<TreeNode<T>>) -> Self {
+        Self(cell)
+    }
+
+    #[inline]
+    pub fn item(&self) -> Ref<'tree, T> {
+        Ref::map(self.0.borrow(), |node| &node.item)
+    }
+
+    #[inline]
+    pub fn left(&self) -> Option<Self> {
+        self.0
+            .borrow()
+            .left
+            .0
+            .as_ref()
+            .map(|rc| Self(Tree::cell_ref(rc)))
+    }
+
+    #[inline]
+    pub fn right(&self) -> Option<Self> {
+        self.0
+            .borrow()
+            .right
+            .0
+            .as_ref()
+            .map(|rc| Self(Tree::cell_ref(rc)))
+    }
+
+    /// The node this one hangs from, or `None` for the root.
+    pub fn parent(&self) -> Option<Self> {
+        let node = self.0.borrow();
+
+        if node.parent.strong_count() == 0 {
+            return None;
+        }
+
+        // The parent owns this node, so it lives at least as long as this
+        // node stays in the tree, which `'tree` guarantees
+        Some(Self(unsafe { &*node.parent.as_ptr() }))
+    }
+
+    /// Every node above this one, nearest first and ending at the root.
+    #[inline]
+    pub fn ancestors(&self) -> Ancestors<'tree, T> {
+        Ancestors(self.parent())
+    }
+
+    /// The deepest node that both this node and `other` sit under (a node
+    /// sits under itself), found by walking up rather than searching down.
+    /// `None` if they belong to different trees.
+    pub fn common_ancestor(&self, other: &Self) -> Option<Self> {
+        let mine: HashSet<*const RefCell<TreeNode<T>>> = Some(*self)
+            .into_iter()
+            .chain(self.ancestors())
+            .map(|node| node.0 as *const _)
+            .collect();
+
+        Some(*other)
+            .into_iter()
+            .chain(other.ancestors())
+            .find(|node| mine.contains(&(node.0 as *const _)))
+    }
+
+    /// Whether both handles point at the same node.
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.0, other.0)
+    }
+}
+
+// Derives would needlessly require `T: Clone`
+impl<T: Ord> Clone for NodeRef<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: Ord> Copy for NodeRef<'_, T> {}
+
+impl<T: Ord + fmt::Debug> fmt::Debug for NodeRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("NodeRef").field(&*self.item()).finish()
+    }
+}
+
+pub struct Ancestors<'tree, T: Ord>(Option<NodeRef<'tree, T>>);
+
+impl<'tree, T: Ord> Iterator for Ancestors<'tree, T> {
+    type Item = NodeRef<'tree, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.0?;
+        self.0 = node.parent();
+        Some(node)
+    }
+}
+
+impl<T: Ord> FusedIterator for Ancestors<'_, T> {}
+
+impl<T: Ord> Tree<T> {
+    /// The topmost node, if there is one.
+    #[inline]
+    pub fn root(&self) -> Option<NodeRef<'_, T>> {
+        self.0.as_ref().map(|rc| NodeRef(Tree::cell_ref(rc)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BinaryTree, Tree};
+
+    #[test]
+    fn walking_up_and_down() {
+        let tree: BinaryTree<i32> = [50, 25, 75, 10, 40, 60, 90, 55].into_iter().collect();
+
+        let root = tree.as_tree().root().unwrap();
+        assert_eq!(*root.item(), 50);
+        assert!(root.parent().is_none());
+        assert_eq!(root.ancestors().count(), 0);
+
+        let node = tree.search_node(&55).unwrap();
+        let ancestors: Vec<i32> = node.ancestors().map(|n| *n.item()).collect();
+        assert_eq!(ancestors, [60, 75, 50]);
+
+        let parent = node.parent().unwrap();
+        assert!(parent.left().unwrap().ptr_eq(&node));
+        assert!(parent.right().is_none());
+        assert!(tree.search_node(&42).is_none());
+    }
+
+    #[test]
+    fn common_ancestors() {
+        let tree: Tree<i32> = "[3,5,1,6,2,0,8,null,null,7,4]".parse().unwrap();
+        let find = |item: i32| {
+            let mut stack: Vec<_> = tree.root().into_iter().collect();
+
+            while let Some(node) = stack.pop() {
+                if *node.item() == item {
+                    return node;
+                }
+                stack.extend(node.left());
+                stack.extend(node.right());
+            }
+
+            unreachable!()
+        };
+
+        let lca = |p, q| *find(p).common_ancestor(&find(q)).unwrap().item();
+        assert_eq!(lca(5, 1), 3);
+        assert_eq!(lca(5, 4), 5);
+        assert_eq!(lca(7, 4), 2);
+        assert_eq!(lca(6, 6), 6);
+
+        let other: Tree<i32> = "[3]".parse().unwrap();
+        assert!(find(3).common_ancestor(&other.root().unwrap()).is_none());
+    }
+}
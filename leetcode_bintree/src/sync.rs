@@ -0,0 +1,387 @@
+//! A thread-safe counterpart to `BinaryTree`, with `Arc<RwLock>` in place
+//! of `Rc<RefCell>`.
+//!
+//! Every link is its own lock, and operations use hand-over-hand locking:
+//! the next link down is locked before the current one is released, so
+//! threads working in different parts of the tree don't wait on each
+//! other, and a thread can't be overtaken between two links. Since every
+//! thread locks from the root downwards, they can't deadlock either.
+
+use std::cmp::Ordering;
+use std::iter::FromIterator;
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+type Link<T> = Arc<RwLock<Option<SyncNode<T>>>>;
+type ReadGuard<'link, T> = RwLockReadGuard<'link, Option<SyncNode<T>>>;
+type WriteGuard<'link, T> = RwLockWriteGuard<'link, Option<SyncNode<T>>>;
+
+struct SyncNode<T> {
+    item: T,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+impl<T> SyncNode<T> {
+    fn new(item: T) -> Self {
+        Self {
+            item,
+            left: Link::default(),
+            right: Link::default(),
+        }
+    }
+}
+
+// The only code running under a lock is our own and `T::cmp`, so a
+// poisoned lock means the tree may be half way through a change
+fn read<T>(link: &Link<T>) -> ReadGuard<'_, T> {
+    link.read()
+        .expect("a thread panicked while changing the tree")
+}
+
+fn write<T>(link: &Link<T>) -> WriteGuard<'_, T> {
+    link.write()
+        .expect("a thread panicked while changing the tree")
+}
+
+/// A binary search tree that can be shared between threads, e.g. behind
+/// an `Arc`. Every method takes `&self`.
+///
+/// Each operation recurses once per level, so as with any plain BST the
+/// input should be reasonably shuffled.
+#[derive(Default)]
+pub struct SyncBinaryTree<T: Ord> {
+    root: Link<T>,
+    size: AtomicUsize,
+}
+
+impl<T: Ord> SyncBinaryTree<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            root: Link::default(),
+            size: AtomicUsize::new(0),
+        }
+    }
+
+    /// The number of items, which may already be stale by the time it's
+    /// read if other threads are changing the tree.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size.load(AtomicOrdering::SeqCst)
+    }
+
+    #[inline]
+    pub fn clear(&self) {
+        let mut root = write(&self.root);
+
+        *root = None;
+        self.size.store(0, AtomicOrdering::SeqCst);
+    }
+
+    /// A copy of the item equal to `target`; the item itself can't be
+    /// borrowed past the lock protecting it.
+    #[inline]
+    pub fn search(&self, target: &T) -> Option<T>
+    where
+        T: Clone,
+    {
+        Self::search_from(&self.root, (), target, |item| item.clone())
+    }
+
+    #[inline]
+    pub fn contains(&self, target: &T) -> bool {
+        Self::search_from(&self.root, (), target, |_| ()).is_some()
+    }
+
+    #[inline]
+    pub fn insert(&self, new_item: T) {
+        if Self::insert_from(&self.root, (), new_item) {
+            self.size.fetch_add(1, AtomicOrdering::SeqCst);
+        }
+    }
+
+    #[inline]
+    pub fn remove(&self, target: &T) -> Option<T> {
+        let removed = Self::remove_from(&self.root, (), target)?;
+
+        self.size.fetch_sub(1, AtomicOrdering::SeqCst);
+        Some(removed)
+    }
+
+    #[inline]
+    pub fn remove_inorder(&self) -> Option<T> {
+        let removed = Self::remove_leftmost(write(&self.root))?;
+
+        self.size.fetch_sub(1, AtomicOrdering::SeqCst);
+        Some(removed)
+    }
+
+    /// The items in order. Parts of the tree are unlocked as soon as they
+    /// have been visited, so while other threads are changing the tree
+    /// this isn't a snapshot of any one moment.
+    pub fn to_vec(&self) -> Vec<T>
+    where
+        T: Clone,
+    {
+        fn visit<T: Clone>(link: &Link<T>, items: &mut Vec<T>) {
+            let guard = read(link);
+
+            if let Some(node) = guard.as_ref() {
+                visit(&node.left, items);
+                items.push(node.item.clone());
+                visit(&node.right, items);
+            }
+        }
+
+        let mut items = Vec::with_capacity(self.size());
+        visit(&self.root, &mut items);
+        items
+    }
+
+    // `held` is the guard on the link above, released only once `link`
+    // is locked
+    fn search_from<R, G>(
+        link: &Link<T>,
+        held: G,
+        target: &T,
+        found: impl FnOnce(&T) -> R,
+    ) -> Option<R> {
+        let guard = read(link);
+        drop(held);
+
+        let node = guard.as_ref()?;
+
+        let next = match target.cmp(&node.item) {
+            Ordering::Less => Arc::clone(&node.left),
+            Ordering::Greater => Arc::clone(&node.right),
+            Ordering::Equal => return Some(found(&node.item)),
+        };
+
+        Self::search_from(&next, guard, target, found)
+    }
+
+    fn insert_from<G>(link: &Link<T>, held: G, new_item: T) -> bool {
+        let mut guard = write(link);
+        drop(held);
+
+        let Some(node) = guard.as_ref() else {
+            *guard = Some(SyncNode::new(new_item));
+            return true;
+        };
+
+        let next = match new_item.cmp(&node.item) {
+            Ordering::Less => Arc::clone(&node.left),
+            Ordering::Greater => Arc::clone(&node.right),
+            Ordering::Equal => return false,
+        };
+
+        Self::insert_from(&next, guard, new_item)
+    }
+
+    fn remove_from<G>(link: &Link<T>, held: G, target: &T) -> Option<T> {
+        let guard = write(link);
+        drop(held);
+
+        let node = guard.as_ref()?;
+
+        let next = match target.cmp(&node.item) {
+            Ordering::Less => Arc::clone(&node.left),
+            Ordering::Greater => Arc::clone(&node.right),
+            Ordering::Equal => return Some(Self::unlink(guard)),
+        };
+
+        Self::remove_from(&next, guard, target)
+    }
+
+    // Removes the node behind `guard`, with both of its children locked so
+    // nothing below changes while things move up. Threads waiting on the
+    // node's own link will find whatever takes its place.
+    fn unlink(mut guard: WriteGuard<'_, T>) -> T {
+        let node = guard.as_mut().expect("only called on a present node");
+        let (left, right) = (Arc::clone(&node.left), Arc::clone(&node.right));
+        let (mut left_guard, mut right_guard) = (write(&left), write(&right));
+
+        match (left_guard.is_some(), right_guard.is_some()) {
+            // Keep the node where it is and trade its item for its successor's
+            (true, true) => {
+                drop(left_guard);
+                let successor =
+                    Self::remove_leftmost(right_guard).expect("right subtree is present");
+                mem::replace(&mut node.item, successor)
+            }
+
+            // Otherwise let its only child (if any) move up
+            (true, false) => Self::replace_with(guard, left_guard.take()),
+            (false, _) => Self::replace_with(guard, right_guard.take()),
+        }
+    }
+
+    fn replace_with(mut guard: WriteGuard<'_, T>, orphan: Option<SyncNode<T>>) -> T {
+        let pruned = mem::replace(&mut *guard, orphan);
+        pruned.expect("only called on a present node").item
+    }
+
+    fn remove_leftmost(mut guard: WriteGuard<'_, T>) -> Option<T> {
+        let node = guard.as_mut()?;
+        let (left, right) = (Arc::clone(&node.left), Arc::clone(&node.right));
+        let left_guard = write(&left);
+
+        if left_guard.is_some() {
+            drop(guard);
+            return Self::remove_leftmost(left_guard);
+        }
+
+        drop(left_guard);
+        let orphan = write(&right).take();
+        Some(Self::replace_with(guard, orphan))
+    }
+}
+
+// Unlink nodes one at a time rather than letting a long chain of drops
+// recurse. Nobody else can hold a link by now, so each can be unwrapped.
+impl<T: Ord> Drop for SyncBinaryTree<T> {
+    fn drop(&mut self) {
+        let into_node = |link: Link<T>| {
+            Arc::try_unwrap(link)
+                .ok()
+                .and_then(|lock| lock.into_inner().ok())
+                .flatten()
+        };
+
+        let root = mem::take(&mut self.root);
+        let mut stack: Vec<_> = into_node(root).into_iter().collect();
+
+        while let Some(SyncNode { left, right, .. }) = stack.pop() {
+            stack.extend(into_node(left));
+            stack.extend(into_node(right));
+        }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SyncBinaryTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let tree = Self::new();
+        iter.into_iter().for_each(|item| tree.insert(item));
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    const THREADS: u64 = 8;
+    const PER_THREAD: u64 = 2_000;
+
+    // A fixed permutation of 0..modulus, so the tree stays shallow
+    fn shuffled(modulus: u64, offset: u64) -> impl Iterator<Item = u64> {
+        (0..modulus).map(move |i| (i * 7_919 + offset) % modulus)
+    }
+
+    #[test]
+    fn single_threaded() {
+        let tree: SyncBinaryTree<u32> = [50, 25, 75, 10, 40, 60, 90, 55, 65, 62]
+            .into_iter()
+            .collect();
+
+        tree.insert(40);
+        assert_eq!(tree.size(), 10);
+        assert_eq!(tree.search(&62), Some(62));
+        assert!(!tree.contains(&61));
+
+        assert_eq!(tree.remove(&10), Some(10));
+        assert_eq!(tree.remove(&25), Some(25));
+        assert_eq!(tree.remove(&50), Some(50));
+        assert_eq!(tree.remove(&60), Some(60));
+        assert_eq!(tree.remove(&50), None);
+        assert_eq!(tree.remove_inorder(), Some(40));
+
+        assert_eq!(tree.size(), 5);
+        assert_eq!(tree.to_vec(), [55, 62, 65, 75, 90]);
+
+        tree.clear();
+        assert_eq!(tree.size(), 0);
+        assert_eq!(tree.remove_inorder(), None);
+    }
+
+    #[test]
+    fn send_sync() {
+        fn is_send<T: Send>() {}
+        fn is_sync<T: Sync>() {}
+
+        is_send::<SyncBinaryTree<i32>>();
+        is_sync::<SyncBinaryTree<i32>>();
+    }
+
+    #[test]
+    fn concurrent_inserts_and_searches() {
+        let tree = SyncBinaryTree::new();
+        let total = THREADS * PER_THREAD;
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                let tree = &tree;
+
+                // Overlapping ranges, so every item is inserted twice
+                s.spawn(move || {
+                    for item in shuffled(total, t) {
+                        if item % THREADS == t || item % THREADS == (t + 1) % THREADS {
+                            tree.insert(item);
+                        }
+                    }
+                });
+
+                s.spawn(move || {
+                    for item in shuffled(total, t).take(PER_THREAD as usize) {
+                        if let Some(found) = tree.search(&item) {
+                            assert_eq!(found, item);
+                        }
+                    }
+                });
+            }
+        });
+
+        assert_eq!(tree.size() as u64, total);
+        assert!(tree.to_vec().into_iter().eq(0..total));
+    }
+
+    #[test]
+    fn concurrent_inserts_and_removes() {
+        let total = THREADS * PER_THREAD;
+        let tree: SyncBinaryTree<u64> = shuffled(total, 0).collect();
+
+        thread::scope(|s| {
+            for t in 0..THREADS {
+                let tree = &tree;
+
+                // Each thread removes its own odd items past the ones the
+                // inorder removals take, and adds new ones
+                s.spawn(move || {
+                    for item in shuffled(total, t).filter(|i| i % THREADS == t) {
+                        if item % 2 == 1 && item >= PER_THREAD {
+                            assert_eq!(tree.remove(&item), Some(item));
+                        }
+                        tree.insert(total + item);
+                    }
+                });
+            }
+
+            s.spawn(|| {
+                for expected in 0..PER_THREAD {
+                    assert_eq!(tree.remove_inorder(), Some(expected));
+                }
+            });
+        });
+
+        let expected: Vec<u64> = (PER_THREAD..total)
+            .filter(|i| i % 2 == 0)
+            .chain(total..2 * total)
+            .collect();
+
+        assert_eq!(tree.size(), expected.len());
+        assert_eq!(tree.to_vec(), expected);
+    }
+}
@@ -0,0 +1,267 @@
+//! Depth-first traversals, and rebuilding a tree from a pair of them.
+//!
+//! An inorder sequence alone can't tell a tree's shape apart, but together
+//! with either a preorder or a postorder sequence it pins down exactly one
+//! tree, as long as the items are distinct.
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::{Tree, TreeNode};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraversalError {
+    /// The two sequences don't have the same number of items.
+    LengthMismatch { order: usize, inorder: usize },
+    /// No tree has both sequences as its traversals, e.g. because they
+    /// don't hold the same items.
+    Inconsistent,
+}
+
+impl fmt::Display for TraversalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LengthMismatch { order, inorder } => write!(
+                f,
+                "traversals differ in length ({} items vs. {} inorder)",
+                order, inorder
+            ),
+            Self::Inconsistent => write!(f, "traversals don't describe the same tree"),
+        }
+    }
+}
+
+impl Error for TraversalError {}
+
+impl<T: Ord + Clone> Tree<T> {
+    // Root, then both subtrees, nearest side first
+    fn preorder_by(&self, left_first: bool) -> Vec<T> {
+        let mut items = Vec::new();
+        let mut stack: Vec<_> = self.0.iter().cloned().collect();
+
+        while let Some(rc) = stack.pop() {
+            let node = rc.borrow();
+            items.push(node.item.clone());
+
+            let (near, far) = match left_first {
+                true => (&node.left, &node.right),
+                false => (&node.right, &node.left),
+            };
+
+            stack.extend(far.0.clone());
+            stack.extend(near.0.clone());
+        }
+
+        items
+    }
+
+    pub fn preorder(&self) -> Vec<T> {
+        self.preorder_by(true)
+    }
+
+    pub fn inorder(&self) -> Vec<T> {
+        let mut items = Vec::new();
+        let mut stack = Vec::new();
+        let mut next = self.0.clone();
+
+        loop {
+            while let Some(rc) = next {
+                next = rc.borrow().left.0.clone();
+                stack.push(rc);
+            }
+
+            let Some(rc) = stack.pop() else {
+                return items;
+            };
+
+            let node = rc.borrow();
+            items.push(node.item.clone());
+            next = node.right.0.clone();
+        }
+    }
+
+    pub fn postorder(&self) -> Vec<T> {
+        // Root, right, left is exactly postorder backwards
+        let mut items = self.preorder_by(false);
+        items.reverse();
+        items
+    }
+
+    /// Rebuilds the tree with the given preorder and inorder traversals in
+    /// O(n). With repeated items several trees may fit, and any one of
+    /// them is returned.
+    pub fn from_preorder_inorder(preorder: &[T], inorder: &[T]) -> Result<Self, TraversalError> {
+        let tree = Self::from_traversals(preorder.iter(), inorder.iter(), true, inorder.len())?;
+
+        match tree.preorder() == preorder && tree.inorder() == inorder {
+            true => Ok(tree),
+            false => Err(TraversalError::Inconsistent),
+        }
+    }
+
+    /// Rebuilds the tree with the given postorder and inorder traversals in
+    /// O(n). With repeated items several trees may fit, and any one of
+    /// them is returned.
+    pub fn from_postorder_inorder(postorder: &[T], inorder: &[T]) -> Result<Self, TraversalError> {
+        let tree = Self::from_traversals(
+            postorder.iter().rev(),
+            inorder.iter().rev(),
+            false,
+            inorder.len(),
+        )?;
+
+        match tree.postorder() == postorder && tree.inorder() == inorder {
+            true => Ok(tree),
+            false => Err(TraversalError::Inconsistent),
+        }
+    }
+
+    // `order` visits every node before its subtrees, nearest side first,
+    // and `inorder` walks the tree starting from that same side. A new
+    // item goes on the near side of the node on top of the stack, unless
+    // that node's near subtree is already done, in which case it goes on
+    // the far side of the last finished node.
+    //
+    // Malformed input still produces some tree; the callers catch it by
+    // comparing its traversals against the input.
+    fn from_traversals<'a>(
+        order: impl ExactSizeIterator<Item = &'a T>,
+        inorder: impl Iterator<Item = &'a T>,
+        left_first: bool,
+        inorder_len: usize,
+    ) -> Result<Self, TraversalError>
+    where
+        T: 'a,
+    {
+        if order.len() != inorder_len {
+            return Err(TraversalError::LengthMismatch {
+                order: order.len(),
+                inorder: inorder_len,
+            });
+        }
+
+        let new_node = |item: &T| Rc::new(RefCell::new(TreeNode::new(item.clone())));
+
+        let mut order = order;
+        let mut inorder = inorder.peekable();
+
+        let Some(root) = order.next().map(new_node) else {
+            return Ok(Tree::default());
+        };
+
+        let mut stack = vec![Rc::clone(&root)];
+
+        for item in order {
+            let top = stack.last().expect("the stack is never left empty");
+            let mut parent = Rc::clone(top);
+            let mut near = left_first;
+
+            if inorder.peek() == Some(&&parent.borrow().item) {
+                near = !left_first;
+
+                while let Some(top) = stack.last() {
+                    if inorder.peek() != Some(&&top.borrow().item) {
+                        break;
+                    }
+
+                    inorder.next();
+                    parent = stack.pop().unwrap();
+                }
+            }
+
+            let child = new_node(item);
+            child.borrow_mut().parent = Rc::downgrade(&parent);
+            *parent.borrow_mut().child_mut(near) = Tree(Some(Rc::clone(&child)));
+            stack.push(child);
+        }
+
+        Ok(Tree(Some(root)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(s: &str) -> Tree<i32> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn traversals() {
+        let t = tree("[1,2,3,4,5,null,6]");
+
+        assert_eq!(t.preorder(), [1, 2, 4, 5, 3, 6]);
+        assert_eq!(t.inorder(), [4, 2, 5, 1, 3, 6]);
+        assert_eq!(t.postorder(), [4, 5, 2, 6, 3, 1]);
+        assert!(tree("[]").preorder().is_empty());
+    }
+
+    #[test]
+    fn rebuilding() {
+        for s in [
+            "[]",
+            "[1]",
+            "[3,9,20,null,null,15,7]",
+            "[1,2,3,4,5,null,6,null,null,7,8]",
+            "[1,null,2,null,3,null,4]",
+            "[1,2,null,3,null,4]",
+            "[5,3,8,1,4,7,9,0,2,null,null,6]",
+        ] {
+            let t = tree(s);
+            let (pre, ino, post) = (t.preorder(), t.inorder(), t.postorder());
+
+            let rebuilt = Tree::from_preorder_inorder(&pre, &ino).unwrap();
+            assert!(rebuilt.parent_links_consistent());
+            assert_eq!(rebuilt, t, "{}", s);
+            assert_eq!(
+                Tree::from_postorder_inorder(&post, &ino).unwrap(),
+                t,
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn rebuilding_deep_chains() {
+        let items: Vec<u32> = (0..100_000).collect();
+        let reversed: Vec<u32> = items.iter().rev().cloned().collect();
+
+        // Preorder == inorder only for a chain of right children
+        let t = Tree::from_preorder_inorder(&items, &items).unwrap();
+        assert_eq!(t.max_depth(), 100_000);
+        assert_eq!(t.postorder(), reversed);
+
+        let t = Tree::from_postorder_inorder(&items, &items).unwrap();
+        assert_eq!(t.max_depth(), 100_000);
+        assert_eq!(t.preorder(), reversed);
+    }
+
+    #[test]
+    fn rejecting_inconsistent_input() {
+        assert_eq!(
+            Tree::from_preorder_inorder(&[1, 2], &[1]),
+            Err(TraversalError::LengthMismatch {
+                order: 2,
+                inorder: 1
+            })
+        );
+        // Different items
+        assert_eq!(
+            Tree::from_preorder_inorder(&[1, 2, 3], &[2, 1, 4]),
+            Err(TraversalError::Inconsistent)
+        );
+        // Same items, but no tree has both orders
+        assert_eq!(
+            Tree::from_preorder_inorder(&[1, 2, 3], &[3, 1, 2]),
+            Err(TraversalError::Inconsistent)
+        );
+        assert_eq!(
+            Tree::from_postorder_inorder(&[3, 1, 2], &[1, 2, 3]),
+            Err(TraversalError::Inconsistent)
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Scenarios every `OrderedSet` has to get through the same way, checked
+//! against `BTreeSet`. A tree crate runs all of them from its tests with:
+//!
+//! ```ignore
+//! ordered_set::conformance_suite!(BinTree<u32>);
+//! ```
+//!
+//! which expands to a `conformance` module with one test per scenario.
+
+use std::collections::BTreeSet;
+use std::ops::Bound;
+
+use crate::OrderedSet;
+
+/// Generates a `conformance` test module running every scenario against a
+/// set of `u32`s made by `Default`.
+#[macro_export]
+macro_rules! conformance_suite {
+    ($set:ty) => {
+        $crate::conformance_suite!(@tests $set;
+            empty,
+            inserting_and_searching,
+            duplicates,
+            removing,
+            iterating,
+            ranges,
+            #[cfg_attr(miri, ignore = "too slow under Miri")] large_inputs,
+            #[cfg_attr(miri, ignore = "too slow under Miri")] sorted_inputs,
+            #[cfg_attr(miri, ignore = "too slow under Miri")] random_operations,
+        );
+    };
+
+    (@tests $set:ty; $($(#[$attr:meta])* $scenario:ident),* $(,)?) => {
+        mod conformance {
+            #[allow(unused_imports)]
+            use super::*;
+
+            $(
+                #[test]
+                $(#[$attr])*
+                fn $scenario() {
+                    $crate::conformance::$scenario::<$set>();
+                }
+            )*
+        }
+    };
+}
+
+// A fixed permutation of 0..len, for len coprime with the multiplier
+fn shuffled(len: u32) -> impl Iterator<Item = u32> {
+    (0..len).map(move |i| ((i as u64 * 7_919 + 13) % len as u64) as u32)
+}
+
+fn items<S: OrderedSet<Item = u32>>(set: &S) -> Vec<u32> {
+    set.iter().map(|item| *item).collect()
+}
+
+fn filled<S: OrderedSet<Item = u32> + Default>(values: impl IntoIterator<Item = u32>) -> S {
+    let mut set = S::default();
+
+    for val in values {
+        set.insert(val);
+    }
+
+    set
+}
+
+// Everything observable about `set` matches `model`
+fn assert_matches<S: OrderedSet<Item = u32>>(set: &S, model: &BTreeSet<u32>) {
+    assert_eq!(set.len(), model.len());
+    assert_eq!(set.is_empty(), model.is_empty());
+    assert_eq!(set.first().map(|item| *item), model.first().copied());
+    assert_eq!(set.last().map(|item| *item), model.last().copied());
+    assert!(items(set).into_iter().eq(model.iter().copied()));
+}
+
+pub fn empty<S: OrderedSet<Item = u32> + Default>() {
+    let mut set = S::default();
+
+    assert_matches(&set, &BTreeSet::new());
+    assert!(!set.contains(&0));
+    assert_eq!(set.remove(&0), None);
+    assert_eq!(set.range(..).count(), 0);
+
+    // Emptied out again
+    assert!(set.insert(1));
+    assert_eq!(set.remove(&1), Some(1));
+    assert_matches(&set, &BTreeSet::new());
+}
+
+pub fn inserting_and_searching<S: OrderedSet<Item = u32> + Default>() {
+    let mut set = S::default();
+
+    for val in [50, 25, 75, 10, 30, 60, 90] {
+        assert!(set.insert(val));
+    }
+
+    assert_eq!(set.len(), 7);
+    assert!([50, 25, 75, 10, 30, 60, 90]
+        .iter()
+        .all(|val| set.contains(val)));
+    assert!(![0, 26, 55, 100].iter().any(|val| set.contains(val)));
+}
+
+pub fn duplicates<S: OrderedSet<Item = u32> + Default>() {
+    let mut set: S = filled([3, 1, 2]);
+
+    assert!(!set.insert(2));
+    assert!(!set.insert(3));
+    assert_eq!(items(&set), [1, 2, 3]);
+
+    // Each value is only ever removed once
+    assert_eq!(set.remove(&2), Some(2));
+    assert_eq!(set.remove(&2), None);
+    assert!(set.insert(2));
+    assert_eq!(set.len(), 3);
+}
+
+pub fn removing<S: OrderedSet<Item = u32> + Default>() {
+    let values = [50, 25, 75, 10, 40, 60, 90, 55, 65, 62, 5];
+    let mut set: S = filled(values);
+    let mut model: BTreeSet<u32> = values.into_iter().collect();
+
+    // Leaves, nodes with one child, nodes with two (with the successor
+    // both directly below and further down), then the rest
+    for val in [5, 10, 90, 25, 60, 50, 62, 75, 40, 55, 65] {
+        assert_eq!(set.remove(&val), model.take(&val), "removing {}", val);
+        assert_eq!(set.remove(&val), None);
+        assert!(!set.contains(&val));
+        assert_matches(&set, &model);
+    }
+}
+
+pub fn iterating<S: OrderedSet<Item = u32> + Default>() {
+    let set: S = filled(shuffled(100));
+
+    assert!(items(&set).into_iter().eq(0..100));
+    assert_eq!(set.iter().count(), 100);
+    assert_eq!(set.first().map(|item| *item), Some(0));
+    assert_eq!(set.last().map(|item| *item), Some(99));
+}
+
+pub fn ranges<S: OrderedSet<Item = u32> + Default>() {
+    let set: S = filled(shuffled(50).map(|val| val * 2));
+    let model: BTreeSet<u32> = (0..50).map(|val| val * 2).collect();
+
+    let bounds = [
+        Bound::Included(10),
+        Bound::Excluded(10),
+        Bound::Included(11),
+        Bound::Excluded(98),
+        Bound::Included(200),
+        Bound::Unbounded,
+    ];
+
+    for start in bounds {
+        for end in bounds {
+            // Reversed ranges hold nothing, where BTreeSet would panic
+            let range: Vec<u32> = set.range((start, end)).map(|item| *item).collect();
+            let expected: Vec<u32> = match crate::is_reversed(&(start, end)) {
+                true => Vec::new(),
+                false => model.range((start, end)).copied().collect(),
+            };
+            assert_eq!(range, expected, "{:?}", (start, end));
+        }
+    }
+}
+
+pub fn large_inputs<S: OrderedSet<Item = u32> + Default>() {
+    let mut set: S = filled(shuffled(10_000));
+    let mut model: BTreeSet<u32> = (0..10_000).collect();
+
+    assert_matches(&set, &model);
+
+    for val in shuffled(10_000).filter(|val| val % 3 != 0) {
+        assert_eq!(set.remove(&val), model.take(&val));
+    }
+
+    assert_matches(&set, &model);
+    assert!(items(&set).into_iter().eq((0..10_000).step_by(3)));
+}
+
+pub fn sorted_inputs<S: OrderedSet<Item = u32> + Default>() {
+    // What unbalanced trees degenerate into a list on
+    let ascending: S = filled(0..2_000);
+    assert!(items(&ascending).into_iter().eq(0..2_000));
+
+    let mut descending: S = filled((0..2_000).rev());
+    assert!(items(&descending).into_iter().eq(0..2_000));
+
+    for val in 0..2_000 {
+        assert_eq!(descending.remove(&val), Some(val));
+    }
+    assert!(descending.is_empty());
+}
+
+pub fn random_operations<S: OrderedSet<Item = u32> + Default>() {
+    let mut set = S::default();
+    let mut model = BTreeSet::new();
+
+    // xorshift, so that failures replay the same way
+    let mut state = 0x2545_f491_u32;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state
+    };
+
+    for step in 0..5_000 {
+        let (op, val) = (next() % 3, next() % 256);
+
+        match op {
+            0 | 1 => assert_eq!(set.insert(val), model.insert(val), "step {}", step),
+            _ => assert_eq!(set.remove(&val), model.take(&val), "step {}", step),
+        }
+
+        assert_eq!(set.contains(&val), model.contains(&val));
+
+        if step % 100 == 0 {
+            assert_matches(&set, &model);
+        }
+    }
+
+    assert_matches(&set, &model);
+}
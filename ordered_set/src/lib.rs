@@ -0,0 +1,366 @@
+//! The operations every tree in the workspace shares, so that code can be
+//! written once against any of them:
+//!
+//! ```
+//! use ordered_set::OrderedSet;
+//! use std::collections::BTreeSet;
+//!
+//! fn evens<S: OrderedSet<Item = u32>>(set: &S) -> Vec<u32> {
+//!     set.range(2..).map(|item| *item).filter(|item| item % 2 == 0).collect()
+//! }
+//!
+//! let set: BTreeSet<u32> = (0..7).collect();
+//! assert_eq!(evens(&set), [2, 4, 6]);
+//! ```
+//!
+//! Items are handed out as `ItemRef`s rather than plain references, since
+//! some trees can only lend them through a guard, e.g. a `RefCell`'s `Ref`.
+
+pub mod conformance;
+
+use std::collections::{btree_set, BTreeSet};
+use std::iter::FusedIterator;
+use std::ops::{Bound, Deref, RangeBounds};
+
+pub trait OrderedSet {
+    type Item: Ord;
+
+    type ItemRef<'set>: Deref<Target = Self::Item>
+    where
+        Self: 'set;
+
+    /// Yields every item in ascending order.
+    type Iter<'set>: Iterator<Item = Self::ItemRef<'set>>
+    where
+        Self: 'set;
+
+    /// Adds `item` unless an equal one is already there, returning whether
+    /// it was added.
+    fn insert(&mut self, item: Self::Item) -> bool;
+
+    fn remove(&mut self, item: &Self::Item) -> Option<Self::Item>;
+
+    fn contains(&self, item: &Self::Item) -> bool;
+
+    fn len(&self) -> usize;
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn iter(&self) -> Self::Iter<'_>;
+
+    #[inline]
+    fn first(&self) -> Option<Self::ItemRef<'_>> {
+        self.iter().next()
+    }
+
+    /// Walks every item unless overridden, which trees that can reach
+    /// their largest item directly should do.
+    #[inline]
+    fn last(&self) -> Option<Self::ItemRef<'_>> {
+        self.iter().last()
+    }
+
+    /// Yields the items within `range`, in ascending order, and nothing if
+    /// it starts after it ends. Trees that can seek start at its lower
+    /// bound, e.g. through [`seek`], and the others skip up to it through
+    /// [`Range`].
+    type Range<'set, R>: Iterator<Item = Self::ItemRef<'set>>
+    where
+        Self: 'set,
+        R: RangeBounds<Self::Item>;
+
+    fn range<R>(&self, range: R) -> Self::Range<'_, R>
+    where
+        R: RangeBounds<Self::Item>;
+}
+
+/// Whether `item` comes before a range starting at `start`.
+#[inline]
+pub fn before_start<T: Ord + ?Sized>(item: &T, start: Bound<&T>) -> bool {
+    match start {
+        Bound::Included(start) => item < start,
+        Bound::Excluded(start) => item <= start,
+        Bound::Unbounded => false,
+    }
+}
+
+/// Whether `item` comes after a range ending at `end`.
+#[inline]
+pub fn past_end<T: Ord + ?Sized>(item: &T, end: Bound<&T>) -> bool {
+    match end {
+        Bound::Included(end) => item > end,
+        Bound::Excluded(end) => item >= end,
+        Bound::Unbounded => false,
+    }
+}
+
+/// Whether `range` starts after it ends, which `BTreeSet::range` would
+/// panic at rather than yield nothing.
+#[inline]
+pub fn is_reversed<T: Ord + ?Sized>(range: &impl RangeBounds<T>) -> bool {
+    match (range.start_bound(), range.end_bound()) {
+        (Bound::Excluded(start), Bound::Excluded(end)) => start >= end,
+        (
+            Bound::Included(start) | Bound::Excluded(start),
+            Bound::Included(end) | Bound::Excluded(end),
+        ) => start > end,
+        _ => false,
+    }
+}
+
+/// Walks down from `root` to the first item not before `start`, returning
+/// every node the walk goes left at, deepest last. That's the stack an
+/// in-order iterator would hold, had it iterated up to that item, so it
+/// can carry on from there.
+pub fn seek<'a, N, T>(
+    root: Option<&'a N>,
+    children: impl Fn(&'a N) -> [Option<&'a N>; 2],
+    item: impl Fn(&'a N) -> &'a T,
+    start: Bound<&T>,
+) -> Vec<&'a N>
+where
+    T: Ord + ?Sized + 'a,
+{
+    let mut stack = Vec::new();
+    let mut next = root;
+
+    while let Some(node) = next {
+        let [left, right] = children(node);
+
+        if before_start(item(node), start) {
+            next = right;
+        } else {
+            stack.push(node);
+            next = left;
+        }
+    }
+
+    stack
+}
+
+/// The items of an ascending iterator that fall within a range, skipping
+/// those below it one by one, and stopping at the first past it.
+pub struct Range<I, R> {
+    // `None` once past the end of the range
+    iter: Option<I>,
+    range: R,
+}
+
+impl<I, R> Range<I, R> {
+    /// `iter` can start anywhere before the range, e.g. at its lower bound
+    /// for trees that can seek there.
+    #[inline]
+    pub fn new(iter: I, range: R) -> Self {
+        Self {
+            iter: Some(iter),
+            range,
+        }
+    }
+}
+
+impl<I, R, T> Iterator for Range<I, R>
+where
+    I: Iterator,
+    I::Item: Deref<Target = T>,
+    T: Ord + ?Sized,
+    R: RangeBounds<T>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let iter = self.iter.as_mut()?;
+
+        for item in iter {
+            if past_end(&*item, self.range.end_bound()) {
+                break;
+            }
+
+            if !before_start(&*item, self.range.start_bound()) {
+                return Some(item);
+            }
+        }
+
+        self.iter = None;
+        None
+    }
+}
+
+impl<I, R, T> FusedIterator for Range<I, R>
+where
+    I: Iterator,
+    I::Item: Deref<Target = T>,
+    T: Ord + ?Sized,
+    R: RangeBounds<T>,
+{
+}
+
+// The standard library's set, to check the trees against
+impl<T: Ord> OrderedSet for BTreeSet<T> {
+    type Item = T;
+    type ItemRef<'set>
+        = &'set T
+    where
+        T: 'set;
+    type Iter<'set>
+        = btree_set::Iter<'set, T>
+    where
+        T: 'set;
+
+    #[inline]
+    fn insert(&mut self, item: T) -> bool {
+        BTreeSet::insert(self, item)
+    }
+
+    #[inline]
+    fn remove(&mut self, item: &T) -> Option<T> {
+        self.take(item)
+    }
+
+    #[inline]
+    fn contains(&self, item: &T) -> bool {
+        BTreeSet::contains(self, item)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        BTreeSet::len(self)
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        BTreeSet::iter(self)
+    }
+
+    #[inline]
+    fn last(&self) -> Option<&T> {
+        BTreeSet::last(self)
+    }
+
+    type Range<'set, R>
+        = btree_set::Range<'set, T>
+    where
+        T: 'set,
+        R: RangeBounds<T>;
+
+    #[inline]
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_, R> {
+        match is_reversed(&range) {
+            true => btree_set::Range::default(),
+            false => BTreeSet::range(self, range),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill<S: OrderedSet<Item = u32> + Default>(items: impl IntoIterator<Item = u32>) -> S {
+        let mut set = S::default();
+        items.into_iter().for_each(|item| {
+            set.insert(item);
+        });
+        set
+    }
+
+    fn collect<I>(items: I) -> Vec<u32>
+    where
+        I: Iterator,
+        I::Item: Deref<Target = u32>,
+    {
+        items.map(|item| *item).collect()
+    }
+
+    #[test]
+    fn through_the_trait() {
+        let mut set: BTreeSet<u32> = fill([5, 1, 4, 1, 3]);
+
+        assert_eq!(OrderedSet::len(&set), 4);
+        assert!(!OrderedSet::insert(&mut set, 4));
+        assert!(OrderedSet::contains(&set, &3));
+        assert_eq!(OrderedSet::remove(&mut set, &3), Some(3));
+        assert_eq!(OrderedSet::remove(&mut set, &3), None);
+
+        assert_eq!(OrderedSet::first(&set), Some(&1));
+        assert_eq!(OrderedSet::last(&set), Some(&5));
+        assert_eq!(collect(OrderedSet::iter(&set)), [1, 4, 5]);
+    }
+
+    #[test]
+    fn ranges() {
+        let set: BTreeSet<u32> = fill((0..20).map(|i| i * 7 % 20));
+        let range = |bounds: (Bound<u32>, Bound<u32>)| collect(OrderedSet::range(&set, bounds));
+
+        assert_eq!(collect(OrderedSet::range(&set, ..=2)), [0, 1, 2]);
+        assert_eq!(collect(OrderedSet::range(&set, 18..)), [18, 19]);
+        assert_eq!(OrderedSet::range(&set, ..).count(), 20);
+
+        assert_eq!(range((Bound::Excluded(3), Bound::Excluded(6))), [4, 5]);
+        assert!(range((Bound::Included(6), Bound::Excluded(6))).is_empty());
+        assert!(range((Bound::Included(30), Bound::Unbounded)).is_empty());
+
+        // Where BTreeSet's own `range` would panic
+        assert!(range((Bound::Included(6), Bound::Included(5))).is_empty());
+        assert!(range((Bound::Excluded(6), Bound::Excluded(6))).is_empty());
+    }
+
+    #[test]
+    fn skipping_up_to_ranges() {
+        let items = [1, 3, 5, 7, 9];
+
+        assert_eq!(collect(Range::new(items.iter(), 4..8)), [5, 7]);
+        assert_eq!(collect(Range::new(items[2..].iter(), 4..=9)), [5, 7, 9]);
+        let reversed = (Bound::Included(8), Bound::Excluded(4));
+        assert_eq!(collect(Range::new(items.iter(), reversed)), []);
+        assert!(is_reversed(&reversed) && !is_reversed(&(4..4)) && !is_reversed(&(4..)));
+    }
+
+    #[test]
+    fn seeking() {
+        //     5
+        //    / \
+        //   3   8
+        //  /   /
+        // 1   7
+        let nodes = [
+            (5, Some(1), Some(3)),
+            (3, Some(2), None),
+            (1, None, None),
+            (8, Some(4), None),
+            (7, None, None),
+        ];
+        let child = |index: Option<usize>| index.map(|index| &nodes[index]);
+        let stack = |start| -> Vec<u32> {
+            seek(
+                Some(&nodes[0]),
+                |node| [child(node.1), child(node.2)],
+                |node| &node.0,
+                start,
+            )
+            .into_iter()
+            .map(|node| node.0)
+            .collect()
+        };
+
+        assert_eq!(stack(Bound::Unbounded), [5, 3, 1]);
+        assert_eq!(stack(Bound::Included(&3)), [5, 3]);
+        assert_eq!(stack(Bound::Excluded(&3)), [5]);
+        assert_eq!(stack(Bound::Included(&6)), [8, 7]);
+        assert_eq!(stack(Bound::Excluded(&8)), []);
+        assert_eq!(
+            seek(
+                None::<&(u32, Option<usize>, Option<usize>)>,
+                |_| [None, None],
+                |node| &node.0,
+                Bound::Unbounded
+            )
+            .len(),
+            0
+        );
+    }
+
+    crate::conformance_suite!(BTreeSet<u32>);
+}
@@ -0,0 +1,692 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::iter::{FromIterator, IntoIterator};
+use std::ops::{Deref, RangeBounds};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use avl_rotation::Rotate;
+use ordered_set::OrderedSet;
+use tree_view::{Node, Shape, TreeView};
+
+/// The kind of shared pointer linking nodes together. Old and new
+/// versions of a tree share every subtree an update didn't touch.
+pub trait SharedPointer {
+    type Ptr<N>: Deref<Target = N> + Clone;
+
+    fn new<N>(node: N) -> Self::Ptr<N>;
+
+    fn ptr_eq<N>(a: &Self::Ptr<N>, b: &Self::Ptr<N>) -> bool;
+}
+
+/// Single threaded sharing through `Rc`.
+#[derive(Clone, Copy, Debug)]
+pub struct RcPointer;
+
+/// `Arc` links, making the tree `Send + Sync` whenever `T` is.
+#[derive(Clone, Copy, Debug)]
+pub struct ArcPointer;
+
+impl SharedPointer for RcPointer {
+    type Ptr<N> = Rc<N>;
+
+    #[inline]
+    fn new<N>(node: N) -> Rc<N> {
+        Rc::new(node)
+    }
+
+    #[inline]
+    fn ptr_eq<N>(a: &Rc<N>, b: &Rc<N>) -> bool {
+        Rc::ptr_eq(a, b)
+    }
+}
+
+impl SharedPointer for ArcPointer {
+    type Ptr<N> = Arc<N>;
+
+    #[inline]
+    fn new<N>(node: N) -> Arc<N> {
+        Arc::new(node)
+    }
+
+    #[inline]
+    fn ptr_eq<N>(a: &Arc<N>, b: &Arc<N>) -> bool {
+        Arc::ptr_eq(a, b)
+    }
+}
+
+struct TreeNode<T: Clone + Ord, P: SharedPointer> {
+    item: T,
+    left: Tree<T, P>,
+    right: Tree<T, P>,
+    height: usize,
+}
+
+struct Tree<T: Clone + Ord, P: SharedPointer>(Option<P::Ptr<TreeNode<T, P>>>);
+
+impl<T: Clone + Ord, P: SharedPointer> Clone for Tree<T, P> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Tree(self.0.clone())
+    }
+}
+
+impl<T: Clone + Ord, P: SharedPointer> Default for Tree<T, P> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<T: Clone + Ord, P: SharedPointer> From<TreeNode<T, P>> for Tree<T, P> {
+    fn from(node: TreeNode<T, P>) -> Self {
+        Tree(Some(P::new(node)))
+    }
+}
+
+impl<T: Clone + Ord, P: SharedPointer> TreeNode<T, P> {
+    // Nodes are never modified once shared, so the height is worked out
+    // once, from children that are already final
+    fn new(item: T, left: Tree<T, P>, right: Tree<T, P>) -> Self {
+        let height = 1 + left.height().max(right.height());
+
+        Self {
+            item,
+            left,
+            right,
+            height,
+        }
+    }
+}
+
+// Rotating takes a node out of its link by copying it, which clones the
+// pointers to its children but leaves the children themselves shared
+impl<T: Clone + Ord, P: SharedPointer> Rotate for TreeNode<T, P> {
+    type Link = Tree<T, P>;
+
+    #[inline]
+    fn left(&mut self) -> &mut Tree<T, P> {
+        &mut self.left
+    }
+
+    #[inline]
+    fn right(&mut self) -> &mut Tree<T, P> {
+        &mut self.right
+    }
+
+    fn from_link(link: Tree<T, P>) -> Option<Self> {
+        let node = link.0.as_deref()?;
+        Some(Self::new(
+            node.item.clone(),
+            node.left.clone(),
+            node.right.clone(),
+        ))
+    }
+
+    #[inline]
+    fn into_link(self) -> Tree<T, P> {
+        self.into()
+    }
+
+    #[inline]
+    fn update_height(&mut self) {
+        self.height = 1 + self.left.height().max(self.right.height());
+    }
+}
+
+// Every update copies the path from the root down to the change and
+// reuses (clones the pointer to) everything hanging off that path
+impl<T: Clone + Ord, P: SharedPointer> Tree<T, P> {
+    #[inline]
+    fn height(&self) -> usize {
+        self.0.as_deref().map_or(0, |n| n.height)
+    }
+
+    fn search(&self, target: &T) -> Option<&TreeNode<T, P>> {
+        let mut tree = self;
+
+        while let Some(node) = tree.0.as_deref() {
+            match target.cmp(&node.item) {
+                Ordering::Less => tree = &node.left,
+                Ordering::Greater => tree = &node.right,
+                Ordering::Equal => break,
+            }
+        }
+
+        tree.0.as_deref()
+    }
+
+    // The same rotations as box_bintree, on copies of the nodes they
+    // change, since the originals may belong to older versions
+    fn rotate_left(item: T, left: Tree<T, P>, right: Tree<T, P>) -> Self {
+        avl_rotation::rotate_left(TreeNode::new(item, left, right)).into()
+    }
+
+    fn rotate_right(item: T, left: Tree<T, P>, right: Tree<T, P>) -> Self {
+        avl_rotation::rotate_right(TreeNode::new(item, left, right)).into()
+    }
+
+    fn calculate_balance(left: &Tree<T, P>, right: &Tree<T, P>) -> i8 {
+        right.height() as i8 - left.height() as i8
+    }
+
+    // Builds a node out of an item and two subtrees whose heights differ
+    // by at most two, rotating if they differ by exactly two
+    fn balanced(item: T, left: Tree<T, P>, right: Tree<T, P>) -> Self {
+        match Self::calculate_balance(&left, &right) {
+            -2 => {
+                let l = left.0.as_deref().unwrap();
+
+                // Left-right case
+                if Self::calculate_balance(&l.left, &l.right) > 0 {
+                    let left = Self::rotate_left(l.item.clone(), l.left.clone(), l.right.clone());
+                    return Self::rotate_right(item, left, right);
+                }
+
+                Self::rotate_right(item, left, right)
+            }
+
+            2 => {
+                let r = right.0.as_deref().unwrap();
+
+                // Right-left case
+                if Self::calculate_balance(&r.left, &r.right) < 0 {
+                    let right = Self::rotate_right(r.item.clone(), r.left.clone(), r.right.clone());
+                    return Self::rotate_left(item, left, right);
+                }
+
+                Self::rotate_left(item, left, right)
+            }
+
+            (-1..=1) => TreeNode::new(item, left, right).into(),
+
+            _ => unreachable!(),
+        }
+    }
+
+    // None when the item is already there, so the caller can keep
+    // sharing the old root as-is
+    fn with_child(&self, new_item: T) -> Option<Self> {
+        let Some(node) = self.0.as_deref() else {
+            return Some(TreeNode::new(new_item, Tree(None), Tree(None)).into());
+        };
+
+        match new_item.cmp(&node.item) {
+            Ordering::Less => {
+                let left = node.left.with_child(new_item)?;
+                Some(Self::balanced(node.item.clone(), left, node.right.clone()))
+            }
+
+            Ordering::Greater => {
+                let right = node.right.with_child(new_item)?;
+                Some(Self::balanced(node.item.clone(), node.left.clone(), right))
+            }
+
+            Ordering::Equal => None,
+        }
+    }
+
+    // Returns the leftmost item alongside the subtree without it
+    fn without_leftmost(&self) -> Option<(T, Self)> {
+        let node = self.0.as_deref()?;
+
+        match node.left.without_leftmost() {
+            None => Some((node.item.clone(), node.right.clone())),
+
+            Some((leftmost, left)) => Some((
+                leftmost,
+                Self::balanced(node.item.clone(), left, node.right.clone()),
+            )),
+        }
+    }
+
+    fn without_child(&self, target: &T) -> Option<Self> {
+        let node = self.0.as_deref()?;
+
+        match target.cmp(&node.item) {
+            Ordering::Less => {
+                let left = node.left.without_child(target)?;
+                Some(Self::balanced(node.item.clone(), left, node.right.clone()))
+            }
+
+            Ordering::Greater => {
+                let right = node.right.without_child(target)?;
+                Some(Self::balanced(node.item.clone(), node.left.clone(), right))
+            }
+
+            Ordering::Equal => match node.right.without_leftmost() {
+                None => Some(node.left.clone()),
+
+                Some((successor, right)) => {
+                    Some(Self::balanced(successor, node.left.clone(), right))
+                }
+            },
+        }
+    }
+}
+
+pub struct PersistentTree<T: Clone + Ord, P: SharedPointer = RcPointer> {
+    root: Tree<T, P>,
+    size: usize,
+}
+
+pub type RcTree<T> = PersistentTree<T, RcPointer>;
+pub type ArcTree<T> = PersistentTree<T, ArcPointer>;
+
+impl<T: Clone + Ord, P: SharedPointer> Default for PersistentTree<T, P> {
+    fn default() -> Self {
+        Self {
+            root: Tree::default(),
+            size: 0,
+        }
+    }
+}
+
+/// Cloning a version is O(1), it only bumps the root's reference count.
+impl<T: Clone + Ord, P: SharedPointer> Clone for PersistentTree<T, P> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            size: self.size,
+        }
+    }
+}
+
+impl<T: Clone + Ord, P: SharedPointer> PersistentTree<T, P> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[inline]
+    pub fn search(&self, target: &T) -> Option<&T> {
+        self.root.search(target).map(|node| &node.item)
+    }
+
+    /// Returns a new version with `new_item` added, leaving this one
+    /// untouched. Copies O(log n) nodes and shares the rest.
+    #[inline]
+    pub fn insert(&self, new_item: T) -> Self {
+        match self.root.with_child(new_item) {
+            None => self.clone(),
+
+            Some(root) => Self {
+                root,
+                size: self.size + 1,
+            },
+        }
+    }
+
+    /// Returns a new version without `target`, leaving this one
+    /// untouched. Copies O(log n) nodes and shares the rest.
+    #[inline]
+    pub fn remove(&self, target: &T) -> Self {
+        match self.root.without_child(target) {
+            None => self.clone(),
+
+            Some(root) => Self {
+                root,
+                size: self.size - 1,
+            },
+        }
+    }
+
+    /// Whether both versions are literally the same tree, in O(1).
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.root.0, &other.root.0) {
+            (Some(a), Some(b)) => P::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T: Clone + Ord, P: SharedPointer> PartialEq for PersistentTree<T, P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || (self.size == other.size && self.iter().eq(other.iter()))
+    }
+}
+
+impl<T: Clone + Ord, P: SharedPointer> Eq for PersistentTree<T, P> {}
+
+impl<T: Clone + Ord + Debug, P: SharedPointer> Debug for PersistentTree<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+pub struct InorderIter<'tree, T: Clone + Ord, P: SharedPointer> {
+    curr_node: Option<&'tree TreeNode<T, P>>,
+    node_stack: Vec<&'tree TreeNode<T, P>>,
+}
+
+impl<'tree, T: Clone + Ord, P: SharedPointer> PersistentTree<T, P> {
+    pub fn iter(&'tree self) -> InorderIter<'tree, T, P> {
+        InorderIter {
+            curr_node: self.root.0.as_deref(),
+            node_stack: vec![],
+        }
+    }
+
+    /// Yields the items within `range` of this version, in ascending order.
+    pub fn range<R>(&'tree self, range: R) -> ordered_set::Range<InorderIter<'tree, T, P>, R>
+    where
+        R: RangeBounds<T>,
+    {
+        let node_stack = ordered_set::seek(
+            self.root.0.as_deref(),
+            |node| [node.left.0.as_deref(), node.right.0.as_deref()],
+            |node| &node.item,
+            range.start_bound(),
+        );
+
+        let iter = InorderIter {
+            curr_node: None,
+            node_stack,
+        };
+        ordered_set::Range::new(iter, range)
+    }
+}
+
+impl<'tree, T: Clone + Ord, P: SharedPointer> Iterator for InorderIter<'tree, T, P> {
+    type Item = &'tree T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.curr_node {
+            self.curr_node = node.left.0.as_deref();
+            self.node_stack.push(node);
+        }
+
+        if let Some(popped_node) = self.node_stack.pop() {
+            self.curr_node = popped_node.right.0.as_deref();
+            return Some(&popped_node.item);
+        }
+
+        None
+    }
+}
+
+impl<'tree, T: Clone + Ord, P: SharedPointer> IntoIterator for &'tree PersistentTree<T, P> {
+    type Item = &'tree T;
+    type IntoIter = InorderIter<'tree, T, P>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Clone + Ord, P: SharedPointer> FromIterator<T> for PersistentTree<T, P> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(Self::new(), |tree, item| tree.insert(item))
+    }
+}
+
+// Swaps in the new version on every change, which only ever copies the
+// path to the changed node
+impl<T: Clone + Ord, P: SharedPointer> OrderedSet for PersistentTree<T, P> {
+    type Item = T;
+    type ItemRef<'set>
+        = &'set T
+    where
+        Self: 'set;
+    type Iter<'set>
+        = InorderIter<'set, T, P>
+    where
+        Self: 'set;
+    type Range<'set, R>
+        = ordered_set::Range<InorderIter<'set, T, P>, R>
+    where
+        Self: 'set,
+        R: RangeBounds<T>;
+
+    #[inline]
+    fn insert(&mut self, item: T) -> bool {
+        let size = self.size();
+        *self = PersistentTree::insert(self, item);
+        self.size() != size
+    }
+
+    #[inline]
+    fn remove(&mut self, item: &T) -> Option<T> {
+        let removed = self.search(item)?.clone();
+        *self = PersistentTree::remove(self, item);
+        Some(removed)
+    }
+
+    #[inline]
+    fn contains(&self, item: &T) -> bool {
+        self.search(item).is_some()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.size()
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+
+    #[inline]
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_, R> {
+        self.range(range)
+    }
+}
+
+impl<T: Clone + Ord + Debug, P: SharedPointer> TreeView for PersistentTree<T, P> {
+    fn shape(&self) -> Shape {
+        Shape::new(
+            self.root.0.as_deref(),
+            |node| [node.left.0.as_deref(), node.right.0.as_deref()],
+            |node| {
+                Node::new(&node.item)
+                    .note("h", node.height)
+                    .note("bf", Tree::calculate_balance(&node.left, &node.right))
+            },
+        )
+    }
+}
+
+impl<T: Clone + Ord + Debug, P: SharedPointer> fmt::Display for PersistentTree<T, P> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.pretty(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ordered_set::conformance_suite!(RcTree<u32>);
+
+    impl<T: Clone + Ord, P: SharedPointer> PersistentTree<T, P> {
+        fn is_balanced(&self) -> bool {
+            fn check<T: Clone + Ord, P: SharedPointer>(tree: &Tree<T, P>) -> bool {
+                let Some(node) = tree.0.as_deref() else {
+                    return true;
+                };
+
+                let balance = Tree::calculate_balance(&node.left, &node.right);
+
+                (-1..=1).contains(&balance)
+                    && node.height == 1 + node.left.height().max(node.right.height())
+                    && check(&node.left)
+                    && check(&node.right)
+            }
+
+            check(&self.root)
+        }
+
+        // Number of nodes reachable from both versions
+        fn shared_nodes(&self, other: &Self) -> usize {
+            fn collect<'a, T: Clone + Ord, P: SharedPointer>(
+                tree: &'a Tree<T, P>,
+                out: &mut Vec<&'a P::Ptr<TreeNode<T, P>>>,
+            ) {
+                if let Some(ptr) = tree.0.as_ref() {
+                    out.push(ptr);
+                    collect(&ptr.left, out);
+                    collect(&ptr.right, out);
+                }
+            }
+
+            let (mut mine, mut theirs) = (vec![], vec![]);
+            collect(&self.root, &mut mine);
+            collect(&other.root, &mut theirs);
+
+            mine.iter()
+                .filter(|a| theirs.iter().any(|b| P::ptr_eq(a, b)))
+                .count()
+        }
+    }
+
+    #[test]
+    fn constructor() {
+        let tree: RcTree<i32> = PersistentTree::new();
+        assert_eq!(tree, tree.clone());
+        assert_eq!(tree, PersistentTree::default());
+
+        let tree: RcTree<u16> = vec![20, 10, 30].into_iter().collect();
+        assert!(tree.iter().copied().eq([10, 20, 30]));
+        assert!((&tree).into_iter().eq(tree.iter()));
+    }
+
+    #[test]
+    fn versions_stay_valid() {
+        let empty: RcTree<u32> = PersistentTree::new();
+        let one = empty.insert(1);
+        let two = one.insert(2);
+        let three = two.insert(3);
+        let removed = three.remove(&2);
+
+        assert_eq!(empty.size(), 0);
+        assert!(one.iter().copied().eq([1]));
+        assert!(two.iter().copied().eq([1, 2]));
+        assert!(three.iter().copied().eq([1, 2, 3]));
+        assert!(removed.iter().copied().eq([1, 3]));
+
+        assert_eq!(three.search(&2), Some(&2));
+        assert_eq!(removed.search(&2), None);
+    }
+
+    #[test]
+    fn no_op_updates_share_everything() {
+        let tree: RcTree<u32> = (0..100).collect();
+
+        assert!(tree.insert(50).ptr_eq(&tree));
+        assert!(tree.remove(&500).ptr_eq(&tree));
+        assert!(tree.clone().ptr_eq(&tree));
+        assert!(!tree.insert(500).ptr_eq(&tree));
+    }
+
+    #[test]
+    fn structural_sharing() {
+        let tree: RcTree<u32> = (0..1_023).collect();
+        let inserted = tree.insert(2_000);
+        let removed = tree.remove(&511);
+
+        // Only a root-to-leaf path (plus a rotation or two) gets copied
+        let height = tree.root.height();
+        assert!(tree.shared_nodes(&inserted) >= tree.size() - 2 * height);
+        assert!(tree.shared_nodes(&removed) >= tree.size() - 2 * height);
+
+        assert_eq!(tree.size(), 1_023);
+        assert_eq!(inserted.size(), 1_024);
+        assert_eq!(removed.size(), 1_022);
+    }
+
+    #[test]
+    fn maintaining_balance() {
+        let mut versions: Vec<RcTree<u32>> = vec![PersistentTree::new()];
+
+        for item in (0..300).chain((300..600).rev()) {
+            let next = versions.last().unwrap().insert(item);
+            assert!(next.is_balanced());
+            versions.push(next);
+        }
+
+        let mut tree = versions.last().unwrap().clone();
+
+        for item in (0..600).filter(|i| i % 3 == 0) {
+            tree = tree.remove(&item);
+            assert!(tree.is_balanced());
+        }
+
+        assert!(tree.iter().copied().eq((0..600).filter(|i| i % 3 != 0)));
+
+        // Every historical version is still intact
+        for (size, version) in versions.iter().enumerate() {
+            assert_eq!(version.size(), size);
+            assert_eq!(version.iter().count(), size);
+        }
+    }
+
+    #[test]
+    fn rotating() {
+        // Right, left, left-right and right-left, mirroring the
+        // box_bintree cases
+        let cases: [([u32; 3], u32); 4] = [
+            ([3, 2, 1], 2),
+            ([1, 2, 3], 2),
+            ([5, 3, 4], 4),
+            ([3, 5, 4], 4),
+        ];
+
+        for (case, expected_root) in cases {
+            let tree: RcTree<u32> = case.into_iter().collect();
+            let root = tree.root.0.as_deref().unwrap();
+
+            assert!(tree.is_balanced());
+            assert_eq!(root.item, expected_root);
+            assert_eq!(root.height, 2);
+        }
+    }
+
+    #[test]
+    fn sharing_across_threads() {
+        let base: ArcTree<u32> = (0..100).collect();
+
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let base = base.clone();
+                std::thread::spawn(move || {
+                    let mine = base.insert(1_000 + t).remove(&t);
+                    (mine.size(), mine.search(&(1_000 + t)).copied())
+                })
+            })
+            .collect();
+
+        for (t, handle) in handles.into_iter().enumerate() {
+            assert_eq!(handle.join().unwrap(), (100, Some(1_000 + t as u32)));
+        }
+
+        assert!(base.iter().copied().eq(0..100));
+    }
+
+    #[test]
+    fn send_sync() {
+        fn is_send<T: Send>() {}
+        fn is_sync<T: Sync>() {}
+
+        is_send::<ArcTree<i32>>();
+        is_sync::<ArcTree<i32>>();
+    }
+
+    #[test]
+    fn exporting_dot() {
+        let tree: RcTree<u32> = (1..=3).collect();
+        let dot = tree.to_dot();
+
+        assert!(dot.contains("n0 [label=\"2\\nh=2 bf=0\"];"));
+        assert!(dot.contains("n1 [label=\"1\\nh=1 bf=0\"];\n    n0:sw -> n1;"));
+        assert!(dot.contains("n4 [label=\"3\\nh=1 bf=0\"];\n    n0:se -> n4;"));
+    }
+}
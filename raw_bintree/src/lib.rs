@@ -1,10 +1,27 @@
+#![warn(clippy::undocumented_unsafe_blocks)]
+
 use std::cmp::Ordering;
+use std::fmt::{self, Debug};
 use std::iter::{FromIterator, IntoIterator};
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
 use std::ptr;
 
-// type Tree<T> = *mut TreeNode<T>;
+use ordered_set::OrderedSet;
+use tree_view::{Node, Shape, TreeView};
+
+#[cfg(test)]
+mod unsafe_paths;
 
-#[derive(PartialEq, Eq, Clone, Debug)]
+// Either null or a node made by `From<TreeNode>`. Each node is owned by
+// exactly one link, the root or its parent's `left` or `right`, and its
+// `parent` points back at that parent (null for the root). Every pointer
+// is copied from the one `Box::into_raw` made, never derived from a
+// reference, and nodes are only written through while the whole tree is
+// borrowed mutably, so reading through the links of a borrowed tree is
+// always sound.
+#[derive(PartialEq, Eq, Debug)]
 struct Tree<T: Ord>(*mut TreeNode<T>);
 
 impl<T: Ord> Default for Tree<T> {
@@ -14,30 +31,33 @@ impl<T: Ord> Default for Tree<T> {
     }
 }
 
-// impl<T: Ord> Drop for Tree<T> {
-//     fn drop(&mut self) {
-//         if !self.0.is_null() {
-//             let _ = unsafe {*self.0.drop_in_place()}
-//         }
-//     }
-// }
+// Trees are just (possibly null) pointers, copying one never copies nodes
+impl<T: Ord> Clone for Tree<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: Ord> Copy for Tree<T> {}
 
-#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(PartialEq, Eq, Debug)]
 struct TreeNode<T: Ord> {
     item: T,
     left: Tree<T>,
     right: Tree<T>,
+    parent: Tree<T>,
     height: usize
 }
 
-impl<T: Ord> Into<Tree<T>> for TreeNode<T> {
+impl<T: Ord> From<TreeNode<T>> for Tree<T> {
     #[inline]
-    fn into(self) -> Tree<T> {
-        Tree(Box::into_raw(Box::new(self)))
+    fn from(node: TreeNode<T>) -> Self {
+        Tree(Box::into_raw(Box::new(node)))
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(Debug)]
 pub struct BinaryTree<T: Ord> {
     root: Tree<T>,
     size: usize,
@@ -53,94 +73,259 @@ impl<T: Ord> Default for BinaryTree<T> {
 }
 
 impl<T: Ord> TreeNode<T> {
-    fn new(item: T) -> Self {
+    fn new(item: T, parent: Tree<T>) -> Self {
         Self {
             item,
             left: Tree(ptr::null_mut()),
             right: Tree(ptr::null_mut()),
+            parent,
             height: 1
         }
     }
 }
 
 impl<T: Ord> Tree<T> {
+    #[inline]
+    fn is_null(self) -> bool {
+        self.0.is_null()
+    }
+
     fn search(&self, target: &T) -> Option<&TreeNode<T>> {
+        // SAFETY: `find` hands back null or a node of this tree, which
+        // stays borrowed along with `self`
+        unsafe { self.find(target).0.as_ref() }
+    }
+
+    fn find(self, target: &T) -> Tree<T> {
         let mut tree_ptr = self.0;
 
+        // SAFETY: only ever null or a node of this tree (see `Tree`)
         while let Some(node) = unsafe { tree_ptr.as_ref() } {
             match target.cmp(&node.item) {
                 Ordering::Less => tree_ptr = node.left.0,
                 Ordering::Greater => tree_ptr = node.right.0,
-                Ordering::Equal => {
-                    return Some(&node)
-                }
+                Ordering::Equal => break,
             }
         }
 
-        None
+        Tree(tree_ptr)
     }
 
-    fn add_child(&mut self, new_item: T) {
+    // The first node not below `start`, or null if there's none
+    fn lower_bound(self, start: Bound<&T>) -> Tree<T> {
         let mut tree = self;
+        let mut found = Tree::default();
 
-        while let Some(node) = unsafe { tree.0.as_mut() } {
-
-            match new_item.cmp(&node.item) {
-                Ordering::Less => tree = &mut node.left,
-                Ordering::Greater => tree = &mut node.right,
-                Ordering::Equal => { return; }
+        // SAFETY: only ever null or a node of this tree (see `Tree`)
+        while let Some(node) = unsafe { tree.0.as_ref() } {
+            if ordered_set::before_start(&node.item, start) {
+                tree = node.right;
+            } else {
+                found = tree;
+                tree = node.left;
             }
         }
 
-        *tree = TreeNode::new(new_item).into();
+        found
     }
 
-    fn remove_leftmost_child(&mut self) -> Option<TreeNode<T>> {
+    // The last node not past `end`, or null if there's none
+    fn upper_bound(self, end: Bound<&T>) -> Tree<T> {
+        let mut tree = self;
+        let mut found = Tree::default();
+
+        // SAFETY: only ever null or a node of this tree (see `Tree`)
+        while let Some(node) = unsafe { tree.0.as_ref() } {
+            if ordered_set::past_end(&node.item, end) {
+                tree = node.left;
+            } else {
+                found = tree;
+                tree = node.right;
+            }
+        }
 
-        let mut tree = self.0;
+        found
+    }
 
+    fn add_child(&mut self, new_item: T) -> bool {
+        let mut parent = Tree::default();
+        let mut link: *mut Tree<T> = self;
+
+        // Walking down by raw pointer alone: reborrowing a node as `&mut`
+        // to reach its child link would invalidate `parent`, which is a
+        // copy of the very pointer that node came from.
+        //
+        // SAFETY: `link` starts at `self` and only ever moves to a child
+        // link of a live node owned by this tree, and `&mut self` keeps
+        // anything else from touching the tree meanwhile.
         unsafe {
-            if let Some(node) = tree.as_mut()  {
+            while !(*link).is_null() {
+                parent = *link;
+                let node = parent.0;
+
+                link = match new_item.cmp(&(*node).item) {
+                    Ordering::Less => ptr::addr_of_mut!((*node).left),
+                    Ordering::Greater => ptr::addr_of_mut!((*node).right),
+                    Ordering::Equal => return false,
+                };
+            }
+
+            *link = TreeNode::new(new_item, parent).into();
+        }
 
-                loop {
-                    match (node.left.0.as_mut(), node.right.0.as_mut()) {
-                        (Some(_), _) => tree = node.left.0 ,
+        true
+    }
 
-                        (None, None) => break,
+    // Both of these expect a non-null tree
+    fn leftmost(self) -> Tree<T> {
+        let mut tree = self;
 
-                        (None, Some(_)) => { 
-                            node.left.0.replace(node.right.0.read());
-                        }
-                    }
-                };
+        // SAFETY: only ever null or a node of this tree (see `Tree`)
+        while let Some(node) = unsafe { tree.0.as_ref() } {
+            if node.left.is_null() {
+                break;
+            }
+            tree = node.left;
+        }
 
+        tree
+    }
 
-                None
-            } else {
-                None
+    fn rightmost(self) -> Tree<T> {
+        let mut tree = self;
+
+        // SAFETY: only ever null or a node of this tree (see `Tree`)
+        while let Some(node) = unsafe { tree.0.as_ref() } {
+            if node.right.is_null() {
+                break;
             }
+            tree = node.right;
         }
 
+        tree
+    }
 
-        // if let None = unsafe { self.0.as_mut() } {
-        //     return None;
-        // }
+    /*
+     * Without a right subtree, the successor is the first ancestor
+     * we reach by climbing out of a left subtree:
+     *
+     *          S
+     *         /
+     *        X
+     *         \
+     *          N   <- climb from N until we come up a left edge
+     */
+    fn successor(self) -> Tree<T> {
+        // SAFETY: null or a node of this tree (see `Tree`)
+        let Some(node) = (unsafe { self.0.as_ref() }) else {
+            return self;
+        };
+
+        if !node.right.is_null() {
+            return node.right.leftmost();
+        }
 
-        // let mut tree = self.0;
+        let mut child = self;
+        let mut parent = node.parent;
 
+        // SAFETY: parent links are null or nodes of the same tree
+        while let Some(p) = unsafe { parent.0.as_ref() } {
+            if p.right != child {
+                break;
+            }
+            child = parent;
+            parent = p.parent;
+        }
+
+        parent
+    }
+
+    fn predecessor(self) -> Tree<T> {
+        // SAFETY: null or a node of this tree (see `Tree`)
+        let Some(node) = (unsafe { self.0.as_ref() }) else {
+            return self;
+        };
 
+        if !node.left.is_null() {
+            return node.left.rightmost();
+        }
 
-        // while let Some(node) = unsafe { tree.as_mut() } {
-        //     if !node.left.0.is_null() {
-        //         tree = node.left.0;
-        //     } else if !node.right.0.is_null() {
-        //         let ret_node = unsafe { node.left.0.replace(node.right.0.read()) };
-        //     } else {
+        let mut child = self;
+        let mut parent = node.parent;
 
-        //     }
-        // }
+        // SAFETY: parent links are null or nodes of the same tree
+        while let Some(p) = unsafe { parent.0.as_ref() } {
+            if p.left != child {
+                break;
+            }
+            child = parent;
+            parent = p.parent;
+        }
 
-        // None
+        parent
+    }
+
+    // Hangs `new` where `old` used to be, self being the root of the
+    // whole tree. Neither subtree's own children are touched.
+    fn transplant(&mut self, old: Tree<T>, new: Tree<T>) {
+        // SAFETY: `old` is a node of this tree, borrowed mutably as a
+        // whole, and no reference into it is held across this call
+        let parent = unsafe { (*old.0).parent };
+
+        // SAFETY: as above, for `old`'s parent
+        match unsafe { parent.0.as_mut() } {
+            None => *self = new,
+            Some(p) if p.left == old => p.left = new,
+            Some(p) => p.right = new,
+        }
+
+        // SAFETY: `new` is null or a node of this tree, as above
+        if let Some(n) = unsafe { new.0.as_mut() } {
+            n.parent = parent;
+        }
+    }
+
+    // Unlinks a node (non-null, belonging to self) and hands back
+    // ownership of its allocation.
+    fn remove_node(&mut self, target: Tree<T>) -> Box<TreeNode<T>> {
+        // SAFETY: `target` is a node of this tree, which is borrowed
+        // mutably, and no reference into any node outlives a statement
+        let (left, right) = unsafe { ((*target.0).left, (*target.0).right) };
+
+        if left.is_null() {
+            self.transplant(target, right);
+        } else if right.is_null() {
+            self.transplant(target, left);
+        } else {
+            let successor = right.leftmost();
+
+            // SAFETY: `successor`, `right` and `left` are all nodes of this
+            // tree, as `right` and `left` are non-null children of `target`
+            unsafe {
+                if (*successor.0).parent != target {
+                    self.transplant(successor, (*successor.0).right);
+                    (*successor.0).right = right;
+                    (*right.0).parent = successor;
+                }
+
+                self.transplant(target, successor);
+                (*successor.0).left = left;
+                (*left.0).parent = successor;
+            }
+        }
+
+        // SAFETY: `target` came from `Box::into_raw`, and nothing links to
+        // it any more, so this is the only owner left
+        unsafe { Box::from_raw(target.0) }
+    }
+
+    fn remove_leftmost_child(&mut self) -> Option<Box<TreeNode<T>>> {
+        if self.is_null() {
+            return None;
+        }
+
+        let leftmost = self.leftmost();
+        Some(self.remove_node(leftmost))
     }
 }
 
@@ -158,38 +343,130 @@ where
         self.size
     }
 
+    #[inline]
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    #[inline]
+    pub fn search(&self, target: &T) -> Option<&T> {
+        self.root.search(target).map(|node| &node.item)
+    }
+
+    #[inline]
+    pub fn insert(&mut self, new_item: T) {
+        if self.root.add_child(new_item) {
+            self.size += 1;
+        }
+    }
+
+    #[inline]
+    pub fn remove(&mut self, target: &T) -> Option<T> {
+        let node = self.root.find(target);
+
+        if node.is_null() {
+            return None;
+        }
+
+        self.size -= 1;
+        Some(self.root.remove_node(node).item)
+    }
+
     #[inline]
     pub fn remove_inorder(&mut self) -> Option<T> {
-        None
+        self.root.remove_leftmost_child().map(|n| {
+            self.size -= 1;
+            n.item
+        })
     }
 }
 
 impl<T: Ord> Drop for BinaryTree<T> {
     fn drop(&mut self) {
-        while let Some(_) = self.remove_inorder() {}
+        // Post-order teardown, climbing back up through parent links
+        // instead of keeping a stack
+        let mut tree = self.root;
+
+        // SAFETY: `tree` is null or a node still owned by the tree. Links
+        // are cleared on the way down, so a node is only freed once it
+        // has no children left and nothing will reach it again.
+        while let Some(node) = unsafe { tree.0.as_mut() } {
+            if !node.left.is_null() {
+                tree = mem::take(&mut node.left);
+            } else if !node.right.is_null() {
+                tree = mem::take(&mut node.right);
+            } else {
+                let parent = node.parent;
+                // SAFETY: see above, and the node came from `Box::into_raw`
+                drop(unsafe { Box::from_raw(tree.0) });
+                tree = parent;
+            }
+        }
+    }
+}
+
+impl<T: Ord + Clone> Clone for BinaryTree<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: Ord> PartialEq for BinaryTree<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size && self.iter().eq(other.iter())
     }
 }
 
-pub struct InorderIntoIter<T: Ord>(BinaryTree<T>);
+impl<T: Ord> Eq for BinaryTree<T> {}
+
+pub struct InorderIntoIter<T: Ord> {
+    tree: BinaryTree<T>,
+    next: Tree<T>,
+}
 
 impl<T: Ord> Iterator for InorderIntoIter<T> {
     type Item = T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.0.remove_inorder()
+        if self.next.is_null() {
+            return None;
+        }
+
+        // self.next is always the leftmost node, so it has no left child
+        // and its successor survives the unlinking
+        let removed = self.next;
+        self.next = removed.successor();
+        self.tree.size -= 1;
+
+        Some(self.tree.root.remove_node(removed).item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.tree.size, Some(self.tree.size))
     }
 }
 
+impl<T: Ord> ExactSizeIterator for InorderIntoIter<T> {}
+
 pub struct InorderIter<'tree, T: Ord> {
-    curr_node: Option<&'tree TreeNode<T>>,
-    node_stack: Vec<&'tree TreeNode<T>>,
+    front: Tree<T>,
+    back: Tree<T>,
+    remaining: usize,
+    _phantom: PhantomData<&'tree TreeNode<T>>,
 }
 
 impl<'tree, T: Ord> BinaryTree<T> {
     pub fn iter(&'tree self) -> InorderIter<'tree, T> {
+        let (front, back) = match self.root.is_null() {
+            true => (Tree::default(), Tree::default()),
+            false => (self.root.leftmost(), self.root.rightmost()),
+        };
+
         InorderIter {
-            curr_node: unsafe { self.root.0.as_ref() },
-            node_stack: vec![]
+            front,
+            back,
+            remaining: self.size,
+            _phantom: PhantomData,
         }
     }
 }
@@ -198,17 +475,105 @@ impl<'tree, T: Ord> Iterator for InorderIter<'tree, T> {
     type Item = &'tree T;
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(node) = self.curr_node {
-            self.curr_node = unsafe { node.left.0.as_ref() };
-            self.node_stack.push(node);
+        if self.remaining == 0 {
+            return None;
         }
 
-        if let Some(popped_node) = self.node_stack.pop() {
-            self.curr_node = unsafe { popped_node.right.0.as_ref() };
-            return Some(&popped_node.item);
+        // SAFETY: null or a node of the tree, which is borrowed for 'tree
+        let node = unsafe { self.front.0.as_ref() }?;
+
+        self.remaining -= 1;
+        self.front = self.front.successor();
+        Some(&node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'tree, T: Ord> DoubleEndedIterator for InorderIter<'tree, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
         }
 
-        None
+        // SAFETY: null or a node of the tree, which is borrowed for 'tree
+        let node = unsafe { self.back.0.as_ref() }?;
+
+        self.remaining -= 1;
+        self.back = self.back.predecessor();
+        Some(&node.item)
+    }
+}
+
+impl<'tree, T: Ord> ExactSizeIterator for InorderIter<'tree, T> {}
+
+/// The items within a range, from either end.
+pub struct RangeIter<'tree, T: Ord> {
+    // Both null once they've met
+    front: Tree<T>,
+    back: Tree<T>,
+    _phantom: PhantomData<&'tree TreeNode<T>>,
+}
+
+impl<'tree, T: Ord> BinaryTree<T> {
+    /// Yields the items within `range` from either end, both found in O(height).
+    pub fn range<R: RangeBounds<T>>(&'tree self, range: R) -> RangeIter<'tree, T> {
+        let front = self.root.lower_bound(range.start_bound());
+        let back = self.root.upper_bound(range.end_bound());
+
+        // Nothing lies between them if they've crossed, e.g. for a range
+        // that starts after it ends
+        // SAFETY: both are null or nodes of this tree, borrowed along
+        // with `self`
+        let empty = match unsafe { (front.0.as_ref(), back.0.as_ref()) } {
+            (Some(first), Some(last)) => first.item > last.item,
+            _ => true,
+        };
+
+        let (front, back) = match empty {
+            true => (Tree::default(), Tree::default()),
+            false => (front, back),
+        };
+
+        RangeIter {
+            front,
+            back,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'tree, T: Ord> Iterator for RangeIter<'tree, T> {
+    type Item = &'tree T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // SAFETY: null or a node of the tree, which is borrowed for 'tree
+        let node = unsafe { self.front.0.as_ref() }?;
+
+        if self.front == self.back {
+            (self.front, self.back) = (Tree::default(), Tree::default());
+        } else {
+            self.front = self.front.successor();
+        }
+
+        Some(&node.item)
+    }
+}
+
+impl<'tree, T: Ord> DoubleEndedIterator for RangeIter<'tree, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        // SAFETY: null or a node of the tree, which is borrowed for 'tree
+        let node = unsafe { self.back.0.as_ref() }?;
+
+        if self.front == self.back {
+            (self.front, self.back) = (Tree::default(), Tree::default());
+        } else {
+            self.back = self.back.predecessor();
+        }
+
+        Some(&node.item)
     }
 }
 
@@ -217,115 +582,294 @@ impl<T: Ord> IntoIterator for BinaryTree<T> {
     type IntoIter = InorderIntoIter<Self::Item>;
 
     fn into_iter(self) -> Self::IntoIter {
-        InorderIntoIter(self)
+        let next = match self.root.is_null() {
+            true => Tree::default(),
+            false => self.root.leftmost(),
+        };
+
+        InorderIntoIter { tree: self, next }
+    }
+}
+
+impl<T: Ord> FromIterator<T> for BinaryTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree: BinaryTree<T> = Self::new();
+
+        iter.into_iter().for_each(|item| tree.insert(item));
+
+        tree
+    }
+}
+
+impl<T: Ord> OrderedSet for BinaryTree<T> {
+    type Item = T;
+    type ItemRef<'set>
+        = &'set T
+    where
+        Self: 'set;
+    type Iter<'set>
+        = InorderIter<'set, T>
+    where
+        Self: 'set;
+    type Range<'set, R>
+        = RangeIter<'set, T>
+    where
+        Self: 'set,
+        R: RangeBounds<T>;
+
+    #[inline]
+    fn insert(&mut self, item: T) -> bool {
+        let size = self.size();
+        BinaryTree::insert(self, item);
+        self.size() != size
+    }
+
+    #[inline]
+    fn remove(&mut self, item: &T) -> Option<T> {
+        BinaryTree::remove(self, item)
+    }
+
+    #[inline]
+    fn contains(&self, item: &T) -> bool {
+        self.search(item).is_some()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.size()
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+
+    #[inline]
+    fn last(&self) -> Option<&T> {
+        self.iter().next_back()
+    }
+
+    #[inline]
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_, R> {
+        self.range(range)
     }
 }
 
-// impl<T: Ord> FromIterator<T> for BinaryTree<T> {
-//     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-//         let mut tree: BinaryTree<T> = Self::new();
+impl<T: Ord + Debug> TreeView for BinaryTree<T> {
+    fn shape(&self) -> Shape {
+        // SAFETY: every link is null or a node of this tree (see `Tree`),
+        // which stays borrowed along with `self`
+        let root = unsafe { self.root.0.as_ref() };
+
+        Shape::new(
+            root,
+            // SAFETY: as above
+            |node| unsafe { [node.left.0.as_ref(), node.right.0.as_ref()] },
+            |node| Node::new(&node.item),
+        )
+    }
+}
 
-//         iter.into_iter().for_each(|item| tree.insert(item));
+impl<T: Ord + Debug> fmt::Display for BinaryTree<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.pretty(), f)
+    }
+}
 
-//         tree
-//     }
-// }
+#[cfg(test)]
+mod test {
+    use super::*;
 
-// #[cfg(test)]
-// mod test {
-//     use super::*;
+    ordered_set::conformance_suite!(BinaryTree<u32>);
 
-//     #[test]
-//     fn test_constructing() {
-//         let default = BinaryTree::<i32>::default();
-//         let new = BinaryTree::<i32>::new();
+    impl<T: Ord> BinaryTree<T> {
+        // Walks every node checking that each child points back at
+        // the node that owns it
+        pub(super) fn parent_links_consistent(&self) -> bool {
+            let mut stack = vec![self.root];
 
-//         let vec = vec![-1, 0, 1, 2, 3];
-//         // let collect: BinaryTree<i32> = BinaryTree::from_iter(vec);
-//         // let cloned  = collect.clone();
+            // SAFETY: every link of a tree is null or one of its nodes
+            if let Some(root) = unsafe { self.root.0.as_ref() } {
+                if !root.parent.is_null() {
+                    return false;
+                }
+            }
 
-//         assert_eq!(default, new);
-//     }
+            while let Some(tree) = stack.pop() {
+                // SAFETY: as above
+                let Some(node) = (unsafe { tree.0.as_ref() }) else {
+                    continue;
+                };
 
-//     // #[test]
-//     // fn test_coercing() {
-//     //     let mut tree = BinaryTree::<u8>::new();
+                for child in [node.left, node.right] {
+                    // SAFETY: as above
+                    if let Some(c) = unsafe { child.0.as_ref() } {
+                        if c.parent != tree {
+                            return false;
+                        }
+                    }
+                    stack.push(child);
+                }
+            }
 
-//     //     tree.insert(1);
-//     //     tree.insert(2);
-//     //     tree.insert(3);
+            true
+        }
+    }
 
-//     //     assert_eq!(tree.remove_inorder(), Some(1));
-//     // }
+    #[test]
+    fn test_constructing() {
+        let default = BinaryTree::<i32>::default();
+        let new = BinaryTree::<i32>::new();
 
-//     #[test]
-//     fn test_inserting() {
-//         let mut tree: BinaryTree<u32> = BinaryTree::new();
+        let vec = vec![-1, 0, 1, 2, 3];
+        let collect: BinaryTree<i32> = BinaryTree::from_iter(vec);
+        let cloned = collect.clone();
 
-//         tree.insert(3);
-//         tree.insert(1);
-//         tree.insert(2);
+        assert_eq!(default, new);
+        assert_eq!(collect, cloned);
+        assert_eq!(cloned.size(), 5);
+    }
 
-//         assert_eq!(tree.size(), 3);
+    #[test]
+    fn test_coercing() {
+        let mut tree = BinaryTree::<u8>::new();
 
-//         let mut tree_iter = tree.iter();
+        tree.insert(1);
+        tree.insert(2);
+        tree.insert(3);
 
-//         assert_eq!(tree_iter.next(), Some(&1));
-//         assert_eq!(tree_iter.next(), Some(&2));
-//         assert_eq!(tree_iter.next(), Some(&3));
+        assert_eq!(tree.remove_inorder(), Some(1));
+    }
+
+    #[test]
+    fn test_inserting() {
+        let mut tree: BinaryTree<u32> = BinaryTree::new();
+
+        tree.insert(3);
+        tree.insert(1);
+        tree.insert(2);
+
+        assert_eq!(tree.size(), 3);
+
+        let mut tree_iter = tree.iter();
+
+        assert_eq!(tree_iter.next(), Some(&1));
+        assert_eq!(tree_iter.next(), Some(&2));
+        assert_eq!(tree_iter.next(), Some(&3));
+
+        tree = BinaryTree::new();
+
+        tree.insert(5);
+        tree.insert(3);
+        tree.insert(1);
+        tree.insert(2);
+        tree.insert(4);
+        tree.insert(4);
+
+        assert_eq!(tree.size(), 5);
+        assert!(tree.parent_links_consistent());
+
+        tree_iter = tree.iter();
+
+        assert_eq!(tree_iter.next(), Some(&1));
+        assert_eq!(tree_iter.next(), Some(&2));
+        assert_eq!(tree_iter.next(), Some(&3));
+        assert_eq!(tree_iter.next(), Some(&4));
+        assert_eq!(tree_iter.next(), Some(&5));
+    }
 
-//         tree = BinaryTree::new();
+    #[test]
+    fn test_searching() {
+        let tree: BinaryTree<i32> = [50, 25, 75, 10, 40, 60, 90].into_iter().collect();
 
-//         tree.insert(5);
-//         tree.insert(3);
-//         tree.insert(1);
-//         tree.insert(2);
-//         tree.insert(4);
+        assert_eq!(tree.search(&40), Some(&40));
+        assert_eq!(tree.search(&90), Some(&90));
+        assert_eq!(tree.search(&45), None);
+    }
+
+    #[test]
+    fn test_removing() {
+        let mut tree: BinaryTree<u64> = BinaryTree::new();
+
+        tree.insert(5);
+        tree.insert(3);
+        tree.insert(1);
+        tree.insert(2);
+        tree.insert(4);
 
-//         assert_eq!(tree.size(), 5);
+        for val in 1..=5 {
+            assert_eq!(tree.remove_inorder(), Some(val));
+            assert!(tree.parent_links_consistent());
+        }
+
+        assert_eq!(tree.remove_inorder(), None);
+        assert_eq!(tree.size(), 0);
 
-//         tree_iter = tree.iter();
+        // Leaf, single child and both children (with the successor both
+        // directly below and deeper down)
+        let mut tree: BinaryTree<u64> = [50, 25, 75, 10, 40, 60, 90, 55, 65, 62]
+            .into_iter()
+            .collect();
 
-//         assert_eq!(tree_iter.next(), Some(&1));
-//         assert_eq!(tree_iter.next(), Some(&2));
-//         assert_eq!(tree_iter.next(), Some(&3));
-//         assert_eq!(tree_iter.next(), Some(&4));
-//         assert_eq!(tree_iter.next(), Some(&5));
-//     }
+        assert_eq!(tree.remove(&10), Some(10));
+        assert_eq!(tree.remove(&25), Some(25));
+        assert_eq!(tree.remove(&75), Some(75));
+        assert_eq!(tree.remove(&50), Some(50));
+        assert_eq!(tree.remove(&50), None);
+        assert!(tree.parent_links_consistent());
 
-//     #[test]
-//     fn test_removing() {
-//         let mut tree: BinaryTree<u64> = BinaryTree::new();
+        assert_eq!(tree.size(), 6);
+        assert!(tree.iter().eq([40, 55, 60, 62, 65, 90].iter()));
+    }
 
-//         tree.insert(5);
-//         tree.insert(3);
-//         tree.insert(1);
-//         tree.insert(2);
-//         tree.insert(4);
+    #[test]
+    fn test_iterating() {
+        let mut tree: BinaryTree<char> = BinaryTree::new();
 
-//         for val in 1..=5 {
-//             assert_eq!(tree.remove_inorder(), Some(val));
-//         }
+        tree.insert('j');
+        tree.insert('a');
+        tree.insert('d');
 
-//         assert_eq!(tree.remove_inorder(), None);
-//     }
+        // Non-consuming iterator
+        for (test_val, tree_val) in ['a', 'd', 'j'].iter().zip(tree.iter()) {
+            assert_eq!(test_val, tree_val)
+        }
 
-//     #[test]
-//     fn test_iterating() {
-//         let mut tree: BinaryTree<char> = BinaryTree::new();
+        // Both ends, meeting in the middle
+        let mut tree_iter = tree.iter();
+        assert_eq!(tree_iter.len(), 3);
+        assert_eq!(tree_iter.next_back(), Some(&'j'));
+        assert_eq!(tree_iter.next(), Some(&'a'));
+        assert_eq!(tree_iter.next_back(), Some(&'d'));
+        assert_eq!(tree_iter.next(), None);
+        assert_eq!(tree_iter.next_back(), None);
+
+        // Consuming
+        for (test_val, tree_val) in ['a', 'd', 'j'].into_iter().zip(tree) {
+            assert_eq!(test_val, tree_val)
+        }
+    }
 
-//         tree.insert('j');
-//         tree.insert('a');
-//         tree.insert('d');
+    #[test]
+    #[cfg_attr(miri, ignore = "too slow under Miri")]
+    fn test_skewed_trees() {
+        // Long parent chains both ways, which a recursive drop or a
+        // per-item walk from the root would struggle with
+        let ascending: BinaryTree<u32> = (0..10_000).collect();
+        assert!(ascending.iter().copied().eq(0..10_000));
+        assert!(ascending.iter().rev().copied().eq((0..10_000).rev()));
+
+        let descending: BinaryTree<u32> = (0..10_000).rev().collect();
+        assert!(descending.into_iter().eq(0..10_000));
+    }
 
-//         // Non-consuming iterator
-//         for (test_val, tree_val) in ['a', 'd', 'j'].iter().zip(tree.iter()) {
-//             assert_eq!(test_val, tree_val)
-//         }
+    #[test]
+    fn test_exporting_dot() {
+        let dot = [2, 1, 3].into_iter().collect::<BinaryTree<u32>>().to_dot();
 
-//         // Consuming
-//         for (test_val, tree_val) in ['a', 'd', 'j'].into_iter().zip(tree) {
-//             assert_eq!(test_val, tree_val)
-//         }
-//     }
-// }
+        assert!(dot.contains("n0 [label=\"2\"];"));
+        assert!(dot.contains("n1 [label=\"1\"];\n    n0:sw -> n1;"));
+        assert!(dot.contains("n4 [label=\"3\"];\n    n0:se -> n4;"));
+    }
+}
@@ -0,0 +1,263 @@
+//! Tests walking every path through the crate's unsafe code: inserting,
+//! every iterator, removing every shape of node and dropping. They're kept
+//! small enough for Miri, under both of its aliasing models, and are meant
+//! for the address and leak sanitizers too:
+//!
+//! ```sh
+//! cargo +nightly miri test -p raw_bintree unsafe_paths
+//! MIRIFLAGS=-Zmiri-tree-borrows cargo +nightly miri test -p raw_bintree unsafe_paths
+//! RUSTFLAGS=-Zsanitizer=address cargo +nightly test -p raw_bintree --lib \
+//!     --target x86_64-unknown-linux-gnu unsafe_paths
+//! RUSTFLAGS=-Zsanitizer=leak cargo +nightly test -p raw_bintree --lib \
+//!     --target x86_64-unknown-linux-gnu unsafe_paths
+//! ```
+
+use std::cell::Cell;
+use std::cmp::Ordering;
+
+use super::*;
+
+// Boxed so that a node freed too early is a use after free, and counting
+// its drops so that leaks and double frees fail even without a sanitizer
+#[derive(Debug)]
+struct Counted<'a> {
+    key: Box<u32>,
+    drops: &'a Cell<usize>,
+}
+
+impl<'a> Counted<'a> {
+    fn new(key: u32, drops: &'a Cell<usize>) -> Self {
+        Self {
+            key: Box::new(key),
+            drops,
+        }
+    }
+}
+
+impl Drop for Counted<'_> {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+    }
+}
+
+impl PartialEq for Counted<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for Counted<'_> {}
+
+impl PartialOrd for Counted<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Counted<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+fn keys(tree: &BinaryTree<Counted<'_>>) -> Vec<u32> {
+    tree.iter().map(|item| *item.key).collect()
+}
+
+fn filled<'a>(keys: &[u32], drops: &'a Cell<usize>) -> BinaryTree<Counted<'a>> {
+    keys.iter().map(|&key| Counted::new(key, drops)).collect()
+}
+
+// Shaped so that removing these in order hits every case of `remove_node`:
+//
+//            50
+//          /    \
+//        20      80
+//       /  \    /  \
+//     10   30  60   90
+//       \       \
+//       15      70
+//               /
+//             65
+const SHAPED: [u32; 10] = [50, 20, 80, 10, 30, 60, 90, 15, 70, 65];
+
+#[test]
+fn inserting() {
+    let drops = Cell::new(0);
+    let mut tree = BinaryTree::new();
+
+    // Into the empty root, then down both sides and back
+    for key in [2, 1, 4, 3, 5] {
+        tree.insert(Counted::new(key, &drops));
+        assert!(tree.parent_links_consistent());
+    }
+
+    // A duplicate is dropped straight away, leaving the original in place
+    let three = Counted::new(3, &drops);
+    let original: *const Counted<'_> = tree.search(&three).unwrap();
+    tree.insert(Counted::new(3, &drops));
+    assert_eq!(drops.get(), 1);
+    assert!(std::ptr::eq(tree.search(&three).unwrap(), original));
+
+    assert_eq!(keys(&tree), [1, 2, 3, 4, 5]);
+    assert_eq!(tree.size(), 5);
+
+    drop(tree);
+    assert_eq!(drops.get(), 1 + 5);
+}
+
+#[test]
+fn iterating() {
+    let drops = Cell::new(0);
+    let empty: BinaryTree<Counted<'_>> = BinaryTree::new();
+    assert!(empty.iter().next().is_none());
+    assert!(empty.iter().next_back().is_none());
+
+    let single = filled(&[1], &drops);
+    let mut iter = single.iter();
+    assert_eq!(iter.next_back().map(|item| *item.key), Some(1));
+    assert!(iter.next().is_none());
+
+    // Several iterators over the same nodes at once, one of them walking
+    // in from both ends until they meet, climbing through parent links
+    let tree = filled(&SHAPED, &drops);
+    let (mut both, all) = (tree.iter(), tree.iter());
+    let mut met = Vec::new();
+
+    while let Some(front) = both.next() {
+        met.push(*front.key);
+        if let Some(back) = both.next_back() {
+            met.push(*back.key);
+        }
+    }
+
+    assert_eq!(met, [10, 90, 15, 80, 20, 70, 30, 65, 50, 60]);
+    assert!(all
+        .rev()
+        .map(|item| *item.key)
+        .eq([90, 80, 70, 65, 60, 50, 30, 20, 15, 10]));
+    assert!(both.next().is_none() && both.next_back().is_none());
+}
+
+#[test]
+fn ranging() {
+    let tree: BinaryTree<u32> = SHAPED.into_iter().collect();
+
+    // From both ends until they meet, across the root
+    let mut range = tree.range(15..=65);
+    assert_eq!(range.next(), Some(&15));
+    assert_eq!(range.next_back(), Some(&65));
+    assert!(range.eq(&[20, 30, 50, 60]));
+
+    // Bounds between items and past either end
+    assert!(tree.range(11..64).rev().eq(&[60, 50, 30, 20, 15]));
+    assert!(tree.range(..).eq(tree.iter()));
+    assert_eq!(tree.range(91..).next(), None);
+
+    // Nothing between the bounds, or bounds the wrong way round
+    assert_eq!(tree.range(31..50).next_back(), None);
+    assert_eq!(
+        tree.range((Bound::Included(60), Bound::Excluded(50)))
+            .next(),
+        None
+    );
+
+    let mut single = tree.range(50..=50);
+    assert_eq!(single.next_back(), Some(&50));
+    assert_eq!(single.next(), None);
+}
+
+#[test]
+fn removing() {
+    let drops = Cell::new(0);
+    let mut tree = filled(&SHAPED, &drops);
+
+    // A missing key leaves everything be
+    assert!(tree.remove(&Counted::new(40, &drops)).is_none());
+    assert_eq!(drops.get(), 1);
+
+    // A leaf, a node with a right child only, one with a left child only,
+    // two children with the successor right below, then further down,
+    // and then the root with two children, one and none
+    let order = [90, 10, 70, 20, 50, 60, 80, 65, 30, 15];
+
+    for (removed, key) in order.into_iter().enumerate() {
+        let item = tree.remove(&Counted::new(key, &drops)).unwrap();
+        assert_eq!(*item.key, key);
+        assert!(tree.parent_links_consistent());
+        assert_eq!(tree.size(), SHAPED.len() - removed - 1);
+        drop(item);
+    }
+
+    assert!(tree.iter().next().is_none());
+    assert_eq!(drops.get(), 1 + 2 * SHAPED.len());
+
+    // Emptied trees are as good as new ones
+    tree.insert(Counted::new(1, &drops));
+    assert_eq!(keys(&tree), [1]);
+}
+
+#[test]
+fn removing_in_order() {
+    let drops = Cell::new(0);
+    let mut tree = filled(&SHAPED, &drops);
+    let mut sorted = SHAPED;
+    sorted.sort_unstable();
+
+    for key in sorted {
+        assert_eq!(tree.remove_inorder().map(|item| *item.key), Some(key));
+        assert!(tree.parent_links_consistent());
+    }
+
+    assert!(tree.remove_inorder().is_none());
+    assert_eq!(drops.get(), SHAPED.len());
+}
+
+#[test]
+fn consuming() {
+    let drops = Cell::new(0);
+    let empty: BinaryTree<Counted<'_>> = BinaryTree::new();
+    assert!(empty.into_iter().next().is_none());
+
+    let mut sorted = SHAPED;
+    sorted.sort_unstable();
+
+    let all = filled(&SHAPED, &drops);
+    assert!(all.into_iter().map(|item| *item.key).eq(sorted));
+    assert_eq!(drops.get(), SHAPED.len());
+
+    // Dropped part of the way through, which has to free the rest
+    let mut partly = filled(&SHAPED, &drops).into_iter();
+    assert_eq!(partly.nth(3).map(|item| *item.key), Some(sorted[3]));
+    assert_eq!(partly.len(), SHAPED.len() - 4);
+    drop(partly);
+    assert_eq!(drops.get(), 2 * SHAPED.len());
+}
+
+#[test]
+fn dropping() {
+    let drops = Cell::new(0);
+
+    // Lopsided both ways and balanced, each torn down through parent links
+    let shapes: [&[u32]; 4] = [&[], &[1, 2, 3, 4, 5], &[5, 4, 3, 2, 1], &SHAPED];
+    let mut made = 0;
+
+    for shape in shapes {
+        drop(filled(shape, &drops));
+        made += shape.len();
+        assert_eq!(drops.get(), made);
+    }
+
+    // Cleared after being partly emptied, which drops every item once,
+    // plus the one made to look 50 up
+    let mut tree = filled(&SHAPED, &drops);
+    drop(tree.remove(&Counted::new(50, &drops)));
+    drop(tree.remove_inorder());
+    tree.clear();
+    assert_eq!(drops.get(), made + SHAPED.len() + 1);
+
+    let tree: BinaryTree<String> = SHAPED.iter().map(u32::to_string).collect();
+    let cloned = tree.clone();
+    drop(tree);
+    assert_eq!(cloned.size(), SHAPED.len());
+}
@@ -0,0 +1,1158 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::iter::{FromIterator, IntoIterator};
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+use std::ptr;
+
+use ordered_set::OrderedSet;
+use tree_view::{self, Node, Shape, TreeView};
+
+/* RB NOTES
+ * The properties of an RB tree follow a few key rules:
+ * 1. Every node is either red or black
+ * 2. The root is always black
+ * 3. Every NULL node is considered black
+ * 4. If a node is red, both children are black (no path can contain
+ *    continguous red nodes)
+ * 5. Every path from root to a NULL has same number of black nodes
+ */
+
+#[repr(u8)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum Color {
+    Red,
+    Black,
+}
+
+impl Color {
+    fn swap(&mut self) {
+        *self = match self {
+            Color::Red => Color::Black,
+            Color::Black => Color::Red,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug)]
+struct Tree<T: Ord>(*mut TreeNode<T>);
+
+impl<T: Ord> Default for Tree<T> {
+    #[inline]
+    fn default() -> Self {
+        Self(ptr::null_mut())
+    }
+}
+
+impl<T: Ord> Clone for Tree<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: Ord> Copy for Tree<T> {}
+
+#[derive(Debug)]
+struct TreeNode<T: Ord> {
+    item: T,
+    left: Tree<T>,
+    right: Tree<T>,
+    parent: Tree<T>,
+    color: Color,
+}
+
+impl<T: Ord> TreeNode<T> {
+    fn new(item: T, parent: Tree<T>) -> Self {
+        Self {
+            item,
+            left: Tree::default(),
+            right: Tree::default(),
+            parent,
+            color: Color::Red,
+        }
+    }
+}
+
+impl<T: Ord> From<TreeNode<T>> for Tree<T> {
+    #[inline]
+    fn from(node: TreeNode<T>) -> Self {
+        Tree(Box::into_raw(Box::new(node)))
+    }
+}
+
+/// Which of the red-black rules (or plain BST rules) a tree breaks,
+/// as reported by [`RbTree::validate`].
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Violation {
+    RedRoot,
+    RedWithRedChild,
+    UnequalBlackHeight,
+    OutOfOrder,
+    BrokenParentLink,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            Violation::RedRoot => "root is red",
+            Violation::RedWithRedChild => "red node has a red child",
+            Violation::UnequalBlackHeight => "paths to NULL differ in black height",
+            Violation::OutOfOrder => "items are not in search order",
+            Violation::BrokenParentLink => "child does not point back at its parent",
+        };
+
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for Violation {}
+
+// Null pointers double as the black NULL leaves, so every accessor here
+// has to tolerate them (rule 3)
+impl<T: Ord> Tree<T> {
+    #[inline]
+    fn is_null(self) -> bool {
+        self.0.is_null()
+    }
+
+    #[inline]
+    fn color(self) -> Color {
+        unsafe { self.0.as_ref() }.map_or(Color::Black, |n| n.color)
+    }
+
+    #[inline]
+    fn set_color(self, color: Color) {
+        if let Some(node) = unsafe { self.0.as_mut() } {
+            node.color = color;
+        }
+    }
+
+    #[inline]
+    fn flip_color(self) {
+        if let Some(node) = unsafe { self.0.as_mut() } {
+            node.color.swap();
+        }
+    }
+
+    #[inline]
+    fn parent(self) -> Tree<T> {
+        unsafe { self.0.as_ref() }.map_or(Tree::default(), |n| n.parent)
+    }
+
+    #[inline]
+    fn left(self) -> Tree<T> {
+        unsafe { self.0.as_ref() }.map_or(Tree::default(), |n| n.left)
+    }
+
+    #[inline]
+    fn right(self) -> Tree<T> {
+        unsafe { self.0.as_ref() }.map_or(Tree::default(), |n| n.right)
+    }
+
+    fn find(self, target: &T) -> Tree<T> {
+        let mut tree = self;
+
+        while let Some(node) = unsafe { tree.0.as_ref() } {
+            match target.cmp(&node.item) {
+                Ordering::Less => tree = node.left,
+                Ordering::Greater => tree = node.right,
+                Ordering::Equal => break,
+            }
+        }
+
+        tree
+    }
+
+    // The first node not below `start`, or null if there's none
+    fn lower_bound(self, start: Bound<&T>) -> Tree<T> {
+        let mut tree = self;
+        let mut found = Tree::default();
+
+        while let Some(node) = unsafe { tree.0.as_ref() } {
+            if ordered_set::before_start(&node.item, start) {
+                tree = node.right;
+            } else {
+                found = tree;
+                tree = node.left;
+            }
+        }
+
+        found
+    }
+
+    // The last node not past `end`, or null if there's none
+    fn upper_bound(self, end: Bound<&T>) -> Tree<T> {
+        let mut tree = self;
+        let mut found = Tree::default();
+
+        while let Some(node) = unsafe { tree.0.as_ref() } {
+            if ordered_set::past_end(&node.item, end) {
+                tree = node.left;
+            } else {
+                found = tree;
+                tree = node.right;
+            }
+        }
+
+        found
+    }
+
+    fn leftmost(self) -> Tree<T> {
+        let mut tree = self;
+
+        while !tree.left().is_null() {
+            tree = tree.left();
+        }
+
+        tree
+    }
+
+    fn rightmost(self) -> Tree<T> {
+        let mut tree = self;
+
+        while !tree.right().is_null() {
+            tree = tree.right();
+        }
+
+        tree
+    }
+
+    fn successor(self) -> Tree<T> {
+        if !self.right().is_null() {
+            return self.right().leftmost();
+        }
+
+        let mut child = self;
+        let mut parent = self.parent();
+
+        while !parent.is_null() && parent.right() == child {
+            child = parent;
+            parent = parent.parent();
+        }
+
+        parent
+    }
+
+    fn predecessor(self) -> Tree<T> {
+        if !self.left().is_null() {
+            return self.left().rightmost();
+        }
+
+        let mut child = self;
+        let mut parent = self.parent();
+
+        while !parent.is_null() && parent.left() == child {
+            child = parent;
+            parent = parent.parent();
+        }
+
+        parent
+    }
+
+    // The methods below are called on the root of the whole tree, since
+    // any of them may end up replacing it
+
+    fn replace_child(&mut self, parent: Tree<T>, old: Tree<T>, new: Tree<T>) {
+        match unsafe { parent.0.as_mut() } {
+            None => *self = new,
+            Some(p) if p.left == old => p.left = new,
+            Some(p) => p.right = new,
+        }
+    }
+
+    fn transplant(&mut self, old: Tree<T>, new: Tree<T>) {
+        let parent = old.parent();
+
+        self.replace_child(parent, old, new);
+
+        if let Some(n) = unsafe { new.0.as_mut() } {
+            n.parent = parent;
+        }
+    }
+
+    /*
+     *          P
+     *        /   \
+     *       /     \
+     *      T1      X
+     *             / \
+     *            /   \
+     *           Y    T2
+     *
+     *
+     *          X
+     *        /   \
+     *       /     \
+     *      P      T2
+     *     / \
+     *    T1  Y
+     */
+    fn rotate_left(&mut self, pivot: Tree<T>) {
+        let x = pivot.right();
+        let y = x.left();
+
+        unsafe {
+            (*pivot.0).right = y;
+            if let Some(y) = y.0.as_mut() {
+                y.parent = pivot;
+            }
+
+            self.transplant(pivot, x);
+
+            (*x.0).left = pivot;
+            (*pivot.0).parent = x;
+        }
+    }
+
+    fn rotate_right(&mut self, pivot: Tree<T>) {
+        let x = pivot.left();
+        let y = x.right();
+
+        unsafe {
+            (*pivot.0).left = y;
+            if let Some(y) = y.0.as_mut() {
+                y.parent = pivot;
+            }
+
+            self.transplant(pivot, x);
+
+            (*x.0).right = pivot;
+            (*pivot.0).parent = x;
+        }
+    }
+
+    fn add_child(&mut self, new_item: T) -> bool {
+        let mut parent = Tree::default();
+        let mut tree = *self;
+
+        while let Some(node) = unsafe { tree.0.as_ref() } {
+            parent = tree;
+
+            match new_item.cmp(&node.item) {
+                Ordering::Less => tree = node.left,
+                Ordering::Greater => tree = node.right,
+                Ordering::Equal => return false,
+            }
+        }
+
+        let greater = unsafe { parent.0.as_ref() }.is_some_and(|p| new_item > p.item);
+        let new_node: Tree<T> = TreeNode::new(new_item, parent).into();
+
+        match unsafe { parent.0.as_mut() } {
+            None => *self = new_node,
+            Some(p) if greater => p.right = new_node,
+            Some(p) => p.left = new_node,
+        }
+
+        self.insert_fixup(new_node);
+        true
+    }
+
+    // Cases as laid out in the README
+    fn insert_fixup(&mut self, mut node: Tree<T>) {
+        loop {
+            let mut parent = node.parent();
+
+            // Case 1: node is the root, paint it black
+            if parent.is_null() {
+                node.set_color(Color::Black);
+                return;
+            }
+
+            // Case 2: parent is black, nothing violated
+            if parent.color() == Color::Black {
+                return;
+            }
+
+            // Case 3: parent is red, so it isn't the root and the
+            // grandparent must be black
+            let grandparent = parent.parent();
+            let parent_is_left = grandparent.left() == parent;
+
+            let uncle = match parent_is_left {
+                true => grandparent.right(),
+                false => grandparent.left(),
+            };
+
+            // 3.1: parent and uncle red, flip all three colors to push
+            // the red up a level
+            if uncle.color() == Color::Red {
+                parent.flip_color();
+                uncle.flip_color();
+                grandparent.flip_color();
+                node = grandparent;
+                continue;
+            }
+
+            // 3.2: parent red, uncle black/NULL, rotate and recolor
+            if parent_is_left {
+                // 3.2.4: parent left, child right, straighten into 3.2.2
+                if parent.right() == node {
+                    self.rotate_left(parent);
+                    parent = node;
+                }
+
+                // 3.2.2: left, left
+                self.rotate_right(grandparent);
+            } else {
+                // 3.2.3: parent right, child left, straighten into 3.2.1
+                if parent.left() == node {
+                    self.rotate_right(parent);
+                    parent = node;
+                }
+
+                // 3.2.1: right, right
+                self.rotate_left(grandparent);
+            }
+
+            parent.set_color(Color::Black);
+            grandparent.set_color(Color::Red);
+            return;
+        }
+    }
+
+    fn remove_node(&mut self, target: Tree<T>) -> Box<TreeNode<T>> {
+        let (left, right) = (target.left(), target.right());
+
+        // The node that actually leaves its position, and the subtree
+        // that slides into it (possibly NULL, hence also tracking where
+        // that subtree now hangs)
+        let removed_color;
+        let replacement;
+        let replacement_parent;
+
+        if left.is_null() || right.is_null() {
+            removed_color = target.color();
+            replacement = if left.is_null() { right } else { left };
+            replacement_parent = target.parent();
+
+            self.transplant(target, replacement);
+        } else {
+            let successor = right.leftmost();
+
+            removed_color = successor.color();
+            replacement = successor.right();
+
+            unsafe {
+                if successor.parent() == target {
+                    replacement_parent = successor;
+                } else {
+                    replacement_parent = successor.parent();
+
+                    self.transplant(successor, replacement);
+                    (*successor.0).right = right;
+                    (*right.0).parent = successor;
+                }
+
+                self.transplant(target, successor);
+                (*successor.0).left = left;
+                (*left.0).parent = successor;
+                (*successor.0).color = target.color();
+            }
+        }
+
+        if removed_color == Color::Black {
+            self.remove_fixup(replacement, replacement_parent);
+        }
+
+        unsafe { Box::from_raw(target.0) }
+    }
+
+    // `node` carries an extra black that has to be pushed up or absorbed
+    fn remove_fixup(&mut self, mut node: Tree<T>, mut parent: Tree<T>) {
+        while node != *self && node.color() == Color::Black {
+            if parent.left() == node {
+                let mut sibling = parent.right();
+
+                if sibling.color() == Color::Red {
+                    sibling.set_color(Color::Black);
+                    parent.set_color(Color::Red);
+                    self.rotate_left(parent);
+                    sibling = parent.right();
+                }
+
+                if sibling.left().color() == Color::Black && sibling.right().color() == Color::Black
+                {
+                    sibling.set_color(Color::Red);
+                    node = parent;
+                    parent = node.parent();
+                    continue;
+                }
+
+                if sibling.right().color() == Color::Black {
+                    sibling.left().set_color(Color::Black);
+                    sibling.set_color(Color::Red);
+                    self.rotate_right(sibling);
+                    sibling = parent.right();
+                }
+
+                sibling.set_color(parent.color());
+                parent.set_color(Color::Black);
+                sibling.right().set_color(Color::Black);
+                self.rotate_left(parent);
+            } else {
+                let mut sibling = parent.left();
+
+                if sibling.color() == Color::Red {
+                    sibling.set_color(Color::Black);
+                    parent.set_color(Color::Red);
+                    self.rotate_right(parent);
+                    sibling = parent.left();
+                }
+
+                if sibling.left().color() == Color::Black && sibling.right().color() == Color::Black
+                {
+                    sibling.set_color(Color::Red);
+                    node = parent;
+                    parent = node.parent();
+                    continue;
+                }
+
+                if sibling.left().color() == Color::Black {
+                    sibling.right().set_color(Color::Black);
+                    sibling.set_color(Color::Red);
+                    self.rotate_left(sibling);
+                    sibling = parent.left();
+                }
+
+                sibling.set_color(parent.color());
+                parent.set_color(Color::Black);
+                sibling.left().set_color(Color::Black);
+                self.rotate_right(parent);
+            }
+
+            node = *self;
+        }
+
+        node.set_color(Color::Black);
+    }
+
+    // Returns the black height of the subtree, counting the NULL leaves
+    fn validate(self, lower: Option<&T>, upper: Option<&T>) -> Result<usize, Violation> {
+        let Some(node) = (unsafe { self.0.as_ref() }) else {
+            return Ok(1);
+        };
+
+        if lower.is_some_and(|l| node.item <= *l) || upper.is_some_and(|u| node.item >= *u) {
+            return Err(Violation::OutOfOrder);
+        }
+
+        for child in [node.left, node.right] {
+            if !child.is_null() && child.parent() != self {
+                return Err(Violation::BrokenParentLink);
+            }
+
+            if node.color == Color::Red && child.color() == Color::Red {
+                return Err(Violation::RedWithRedChild);
+            }
+        }
+
+        let left_height = node.left.validate(lower, Some(&node.item))?;
+        let right_height = node.right.validate(Some(&node.item), upper)?;
+
+        if left_height != right_height {
+            return Err(Violation::UnequalBlackHeight);
+        }
+
+        Ok(left_height + (node.color == Color::Black) as usize)
+    }
+}
+
+pub struct RbTree<T: Ord> {
+    root: Tree<T>,
+    size: usize,
+}
+
+// The tree uniquely owns every node behind its pointers, same as a
+// Box-based tree would
+unsafe impl<T: Ord + Send> Send for RbTree<T> {}
+unsafe impl<T: Ord + Sync> Sync for RbTree<T> {}
+
+impl<T: Ord> Default for RbTree<T> {
+    fn default() -> Self {
+        Self {
+            root: Tree::default(),
+            size: 0,
+        }
+    }
+}
+
+impl<T: Ord> RbTree<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    #[inline]
+    pub fn search(&self, target: &T) -> Option<&T> {
+        unsafe { self.root.find(target).0.as_ref() }.map(|node| &node.item)
+    }
+
+    #[inline]
+    pub fn insert(&mut self, new_item: T) {
+        if self.root.add_child(new_item) {
+            self.size += 1;
+        }
+    }
+
+    #[inline]
+    pub fn remove(&mut self, target: &T) -> Option<T> {
+        let node = self.root.find(target);
+
+        if node.is_null() {
+            return None;
+        }
+
+        self.size -= 1;
+        Some(self.root.remove_node(node).item)
+    }
+
+    #[inline]
+    pub fn remove_inorder(&mut self) -> Option<T> {
+        if self.root.is_null() {
+            return None;
+        }
+
+        let leftmost = self.root.leftmost();
+
+        self.size -= 1;
+        Some(self.root.remove_node(leftmost).item)
+    }
+
+    /// Checks all five colour rules from the notes above, along with
+    /// search order and parent links.
+    pub fn validate(&self) -> Result<(), Violation> {
+        if self.root.color() == Color::Red {
+            return Err(Violation::RedRoot);
+        }
+
+        if !self.root.parent().is_null() {
+            return Err(Violation::BrokenParentLink);
+        }
+
+        self.root.validate(None, None).map(|_| ())
+    }
+}
+
+impl<T: Ord> Drop for RbTree<T> {
+    fn drop(&mut self) {
+        let mut tree = self.root;
+
+        while let Some(node) = unsafe { tree.0.as_mut() } {
+            if !node.left.is_null() {
+                tree = mem::take(&mut node.left);
+            } else if !node.right.is_null() {
+                tree = mem::take(&mut node.right);
+            } else {
+                let parent = node.parent;
+                drop(unsafe { Box::from_raw(tree.0) });
+                tree = parent;
+            }
+        }
+    }
+}
+
+impl<T: Ord + Clone> Clone for RbTree<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: Ord> PartialEq for RbTree<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Ord> Eq for RbTree<T> {}
+
+impl<T: Ord + Debug> Debug for RbTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+pub struct InorderIntoIter<T: Ord>(RbTree<T>);
+
+impl<T: Ord> Iterator for InorderIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.remove_inorder()
+    }
+}
+
+pub struct InorderIter<'tree, T: Ord> {
+    front: Tree<T>,
+    back: Tree<T>,
+    remaining: usize,
+    _phantom: PhantomData<&'tree TreeNode<T>>,
+}
+
+impl<'tree, T: Ord> RbTree<T> {
+    pub fn iter(&'tree self) -> InorderIter<'tree, T> {
+        InorderIter {
+            front: self.root.leftmost(),
+            back: self.root.rightmost(),
+            remaining: self.size,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'tree, T: Ord> Iterator for InorderIter<'tree, T> {
+    type Item = &'tree T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let node = unsafe { self.front.0.as_ref() }?;
+
+        self.remaining -= 1;
+        self.front = self.front.successor();
+        Some(&node.item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'tree, T: Ord> DoubleEndedIterator for InorderIter<'tree, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let node = unsafe { self.back.0.as_ref() }?;
+
+        self.remaining -= 1;
+        self.back = self.back.predecessor();
+        Some(&node.item)
+    }
+}
+
+impl<'tree, T: Ord> ExactSizeIterator for InorderIter<'tree, T> {}
+
+/// The items within a range, from either end.
+pub struct RangeIter<'tree, T: Ord> {
+    // Both null once they've met
+    front: Tree<T>,
+    back: Tree<T>,
+    _phantom: PhantomData<&'tree TreeNode<T>>,
+}
+
+impl<'tree, T: Ord> RbTree<T> {
+    /// Yields the items within `range` from either end, both found in O(log n).
+    pub fn range<R: RangeBounds<T>>(&'tree self, range: R) -> RangeIter<'tree, T> {
+        let front = self.root.lower_bound(range.start_bound());
+        let back = self.root.upper_bound(range.end_bound());
+
+        // Nothing lies between them if they've crossed, e.g. for a range
+        // that starts after it ends
+        let empty = match unsafe { (front.0.as_ref(), back.0.as_ref()) } {
+            (Some(first), Some(last)) => first.item > last.item,
+            _ => true,
+        };
+
+        let (front, back) = match empty {
+            true => (Tree::default(), Tree::default()),
+            false => (front, back),
+        };
+
+        RangeIter {
+            front,
+            back,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'tree, T: Ord> Iterator for RangeIter<'tree, T> {
+    type Item = &'tree T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = unsafe { self.front.0.as_ref() }?;
+
+        if self.front == self.back {
+            (self.front, self.back) = (Tree::default(), Tree::default());
+        } else {
+            self.front = self.front.successor();
+        }
+
+        Some(&node.item)
+    }
+}
+
+impl<'tree, T: Ord> DoubleEndedIterator for RangeIter<'tree, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let node = unsafe { self.back.0.as_ref() }?;
+
+        if self.front == self.back {
+            (self.front, self.back) = (Tree::default(), Tree::default());
+        } else {
+            self.back = self.back.predecessor();
+        }
+
+        Some(&node.item)
+    }
+}
+
+impl<T: Ord> IntoIterator for RbTree<T> {
+    type Item = T;
+    type IntoIter = InorderIntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        InorderIntoIter(self)
+    }
+}
+
+impl<T: Ord> FromIterator<T> for RbTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree: RbTree<T> = Self::new();
+        iter.into_iter().for_each(|item| tree.insert(item));
+        tree
+    }
+}
+
+impl<T: Ord> OrderedSet for RbTree<T> {
+    type Item = T;
+    type ItemRef<'set>
+        = &'set T
+    where
+        Self: 'set;
+    type Iter<'set>
+        = InorderIter<'set, T>
+    where
+        Self: 'set;
+    type Range<'set, R>
+        = RangeIter<'set, T>
+    where
+        Self: 'set,
+        R: RangeBounds<T>;
+
+    #[inline]
+    fn insert(&mut self, item: T) -> bool {
+        let size = self.size();
+        RbTree::insert(self, item);
+        self.size() != size
+    }
+
+    #[inline]
+    fn remove(&mut self, item: &T) -> Option<T> {
+        RbTree::remove(self, item)
+    }
+
+    #[inline]
+    fn contains(&self, item: &T) -> bool {
+        self.search(item).is_some()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.size()
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+
+    #[inline]
+    fn last(&self) -> Option<&T> {
+        self.iter().next_back()
+    }
+
+    #[inline]
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_, R> {
+        self.range(range)
+    }
+}
+
+impl<T: Ord + Debug> TreeView for RbTree<T> {
+    fn shape(&self) -> Shape {
+        // SAFETY: every link is null or a node of this tree, which stays
+        // borrowed along with `self`
+        let root = unsafe { self.root.0.as_ref() };
+
+        Shape::new(
+            root,
+            // SAFETY: as above
+            |node| unsafe { [node.left.0.as_ref(), node.right.0.as_ref()] },
+            |node| {
+                let color = match node.color {
+                    Color::Red => tree_view::Color::Red,
+                    Color::Black => tree_view::Color::Black,
+                };
+                Node::new(&node.item).color(color)
+            },
+        )
+    }
+}
+
+impl<T: Ord + Debug> fmt::Display for RbTree<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.pretty(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ordered_set::conformance_suite!(RbTree<u32>);
+
+    impl<T: Ord + Copy> RbTree<T> {
+        // (item, color) pairs in preorder, to pin down exact shapes
+        fn preorder(&self) -> Vec<(T, Color)> {
+            let mut out = vec![];
+            let mut stack = vec![self.root];
+
+            while let Some(tree) = stack.pop() {
+                if let Some(node) = unsafe { tree.0.as_ref() } {
+                    out.push((node.item, node.color));
+                    stack.push(node.right);
+                    stack.push(node.left);
+                }
+            }
+
+            out
+        }
+    }
+
+    // Tiny xorshift so the shuffled workloads are the same every run
+    fn shuffled(n: u32, mut seed: u64) -> Vec<u32> {
+        let mut items: Vec<u32> = (0..n).collect();
+
+        for i in (1..items.len()).rev() {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            items.swap(i, (seed % (i as u64 + 1)) as usize);
+        }
+
+        items
+    }
+
+    use Color::{Black as B, Red as R};
+
+    #[test]
+    fn color_swap() {
+        let mut color = Color::Red;
+        color.swap();
+        assert_eq!(color, Color::Black);
+        color.swap();
+        assert_eq!(color, Color::Red);
+    }
+
+    #[test]
+    fn constructor() {
+        let tree: RbTree<i32> = RbTree::new();
+        assert_eq!(tree, tree.clone());
+        assert_eq!(tree, RbTree::default());
+        assert_eq!(tree.validate(), Ok(()));
+
+        let tree: RbTree<u16> = vec![20, 10, 30].into_iter().collect();
+        assert_eq!(tree, tree.clone());
+
+        for (tree_val, num) in tree.into_iter().zip([10, 20, 30]) {
+            assert_eq!(tree_val, num);
+        }
+    }
+
+    #[test]
+    fn insertion_cases() {
+        let mut tree: RbTree<u32> = RbTree::new();
+
+        // Case 1: empty tree, root comes out black
+        tree.insert(10);
+        assert_eq!(tree.preorder(), [(10, B)]);
+
+        // Case 2: black parent
+        tree.insert(5);
+        tree.insert(15);
+        assert_eq!(tree.preorder(), [(10, B), (5, R), (15, R)]);
+
+        // 3.1: red parent and uncle, recolor
+        tree.insert(1);
+        assert_eq!(tree.preorder(), [(10, B), (5, B), (1, R), (15, B)]);
+
+        // 3.2.2: left, left
+        tree.insert(0);
+        assert_eq!(tree.preorder(), [(10, B), (1, B), (0, R), (5, R), (15, B)]);
+
+        // 3.2.1: right, right
+        let mut tree: RbTree<u32> = [1, 2, 3].into_iter().collect();
+        assert_eq!(tree.preorder(), [(2, B), (1, R), (3, R)]);
+
+        // 3.2.3: parent right, child left
+        tree = [1, 3, 2].into_iter().collect();
+        assert_eq!(tree.preorder(), [(2, B), (1, R), (3, R)]);
+
+        // 3.2.4: parent left, child right
+        tree = [3, 1, 2].into_iter().collect();
+        assert_eq!(tree.preorder(), [(2, B), (1, R), (3, R)]);
+
+        tree.insert(2);
+        assert_eq!(tree.size(), 3);
+    }
+
+    #[test]
+    fn searching() {
+        let tree: RbTree<u32> = shuffled(500, 7).into_iter().collect();
+
+        assert_eq!(tree.search(&0), Some(&0));
+        assert_eq!(tree.search(&250), Some(&250));
+        assert_eq!(tree.search(&499), Some(&499));
+        assert_eq!(tree.search(&500), None);
+    }
+
+    #[test]
+    fn iterating() {
+        let tree: RbTree<u32> = shuffled(100, 3).into_iter().collect();
+
+        assert!(tree.iter().copied().eq(0..100));
+        assert!(tree.iter().rev().copied().eq((0..100).rev()));
+        assert_eq!(tree.iter().len(), 100);
+        assert!(tree.into_iter().eq(0..100));
+    }
+
+    #[test]
+    fn ranging() {
+        let tree: RbTree<u32> = shuffled(100, 5).into_iter().map(|i| i * 2).collect();
+
+        let mut range = tree.range(11..=40);
+        assert_eq!(range.next(), Some(&12));
+        assert_eq!(range.next_back(), Some(&40));
+        assert!(range.copied().eq((14..40).step_by(2)));
+
+        assert!(tree.range(..7).rev().copied().eq([6, 4, 2, 0]));
+        assert_eq!(tree.range(199..).next(), None);
+        assert_eq!(tree.range(41..42).next(), None);
+        assert_eq!(
+            tree.range((Bound::Included(50), Bound::Excluded(40)))
+                .next_back(),
+            None
+        );
+    }
+
+    #[test]
+    fn removing() {
+        let mut tree: RbTree<u32> = (0..8).collect();
+
+        assert_eq!(tree.remove(&8), None);
+        assert_eq!(tree.remove(&3), Some(3));
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.remove_inorder(), Some(0));
+        assert_eq!(tree.validate(), Ok(()));
+        assert_eq!(tree.size(), 6);
+        assert!(tree.iter().copied().eq([1, 2, 4, 5, 6, 7]));
+
+        tree.clear();
+        assert_eq!(tree.remove_inorder(), None);
+        assert_eq!(tree.size(), 0);
+    }
+
+    #[test]
+    fn maintaining_invariants() {
+        let mut tree: RbTree<u32> = RbTree::new();
+
+        // Failures draw the tree, colours and all
+        for item in shuffled(2_000, 42) {
+            tree.insert(item);
+            assert_eq!(tree.validate(), Ok(()), "\n{}", tree);
+        }
+
+        for item in shuffled(2_000, 99).into_iter().filter(|i| i % 3 != 0) {
+            assert_eq!(tree.remove(&item), Some(item));
+            assert_eq!(tree.validate(), Ok(()), "\n{}", tree);
+        }
+
+        assert!(tree.iter().copied().eq((0..2_000).filter(|i| i % 3 == 0)));
+
+        while tree.remove_inorder().is_some() {
+            assert_eq!(tree.validate(), Ok(()), "\n{}", tree);
+        }
+
+        // Sorted input is the worst case for a plain BST
+        let tree: RbTree<u32> = (0..10_000).collect();
+        assert_eq!(tree.validate(), Ok(()));
+    }
+
+    #[test]
+    fn catching_violations() {
+        let tree: RbTree<u32> = [10, 5, 15, 1].into_iter().collect();
+        assert_eq!(tree.preorder(), [(10, B), (5, B), (1, R), (15, B)]);
+
+        let (five, one) = (tree.root.left(), tree.root.left().left());
+
+        tree.root.set_color(Color::Red);
+        assert_eq!(tree.validate(), Err(Violation::RedRoot));
+        tree.root.set_color(Color::Black);
+
+        five.set_color(Color::Red);
+        assert_eq!(tree.validate(), Err(Violation::RedWithRedChild));
+        five.set_color(Color::Black);
+
+        one.set_color(Color::Black);
+        assert_eq!(tree.validate(), Err(Violation::UnequalBlackHeight));
+        one.set_color(Color::Red);
+
+        assert_eq!(tree.validate(), Ok(()));
+
+        unsafe { (*one.0).item = 7 };
+        assert_eq!(tree.validate(), Err(Violation::OutOfOrder));
+        unsafe { (*one.0).item = 1 };
+
+        unsafe { (*one.0).parent = tree.root };
+        assert_eq!(tree.validate(), Err(Violation::BrokenParentLink));
+        unsafe { (*one.0).parent = five };
+    }
+
+    #[test]
+    fn send_sync() {
+        fn is_send<T: Send>() {}
+        fn is_sync<T: Sync>() {}
+
+        is_send::<RbTree<i32>>();
+        is_sync::<RbTree<i32>>();
+    }
+
+    #[test]
+    fn exporting_dot() {
+        let dot = (1..=3).collect::<RbTree<u32>>().to_dot();
+
+        assert!(dot.contains("n0 [label=\"2\", style=filled, fillcolor=black, fontcolor=white];"));
+        assert!(dot.contains("n1 [label=\"1\", style=filled, fillcolor=red"));
+        assert!(dot.contains("n4 [label=\"3\", style=filled, fillcolor=red"));
+    }
+
+    #[test]
+    fn drawing() {
+        let tree: RbTree<u32> = (1..=4).collect();
+
+        assert_eq!(
+            tree.to_string(),
+            "2 (black)\n├── 1 (black)\n└── 3 (black)\n    ├── ·\n    └── 4 (red)"
+        );
+        assert_eq!(
+            format!("{:#}", tree.pretty().notes(false)),
+            " 2\n┌┴┐\n1 3\n  └┐\n   4"
+        );
+    }
+}
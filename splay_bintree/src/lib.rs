@@ -0,0 +1,618 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::iter::{FromIterator, IntoIterator};
+use std::mem;
+use std::ops::RangeBounds;
+
+use ordered_set::OrderedSet;
+use tree_view::{Node, Shape, TreeView};
+
+struct TreeNode<T: Ord> {
+    item: T,
+    left: Tree<T>,
+    right: Tree<T>,
+}
+
+struct Tree<T: Ord>(Option<Box<TreeNode<T>>>);
+
+impl<T: Ord> TreeNode<T> {
+    fn new(item: T) -> Self {
+        Self {
+            item,
+            left: Tree(None),
+            right: Tree(None),
+        }
+    }
+}
+
+impl<T: Ord> From<TreeNode<T>> for Tree<T> {
+    fn from(node: TreeNode<T>) -> Self {
+        Tree(Some(Box::new(node)))
+    }
+}
+
+impl<T: Ord> Default for Tree<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<T: Ord> Tree<T> {
+    // Plain BST lookup, leaves the shape alone
+    fn search(&self, target: &T) -> Option<&TreeNode<T>> {
+        let mut tree = self;
+
+        while let Some(node) = tree.0.as_deref() {
+            match target.cmp(&node.item) {
+                Ordering::Less => tree = &node.left,
+                Ordering::Greater => tree = &node.right,
+                Ordering::Equal => break,
+            }
+        }
+
+        tree.0.as_deref()
+    }
+
+    /*
+     * Top-down splay. Nodes we pass on the way down are peeled off into
+     * a left spine (everything smaller than the target) and a right
+     * spine (everything larger), then hung back either side of wherever
+     * the search stopped:
+     *
+     *      L1            R1
+     *        \          /
+     *         L2      R2
+     *          \     /
+     *       root.left   root.right
+     *
+     * Zig-zig steps rotate before peeling, which is what halves the
+     * depth of long access paths.
+     */
+    fn splay_by<F>(&mut self, cmp: F)
+    where
+        F: Fn(&T) -> Ordering,
+    {
+        let Some(mut root) = self.0.take() else {
+            return;
+        };
+
+        let mut left_spine: Vec<Box<TreeNode<T>>> = vec![];
+        let mut right_spine: Vec<Box<TreeNode<T>>> = vec![];
+
+        loop {
+            match cmp(&root.item) {
+                Ordering::Less => {
+                    let Some(mut child) = root.left.0.take() else {
+                        break;
+                    };
+
+                    if cmp(&child.item) == Ordering::Less {
+                        // Zig-zig, rotate right
+                        root.left = mem::take(&mut child.right);
+                        child.right = Tree(Some(root));
+                        root = child;
+
+                        let Some(next) = root.left.0.take() else {
+                            break;
+                        };
+
+                        right_spine.push(mem::replace(&mut root, next));
+                    } else {
+                        right_spine.push(mem::replace(&mut root, child));
+                    }
+                }
+
+                Ordering::Greater => {
+                    let Some(mut child) = root.right.0.take() else {
+                        break;
+                    };
+
+                    if cmp(&child.item) == Ordering::Greater {
+                        // Zig-zig, rotate left
+                        root.right = mem::take(&mut child.left);
+                        child.left = Tree(Some(root));
+                        root = child;
+
+                        let Some(next) = root.right.0.take() else {
+                            break;
+                        };
+
+                        left_spine.push(mem::replace(&mut root, next));
+                    } else {
+                        left_spine.push(mem::replace(&mut root, child));
+                    }
+                }
+
+                Ordering::Equal => break,
+            }
+        }
+
+        let mut left = mem::take(&mut root.left);
+        while let Some(mut node) = left_spine.pop() {
+            node.right = left;
+            left = Tree(Some(node));
+        }
+
+        let mut right = mem::take(&mut root.right);
+        while let Some(mut node) = right_spine.pop() {
+            node.left = right;
+            right = Tree(Some(node));
+        }
+
+        root.left = left;
+        root.right = right;
+        self.0 = Some(root);
+    }
+
+    #[inline]
+    fn splay(&mut self, target: &T) {
+        self.splay_by(|item| target.cmp(item));
+    }
+
+    fn add_child(&mut self, new_item: T) -> bool {
+        self.splay(&new_item);
+
+        let mut new_node = TreeNode::new(new_item);
+
+        if let Some(mut root) = self.0.take() {
+            match new_node.item.cmp(&root.item) {
+                Ordering::Equal => {
+                    self.0 = Some(root);
+                    return false;
+                }
+
+                Ordering::Less => {
+                    new_node.left = mem::take(&mut root.left);
+                    new_node.right = Tree(Some(root));
+                }
+
+                Ordering::Greater => {
+                    new_node.right = mem::take(&mut root.right);
+                    new_node.left = Tree(Some(root));
+                }
+            }
+        }
+
+        *self = new_node.into();
+        true
+    }
+
+    // Expects the node to remove to already be splayed up to the root
+    fn remove_root(&mut self) -> Option<T> {
+        let mut root = self.0.take()?;
+        let mut left = mem::take(&mut root.left);
+
+        // Everything on the left is smaller than the old root, so
+        // splaying for "bigger than anything" brings its maximum up,
+        // leaving a free right slot
+        match left.0.as_deref_mut() {
+            None => *self = mem::take(&mut root.right),
+
+            Some(_) => {
+                left.splay_by(|_| Ordering::Greater);
+                left.0.as_deref_mut().unwrap().right = mem::take(&mut root.right);
+                *self = left;
+            }
+        }
+
+        Some(root.item)
+    }
+}
+
+pub struct SplayTree<T: Ord> {
+    root: Tree<T>,
+    size: usize,
+}
+
+impl<T: Ord> Default for SplayTree<T> {
+    fn default() -> Self {
+        Self {
+            root: Tree::default(),
+            size: 0,
+        }
+    }
+}
+
+impl<T: Ord> SplayTree<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Looks up `target` and splays it (or the last node visited) to the
+    /// root, so repeated lookups of the same hot keys stay cheap.
+    #[inline]
+    pub fn search(&mut self, target: &T) -> Option<&T> {
+        self.root.splay(target);
+
+        self.root
+            .0
+            .as_deref()
+            .filter(|root| root.item == *target)
+            .map(|root| &root.item)
+    }
+
+    /// Looks up `target` without restructuring the tree.
+    #[inline]
+    pub fn peek(&self, target: &T) -> Option<&T> {
+        self.root.search(target).map(|node| &node.item)
+    }
+
+    #[inline]
+    pub fn insert(&mut self, new_item: T) {
+        if self.root.add_child(new_item) {
+            self.size += 1;
+        }
+    }
+
+    #[inline]
+    pub fn remove(&mut self, target: &T) -> Option<T> {
+        self.root.splay(target);
+
+        if self.root.0.as_deref()?.item != *target {
+            return None;
+        }
+
+        self.size -= 1;
+        self.root.remove_root()
+    }
+
+    #[inline]
+    pub fn remove_inorder(&mut self) -> Option<T> {
+        self.root.splay_by(|_| Ordering::Less);
+
+        self.root.remove_root().inspect(|_| self.size -= 1)
+    }
+}
+
+impl<T: Ord> Drop for SplayTree<T> {
+    // Splay trees can legitimately degrade into long paths between
+    // accesses, so tear down with an explicit stack rather than letting
+    // Box recurse
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<TreeNode<T>>> = self.root.0.take().into_iter().collect();
+
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.0.take());
+            stack.extend(node.right.0.take());
+        }
+    }
+}
+
+impl<T: Ord + Clone> Clone for SplayTree<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: Ord> PartialEq for SplayTree<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Ord> Eq for SplayTree<T> {}
+
+impl<T: Ord + Debug> Debug for SplayTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+pub struct InorderIntoIter<T: Ord>(SplayTree<T>);
+
+impl<T: Ord> Iterator for InorderIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.remove_inorder()
+    }
+}
+
+pub struct InorderIter<'tree, T: Ord> {
+    curr_node: Option<&'tree TreeNode<T>>,
+    node_stack: Vec<&'tree TreeNode<T>>,
+}
+
+impl<'tree, T: Ord> SplayTree<T> {
+    pub fn iter(&'tree self) -> InorderIter<'tree, T> {
+        InorderIter {
+            curr_node: self.root.0.as_deref(),
+            node_stack: vec![],
+        }
+    }
+
+    /// Yields the items within `range` in ascending order, without splaying.
+    pub fn range<R>(&'tree self, range: R) -> ordered_set::Range<InorderIter<'tree, T>, R>
+    where
+        R: RangeBounds<T>,
+    {
+        let node_stack = ordered_set::seek(
+            self.root.0.as_deref(),
+            |node| [node.left.0.as_deref(), node.right.0.as_deref()],
+            |node| &node.item,
+            range.start_bound(),
+        );
+
+        let iter = InorderIter {
+            curr_node: None,
+            node_stack,
+        };
+        ordered_set::Range::new(iter, range)
+    }
+}
+
+impl<'tree, T: Ord> Iterator for InorderIter<'tree, T> {
+    type Item = &'tree T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.curr_node {
+            self.curr_node = node.left.0.as_deref();
+            self.node_stack.push(node);
+        }
+
+        if let Some(popped_node) = self.node_stack.pop() {
+            self.curr_node = popped_node.right.0.as_deref();
+            return Some(&popped_node.item);
+        }
+
+        None
+    }
+}
+
+impl<T: Ord> IntoIterator for SplayTree<T> {
+    type Item = T;
+    type IntoIter = InorderIntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        InorderIntoIter(self)
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SplayTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree: SplayTree<T> = Self::new();
+        iter.into_iter().for_each(|item| tree.insert(item));
+        tree
+    }
+}
+
+// Lookups go through `peek`, since `contains` only gets `&self` and so
+// can't splay
+impl<T: Ord> OrderedSet for SplayTree<T> {
+    type Item = T;
+    type ItemRef<'set>
+        = &'set T
+    where
+        Self: 'set;
+    type Iter<'set>
+        = InorderIter<'set, T>
+    where
+        Self: 'set;
+    type Range<'set, R>
+        = ordered_set::Range<InorderIter<'set, T>, R>
+    where
+        Self: 'set,
+        R: RangeBounds<T>;
+
+    #[inline]
+    fn insert(&mut self, item: T) -> bool {
+        let size = self.size();
+        SplayTree::insert(self, item);
+        self.size() != size
+    }
+
+    #[inline]
+    fn remove(&mut self, item: &T) -> Option<T> {
+        SplayTree::remove(self, item)
+    }
+
+    #[inline]
+    fn contains(&self, item: &T) -> bool {
+        self.peek(item).is_some()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.size()
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+
+    #[inline]
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_, R> {
+        self.range(range)
+    }
+}
+
+impl<T: Ord + Debug> TreeView for SplayTree<T> {
+    fn shape(&self) -> Shape {
+        Shape::new(
+            self.root.0.as_deref(),
+            |node| [node.left.0.as_deref(), node.right.0.as_deref()],
+            |node| Node::new(&node.item),
+        )
+    }
+}
+
+impl<T: Ord + Debug> fmt::Display for SplayTree<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.pretty(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ordered_set::conformance_suite!(SplayTree<u32>);
+
+    impl<T: Ord + Copy> SplayTree<T> {
+        fn root_item(&self) -> Option<T> {
+            self.root.0.as_deref().map(|n| n.item)
+        }
+
+        fn depth_of(&self, target: &T) -> Option<usize> {
+            let mut tree = &self.root;
+            let mut depth = 0;
+
+            while let Some(node) = tree.0.as_deref() {
+                match target.cmp(&node.item) {
+                    Ordering::Less => tree = &node.left,
+                    Ordering::Greater => tree = &node.right,
+                    Ordering::Equal => return Some(depth),
+                }
+                depth += 1;
+            }
+
+            None
+        }
+    }
+
+    #[test]
+    fn constructor() {
+        let tree: SplayTree<i32> = SplayTree::new();
+        assert_eq!(tree, tree.clone());
+        assert_eq!(tree, SplayTree::default());
+
+        let tree: SplayTree<u16> = vec![20, 10, 30].into_iter().collect();
+        assert_eq!(tree, tree.clone());
+
+        for (tree_val, num) in tree.into_iter().zip([10, 20, 30]) {
+            assert_eq!(tree_val, num);
+        }
+    }
+
+    #[test]
+    fn inserting() {
+        let mut tree: SplayTree<u32> = SplayTree::new();
+
+        tree.insert(20);
+        tree.insert(10);
+        tree.insert(30);
+        tree.insert(10);
+
+        // Every insert lands at the root
+        assert_eq!(tree.root_item(), Some(10));
+        assert_eq!(tree.size(), 3);
+        assert!(tree.iter().copied().eq([10, 20, 30]));
+    }
+
+    #[test]
+    fn searching_splays() {
+        let mut tree: SplayTree<u32> = (0..100).collect();
+
+        assert_eq!(tree.search(&42), Some(&42));
+        assert_eq!(tree.root_item(), Some(42));
+
+        // A miss still splays the last node on the search path
+        assert_eq!(tree.search(&1000), None);
+        assert_eq!(tree.root_item(), Some(99));
+
+        assert!(tree.iter().copied().eq(0..100));
+    }
+
+    #[test]
+    fn peeking_leaves_shape() {
+        let mut tree: SplayTree<u32> = (0..100).collect();
+        tree.search(&50);
+
+        assert_eq!(tree.peek(&3), Some(&3));
+        assert_eq!(tree.peek(&100), None);
+        assert_eq!(tree.root_item(), Some(50));
+    }
+
+    #[test]
+    fn hot_keys_stay_shallow() {
+        // Sorted inserts leave a single long path behind
+        let mut tree: SplayTree<u32> = (0..1_000).collect();
+        assert_eq!(tree.depth_of(&0), Some(999));
+
+        let hot = [3, 500, 997];
+
+        for _ in 0..10 {
+            for key in hot {
+                assert_eq!(tree.search(&key), Some(&key));
+            }
+        }
+
+        // Recently used keys sit within a few levels of the root
+        for key in hot {
+            assert!(tree.depth_of(&key).unwrap() <= 4);
+        }
+
+        // The zig-zig steps roughly halve the old path too
+        assert!(tree.depth_of(&0).unwrap() < 600);
+    }
+
+    #[test]
+    fn removing() {
+        let mut tree: SplayTree<u32> = [50, 25, 75, 10, 40, 60, 90].into_iter().collect();
+
+        assert_eq!(tree.remove(&40), Some(40));
+        assert_eq!(tree.remove(&40), None);
+        assert_eq!(tree.remove(&50), Some(50));
+        assert_eq!(tree.remove_inorder(), Some(10));
+        assert_eq!(tree.size(), 4);
+        assert!(tree.iter().copied().eq([25, 60, 75, 90]));
+
+        tree.clear();
+        assert_eq!(tree.remove(&25), None);
+        assert_eq!(tree.remove_inorder(), None);
+        assert_eq!(tree.size(), 0);
+    }
+
+    #[test]
+    fn iterating() {
+        let mut tree: SplayTree<u32> = [5, 3, 8, 1, 4, 9].into_iter().collect();
+
+        tree.search(&4);
+        assert!(tree.iter().copied().eq([1, 3, 4, 5, 8, 9]));
+        assert!(tree.into_iter().eq([1, 3, 4, 5, 8, 9]));
+    }
+
+    #[test]
+    fn deep_paths() {
+        // Descending inserts make a right-leaning path; neither into_iter
+        // nor drop may recurse down it
+        let tree: SplayTree<u32> = (0..200_000).rev().collect();
+        assert!(tree.into_iter().eq(0..200_000));
+
+        let tree: SplayTree<u32> = (0..200_000).collect();
+        drop(tree);
+    }
+
+    #[test]
+    fn send_sync() {
+        fn is_send<T: Send>() {}
+        fn is_sync<T: Sync>() {}
+
+        is_send::<SplayTree<i32>>();
+        is_sync::<SplayTree<i32>>();
+    }
+
+    #[test]
+    fn exporting_dot() {
+        // The last item inserted is splayed up to the root
+        let tree: SplayTree<u32> = (1..=3).collect();
+        let dot = tree.to_dot();
+
+        assert_eq!(tree.shape().len(), 3);
+        assert!(dot.contains("n0 [label=\"3\"];"));
+        assert!(dot.contains("n0:sw -> n1;"));
+    }
+}
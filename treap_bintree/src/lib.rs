@@ -0,0 +1,744 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Debug};
+use std::iter::{FromIterator, IntoIterator};
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+
+use ordered_set::OrderedSet;
+use tree_view::{Node, Shape, TreeView};
+
+// xorshift64*, plenty for shuffling priorities and keeps the crate
+// dependency free
+#[derive(Clone, Debug)]
+struct Rng(u64);
+
+impl Rng {
+    const DEFAULT_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+    fn new(seed: u64) -> Self {
+        // xorshift gets stuck on an all-zero state
+        Self(if seed == 0 { Self::DEFAULT_SEED } else { seed })
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+struct TreeNode<T: Ord> {
+    item: T,
+    priority: u64,
+    left: Tree<T>,
+    right: Tree<T>,
+    size: usize,
+}
+
+struct Tree<T: Ord>(Option<Box<TreeNode<T>>>);
+
+impl<T: Ord> TreeNode<T> {
+    fn new(item: T, priority: u64) -> Self {
+        Self {
+            item,
+            priority,
+            left: Tree(None),
+            right: Tree(None),
+            size: 1,
+        }
+    }
+
+    #[inline]
+    fn update_size(&mut self) {
+        self.size = 1 + self.left.size() + self.right.size();
+    }
+}
+
+impl<T: Ord> From<TreeNode<T>> for Tree<T> {
+    fn from(node: TreeNode<T>) -> Self {
+        Tree(Some(Box::new(node)))
+    }
+}
+
+impl<T: Ord> Default for Tree<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<T: Ord> Tree<T> {
+    #[inline]
+    fn size(&self) -> usize {
+        self.0.as_deref().map_or(0, |n| n.size)
+    }
+
+    fn search(&self, target: &T) -> Option<&TreeNode<T>> {
+        let mut tree = self;
+
+        while let Some(node) = tree.0.as_deref() {
+            match target.cmp(&node.item) {
+                Ordering::Less => tree = &node.left,
+                Ordering::Greater => tree = &node.right,
+                Ordering::Equal => break,
+            }
+        }
+
+        tree.0.as_deref()
+    }
+
+    fn first(&self) -> Option<&T> {
+        let mut node = self.0.as_deref()?;
+
+        while let Some(left) = node.left.0.as_deref() {
+            node = left;
+        }
+
+        Some(&node.item)
+    }
+
+    fn last(&self) -> Option<&T> {
+        let mut node = self.0.as_deref()?;
+
+        while let Some(right) = node.right.0.as_deref() {
+            node = right;
+        }
+
+        Some(&node.item)
+    }
+
+    /*
+     * Cuts the tree in two along a path from the root, every item for
+     * which `goes_left` holds ending up in the first tree. The predicate
+     * has to be monotone over the search order (true, ..., true, false,
+     * ..., false), like "less than some key".
+     *
+     *          5                  2            5
+     *        /   \     < 4       / \          / \
+     *       2     8    ===>     1   3        4   8
+     *      / \   /
+     *     1   3 4
+     */
+    fn split_by<F>(self, goes_left: &F) -> (Tree<T>, Tree<T>)
+    where
+        F: Fn(&T) -> bool,
+    {
+        let Some(mut node) = self.0 else {
+            return (Tree(None), Tree(None));
+        };
+
+        if goes_left(&node.item) {
+            let (low, high) = mem::take(&mut node.right).split_by(goes_left);
+            node.right = low;
+            node.update_size();
+            (Tree(Some(node)), high)
+        } else {
+            let (low, high) = mem::take(&mut node.left).split_by(goes_left);
+            node.left = high;
+            node.update_size();
+            (low, Tree(Some(node)))
+        }
+    }
+
+    // Inverse of split_by, every item in `low` must be smaller than every
+    // item in `high`. The higher priority root wins at each level.
+    fn merge(low: Tree<T>, high: Tree<T>) -> Tree<T> {
+        match (low.0, high.0) {
+            (None, high) => Tree(high),
+            (low, None) => Tree(low),
+
+            (Some(mut l), Some(mut h)) => {
+                if l.priority >= h.priority {
+                    l.right = Tree::merge(mem::take(&mut l.right), Tree(Some(h)));
+                    l.update_size();
+                    Tree(Some(l))
+                } else {
+                    h.left = Tree::merge(Tree(Some(l)), mem::take(&mut h.left));
+                    h.update_size();
+                    Tree(Some(h))
+                }
+            }
+        }
+    }
+
+    // Set union of two arbitrary treaps, keeping the copy already in
+    // `self` when both hold an equal item
+    fn union(self, other: Tree<T>) -> Tree<T> {
+        match (self.0, other.0) {
+            (None, other) => Tree(other),
+            (this, None) => Tree(this),
+
+            (Some(mut a), Some(b)) if a.priority >= b.priority => {
+                let (low, rest) = Tree(Some(b)).split_by(&|item| *item < a.item);
+                let (_dup, high) = rest.split_by(&|item| *item <= a.item);
+
+                a.left = mem::take(&mut a.left).union(low);
+                a.right = mem::take(&mut a.right).union(high);
+                a.update_size();
+                Tree(Some(a))
+            }
+
+            (Some(a), Some(mut b)) => {
+                let (low, rest) = Tree(Some(a)).split_by(&|item| *item < b.item);
+                let (dup, high) = rest.split_by(&|item| *item <= b.item);
+
+                // Prefer the item from self over the one from other
+                if let Some(dup) = dup.0 {
+                    b.item = dup.item;
+                }
+
+                b.left = low.union(mem::take(&mut b.left));
+                b.right = high.union(mem::take(&mut b.right));
+                b.update_size();
+                Tree(Some(b))
+            }
+        }
+    }
+
+    fn add_child(&mut self, new_node: TreeNode<T>) -> bool {
+        if self.search(&new_node.item).is_some() {
+            return false;
+        }
+
+        let (low, high) = mem::take(self).split_by(&|item| *item < new_node.item);
+        *self = Tree::merge(Tree::merge(low, new_node.into()), high);
+        true
+    }
+
+    fn remove_leftmost_child(&mut self) -> Option<Box<TreeNode<T>>> {
+        let node = self.0.as_deref_mut()?;
+
+        if node.left.0.is_some() {
+            let pruned = node.left.remove_leftmost_child();
+            node.update_size();
+            return pruned;
+        }
+
+        let mut pruned = self.0.take()?;
+        *self = mem::take(&mut pruned.right);
+        Some(pruned)
+    }
+}
+
+pub struct Treap<T: Ord> {
+    root: Tree<T>,
+    rng: Rng,
+}
+
+impl<T: Ord> Default for Treap<T> {
+    fn default() -> Self {
+        Self::with_seed(Rng::DEFAULT_SEED)
+    }
+}
+
+impl<T: Ord> Treap<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Same seed, same sequence of operations, same shape.
+    #[inline]
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            root: Tree(None),
+            rng: Rng::new(seed),
+        }
+    }
+
+    // Splitting hands out a tree with its own priority stream, derived
+    // from ours so the whole history stays reproducible
+    #[inline]
+    fn spawn(&mut self, root: Tree<T>) -> Self {
+        Self {
+            root,
+            rng: Rng::new(self.rng.next_u64()),
+        }
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.root.size()
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        _ = self.root.0.take();
+    }
+
+    #[inline]
+    pub fn search(&self, target: &T) -> Option<&T> {
+        self.root.search(target).map(|node| &node.item)
+    }
+
+    #[inline]
+    pub fn first(&self) -> Option<&T> {
+        self.root.first()
+    }
+
+    #[inline]
+    pub fn last(&self) -> Option<&T> {
+        self.root.last()
+    }
+
+    #[inline]
+    pub fn insert(&mut self, new_item: T) {
+        let priority = self.rng.next_u64();
+        self.root.add_child(TreeNode::new(new_item, priority));
+    }
+
+    #[inline]
+    pub fn remove(&mut self, target: &T) -> Option<T> {
+        self.remove_range((Bound::Included(target), Bound::Included(target)))
+            .remove_inorder()
+    }
+
+    #[inline]
+    pub fn remove_inorder(&mut self) -> Option<T> {
+        self.root.remove_leftmost_child().map(|n| n.item)
+    }
+
+    /// Moves every item at or above `key` into a new treap, in
+    /// O(log n) expected time.
+    pub fn split_off(&mut self, key: &T) -> Self {
+        let (low, high) = mem::take(&mut self.root).split_by(&|item| item < key);
+
+        self.root = low;
+        self.spawn(high)
+    }
+
+    /// Concatenates `other` onto the end of this treap in O(log n)
+    /// expected time, the inverse of [`Treap::split_off`].
+    ///
+    /// # Panics
+    ///
+    /// Panics unless every item in `other` is greater than every item
+    /// already here. Use [`Treap::append`] for overlapping sets.
+    pub fn merge(&mut self, mut other: Self) {
+        if let (Some(last), Some(first)) = (self.last(), other.first()) {
+            assert!(
+                last < first,
+                "merge requires every item of the right treap to be greater"
+            );
+        }
+
+        let low = mem::take(&mut self.root);
+        self.root = Tree::merge(low, mem::take(&mut other.root));
+    }
+
+    /// Moves every item out of `other` into this treap, keeping our copy
+    /// of any item both contain. Costs O(m log(n / m)) expected for sets
+    /// of size m <= n rather than m separate inserts.
+    pub fn append(&mut self, other: &mut Self) {
+        let this = mem::take(&mut self.root);
+        self.root = this.union(mem::take(&mut other.root));
+    }
+
+    /// Cuts out every item in `range` with two splits and a merge,
+    /// O(log n) expected regardless of how many items go, and returns
+    /// them as their own treap.
+    pub fn remove_range<R: RangeBounds<T>>(&mut self, range: R) -> Self {
+        let below_range = |item: &T| ordered_set::before_start(item, range.start_bound());
+        let up_to_end = |item: &T| !ordered_set::past_end(item, range.end_bound());
+
+        let (low, rest) = mem::take(&mut self.root).split_by(&below_range);
+        let (mid, high) = rest.split_by(&up_to_end);
+
+        self.root = Tree::merge(low, high);
+        self.spawn(mid)
+    }
+}
+
+impl<T: Ord + Clone> Clone for Treap<T> {
+    fn clone(&self) -> Self {
+        let mut tree = Self {
+            root: Tree(None),
+            rng: self.rng.clone(),
+        };
+
+        self.iter().cloned().for_each(|item| tree.insert(item));
+        tree
+    }
+}
+
+impl<T: Ord> PartialEq for Treap<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.size() == other.size() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Ord> Eq for Treap<T> {}
+
+impl<T: Ord + Debug> Debug for Treap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+pub struct InorderIntoIter<T: Ord>(Treap<T>);
+
+impl<T: Ord> Iterator for InorderIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.remove_inorder()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.size(), Some(self.0.size()))
+    }
+}
+
+pub struct InorderIter<'tree, T: Ord> {
+    curr_node: Option<&'tree TreeNode<T>>,
+    node_stack: Vec<&'tree TreeNode<T>>,
+}
+
+impl<'tree, T: Ord> Treap<T> {
+    pub fn iter(&'tree self) -> InorderIter<'tree, T> {
+        InorderIter {
+            curr_node: self.root.0.as_deref(),
+            node_stack: vec![],
+        }
+    }
+
+    /// Yields the items within `range` in ascending order, reaching the first in O(log n) expected.
+    pub fn range<R>(&'tree self, range: R) -> ordered_set::Range<InorderIter<'tree, T>, R>
+    where
+        R: RangeBounds<T>,
+    {
+        let node_stack = ordered_set::seek(
+            self.root.0.as_deref(),
+            |node| [node.left.0.as_deref(), node.right.0.as_deref()],
+            |node| &node.item,
+            range.start_bound(),
+        );
+
+        let iter = InorderIter {
+            curr_node: None,
+            node_stack,
+        };
+        ordered_set::Range::new(iter, range)
+    }
+}
+
+impl<'tree, T: Ord> Iterator for InorderIter<'tree, T> {
+    type Item = &'tree T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.curr_node {
+            self.curr_node = node.left.0.as_deref();
+            self.node_stack.push(node);
+        }
+
+        if let Some(popped_node) = self.node_stack.pop() {
+            self.curr_node = popped_node.right.0.as_deref();
+            return Some(&popped_node.item);
+        }
+
+        None
+    }
+}
+
+impl<T: Ord> IntoIterator for Treap<T> {
+    type Item = T;
+    type IntoIter = InorderIntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        InorderIntoIter(self)
+    }
+}
+
+impl<T: Ord> FromIterator<T> for Treap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree: Treap<T> = Self::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T: Ord> Extend<T> for Treap<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|item| self.insert(item));
+    }
+}
+
+impl<T: Ord> OrderedSet for Treap<T> {
+    type Item = T;
+    type ItemRef<'set>
+        = &'set T
+    where
+        Self: 'set;
+    type Iter<'set>
+        = InorderIter<'set, T>
+    where
+        Self: 'set;
+    type Range<'set, R>
+        = ordered_set::Range<InorderIter<'set, T>, R>
+    where
+        Self: 'set,
+        R: RangeBounds<T>;
+
+    #[inline]
+    fn insert(&mut self, item: T) -> bool {
+        let size = self.size();
+        Treap::insert(self, item);
+        self.size() != size
+    }
+
+    #[inline]
+    fn remove(&mut self, item: &T) -> Option<T> {
+        Treap::remove(self, item)
+    }
+
+    #[inline]
+    fn contains(&self, item: &T) -> bool {
+        self.search(item).is_some()
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.size()
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        self.iter()
+    }
+
+    #[inline]
+    fn first(&self) -> Option<&T> {
+        Treap::first(self)
+    }
+
+    #[inline]
+    fn last(&self) -> Option<&T> {
+        Treap::last(self)
+    }
+
+    #[inline]
+    fn range<R: RangeBounds<T>>(&self, range: R) -> Self::Range<'_, R> {
+        self.range(range)
+    }
+}
+
+impl<T: Ord + Debug> TreeView for Treap<T> {
+    fn shape(&self) -> Shape {
+        Shape::new(
+            self.root.0.as_deref(),
+            |node| [node.left.0.as_deref(), node.right.0.as_deref()],
+            |node| {
+                Node::new(&node.item)
+                    .note("p", node.priority)
+                    .note("n", node.size)
+            },
+        )
+    }
+}
+
+impl<T: Ord + Debug> fmt::Display for Treap<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.pretty(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ordered_set::conformance_suite!(Treap<u32>);
+
+    impl<T: Ord + Copy> Treap<T> {
+        // Checks search order, heap order on priorities and cached
+        // subtree sizes all at once
+        fn is_valid(&self) -> bool {
+            fn check<T: Ord>(tree: &Tree<T>, lower: Option<&T>, upper: Option<&T>) -> bool {
+                let Some(node) = tree.0.as_deref() else {
+                    return true;
+                };
+
+                let children = [&node.left, &node.right];
+
+                lower.is_none_or(|l| node.item > *l)
+                    && upper.is_none_or(|u| node.item < *u)
+                    && children
+                        .iter()
+                        .all(|c| c.0.as_deref().is_none_or(|c| c.priority <= node.priority))
+                    && node.size == 1 + node.left.size() + node.right.size()
+                    && check(&node.left, lower, Some(&node.item))
+                    && check(&node.right, Some(&node.item), upper)
+            }
+
+            check(&self.root, None, None)
+        }
+
+        fn preorder(&self) -> Vec<T> {
+            let mut out = vec![];
+            let mut stack = vec![&self.root];
+
+            while let Some(tree) = stack.pop() {
+                if let Some(node) = tree.0.as_deref() {
+                    out.push(node.item);
+                    stack.push(&node.right);
+                    stack.push(&node.left);
+                }
+            }
+
+            out
+        }
+    }
+
+    #[test]
+    fn constructor() {
+        let tree: Treap<i32> = Treap::new();
+        assert_eq!(tree, tree.clone());
+        assert_eq!(tree, Treap::default());
+
+        let tree: Treap<u16> = vec![20, 10, 30].into_iter().collect();
+        assert_eq!(tree, tree.clone());
+
+        for (tree_val, num) in tree.into_iter().zip([10, 20, 30]) {
+            assert_eq!(tree_val, num);
+        }
+    }
+
+    #[test]
+    fn deterministic_shapes() {
+        let mut a: Treap<u32> = Treap::with_seed(7);
+        let mut b: Treap<u32> = Treap::with_seed(7);
+        let mut c: Treap<u32> = Treap::with_seed(8);
+
+        for item in [50, 20, 80, 10, 30, 70, 90, 60, 40] {
+            a.insert(item);
+            b.insert(item);
+            c.insert(item);
+        }
+
+        assert_eq!(a.preorder(), b.preorder());
+        assert_ne!(a.preorder(), c.preorder());
+        assert_eq!(a, c);
+    }
+
+    #[test]
+    fn inserting_and_searching() {
+        let mut tree: Treap<u32> = Treap::with_seed(1);
+
+        for item in (0..1_000).rev() {
+            tree.insert(item);
+        }
+        tree.insert(500);
+
+        assert!(tree.is_valid());
+        assert_eq!(tree.size(), 1_000);
+        assert_eq!(tree.search(&500), Some(&500));
+        assert_eq!(tree.search(&1_000), None);
+        assert_eq!(tree.first(), Some(&0));
+        assert_eq!(tree.last(), Some(&999));
+        assert!(tree.iter().copied().eq(0..1_000));
+    }
+
+    #[test]
+    fn removing() {
+        let mut tree: Treap<u32> = (0..10).collect();
+
+        assert_eq!(tree.remove(&4), Some(4));
+        assert_eq!(tree.remove(&4), None);
+        assert_eq!(tree.remove_inorder(), Some(0));
+        assert!(tree.is_valid());
+        assert_eq!(tree.size(), 8);
+        assert!(tree.into_iter().eq([1, 2, 3, 5, 6, 7, 8, 9]));
+    }
+
+    #[test]
+    fn splitting_and_merging() {
+        let mut low: Treap<u32> = (0..100).collect();
+
+        let high = low.split_off(&60);
+        assert!(low.is_valid() && high.is_valid());
+        assert!(low.iter().copied().eq(0..60));
+        assert!(high.iter().copied().eq(60..100));
+        assert_eq!((low.size(), high.size()), (60, 40));
+
+        // Splitting on a key that isn't present
+        let mut evens: Treap<u32> = (0..20).map(|i| i * 2).collect();
+        let odd_split = evens.split_off(&7);
+        assert_eq!(evens.last(), Some(&6));
+        assert_eq!(odd_split.first(), Some(&8));
+
+        low.merge(high);
+        assert!(low.is_valid());
+        assert!(low.iter().copied().eq(0..100));
+
+        low.merge(Treap::new());
+        Treap::new().merge(low);
+    }
+
+    #[test]
+    #[should_panic(expected = "merge requires")]
+    fn merging_overlapping() {
+        let mut low: Treap<u32> = (0..10).collect();
+        low.merge((5..15).collect());
+    }
+
+    #[test]
+    fn removing_ranges() {
+        let mut tree: Treap<u32> = (0..100).collect();
+
+        let removed = tree.remove_range(10..20);
+        assert!(removed.iter().copied().eq(10..20));
+        assert_eq!(tree.size(), 90);
+
+        let removed = tree.remove_range(..=4);
+        assert!(removed.iter().copied().eq(0..=4));
+
+        let removed = tree.remove_range((Bound::Excluded(89), Bound::Unbounded));
+        assert!(removed.iter().copied().eq(90..100));
+
+        let removed = tree.remove_range(200..300);
+        assert_eq!(removed.size(), 0);
+
+        assert!(tree.is_valid());
+        assert!(tree.iter().copied().eq((5..10).chain(20..=89)));
+    }
+
+    #[test]
+    fn appending() {
+        let mut evens: Treap<u32> = (0..500).map(|i| i * 2).collect();
+        let mut thirds: Treap<u32> = Treap::with_seed(3);
+        thirds.extend((0..300).map(|i| i * 3));
+
+        evens.append(&mut thirds);
+
+        let mut expected: Vec<u32> = (0..500)
+            .map(|i| i * 2)
+            .chain((0..300).map(|i| i * 3))
+            .collect();
+        expected.sort();
+        expected.dedup();
+
+        assert!(evens.is_valid());
+        assert_eq!(thirds.size(), 0);
+        assert_eq!(evens.size(), expected.len());
+        assert!(evens.iter().copied().eq(expected));
+    }
+
+    #[test]
+    fn send_sync() {
+        fn is_send<T: Send>() {}
+        fn is_sync<T: Sync>() {}
+
+        is_send::<Treap<i32>>();
+        is_sync::<Treap<i32>>();
+    }
+
+    #[test]
+    fn exporting_dot() {
+        let tree: Treap<u32> = (1..=3).collect();
+        let dot = tree.to_dot();
+
+        assert_eq!(tree.shape().len(), 3);
+        assert!(dot.contains(" n=3\"];"));
+        assert_eq!(dot.matches("p=").count(), 3);
+    }
+}
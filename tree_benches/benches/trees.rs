@@ -0,0 +1,124 @@
+use std::collections::BTreeSet;
+use std::hint::black_box;
+use std::time::Duration;
+
+use criterion::measurement::WallTime;
+use criterion::{
+    criterion_group, criterion_main, BatchSize, BenchmarkGroup, BenchmarkId, Criterion, Throughput,
+};
+use ordered_set::OrderedSet;
+use tree_benches::{Pattern, XorShift};
+
+const SIZES: [usize; 3] = [100, 1_000, 10_000];
+
+// Every benchmark id is `operation/pattern/implementation/size`
+fn group<'c>(
+    c: &'c mut Criterion,
+    operation: &str,
+    pattern: Pattern,
+    size: usize,
+) -> BenchmarkGroup<'c, WallTime> {
+    let mut group = c.benchmark_group(format!("{}/{}", operation, pattern));
+    group.throughput(Throughput::Elements(size as u64));
+    group
+}
+
+// Lookups go through `search`, which takes the set mutably so that trees
+// which restructure on every lookup, like a splay tree, can be measured
+// doing so
+fn bench_set<S>(c: &mut Criterion, name: &str, search: fn(&mut S, &u32) -> bool)
+where
+    S: OrderedSet<Item = u32> + Default + Clone + FromIterator<u32>,
+{
+    for pattern in Pattern::ALL {
+        for size in SIZES {
+            let input = pattern.generate(size);
+            let set: S = input.iter().copied().collect();
+            let id = BenchmarkId::new(name, size);
+
+            // Looked up and removed in an order unrelated to the input's
+            let mut hits = input.clone();
+            XorShift::new(!(size as u32)).shuffle(&mut hits);
+            let misses: Vec<u32> = hits.iter().map(|val| val + 1).collect();
+
+            group(c, "insert", pattern, size).bench_function(id.clone(), |b| {
+                b.iter_batched(
+                    S::default,
+                    |mut set| {
+                        for &val in &input {
+                            set.insert(val);
+                        }
+                        set
+                    },
+                    BatchSize::LargeInput,
+                )
+            });
+
+            group(c, "collect", pattern, size).bench_function(id.clone(), |b| {
+                b.iter(|| input.iter().copied().collect::<S>())
+            });
+
+            // Each batch starts from the same shape, however searching
+            // leaves it
+            for (operation, targets) in [("search_hit", &hits), ("search_miss", &misses)] {
+                group(c, operation, pattern, size).bench_function(id.clone(), |b| {
+                    b.iter_batched(
+                        || set.clone(),
+                        |mut set| {
+                            let found = targets.iter().filter(|val| search(&mut set, val)).count();
+                            (set, found)
+                        },
+                        BatchSize::LargeInput,
+                    )
+                });
+            }
+
+            group(c, "remove", pattern, size).bench_function(id.clone(), |b| {
+                b.iter_batched(
+                    || set.clone(),
+                    |mut set| {
+                        for val in &hits {
+                            black_box(set.remove(val));
+                        }
+                        set
+                    },
+                    BatchSize::LargeInput,
+                )
+            });
+
+            group(c, "iterate", pattern, size).bench_function(id, |b| {
+                b.iter(|| set.iter().fold(0_u64, |sum, val| sum + *val as u64))
+            });
+        }
+    }
+}
+
+fn contains<S: OrderedSet<Item = u32>>(set: &mut S, val: &u32) -> bool {
+    set.contains(val)
+}
+
+fn trees(c: &mut Criterion) {
+    bench_set::<BTreeSet<u32>>(c, "BTreeSet", contains);
+    bench_set::<box_bintree::BinTree<u32>>(c, "box_bintree", contains);
+    bench_set::<box_bintree_iterative::BinTree<u32>>(c, "box_bintree_iterative", contains);
+    bench_set::<raw_bintree::BinaryTree<u32>>(c, "raw_bintree", contains);
+    bench_set::<leetcode_bintree::BinaryTree<u32>>(c, "leetcode_bintree", contains);
+    bench_set::<rb_bintree::RbTree<u32>>(c, "rb_bintree", contains);
+    // `contains` only peeks, leaving the tree as it was
+    bench_set::<splay_bintree::SplayTree<u32>>(c, "splay_bintree", |set, val| {
+        set.search(val).is_some()
+    });
+    bench_set::<treap_bintree::Treap<u32>>(c, "treap_bintree", contains);
+    bench_set::<persistent_bintree::RcTree<u32>>(c, "persistent_bintree", contains);
+}
+
+criterion_group! {
+    name = benches;
+    // Several hundred benchmarks, so each one gets a short run
+    config = Criterion::default()
+        .sample_size(10)
+        .warm_up_time(Duration::from_millis(200))
+        .measurement_time(Duration::from_millis(500));
+    targets = trees
+}
+criterion_main!(benches);
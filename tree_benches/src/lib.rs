@@ -0,0 +1,122 @@
+//! Benchmarks comparing every tree in the workspace with `BTreeSet`. The
+//! inputs are generated here, the benchmarks themselves are in
+//! `benches/trees.rs`:
+//!
+//! ```sh
+//! cargo bench -p tree_benches
+//! cargo bench -p tree_benches -- 'search_hit/random'
+//! ```
+//!
+//! Nothing is downloaded or compared against a remote service; results go to
+//! `target/criterion`, and `baselines/` holds committed runs to compare new
+//! ones with (see its README).
+
+use std::fmt;
+
+/// How the `n` values of an input are ordered. The values themselves are
+/// always `0, 2, 4, ..`, so odd numbers make guaranteed misses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    Sorted,
+    Reversed,
+    Random,
+    /// Sorted runs of consecutive values, the runs in random order.
+    Clustered,
+}
+
+// How many values make up each run of a clustered input
+const RUN: usize = 32;
+
+impl Pattern {
+    pub const ALL: [Pattern; 4] = [
+        Pattern::Sorted,
+        Pattern::Reversed,
+        Pattern::Random,
+        Pattern::Clustered,
+    ];
+
+    pub fn generate(self, n: usize) -> Vec<u32> {
+        let mut values: Vec<u32> = (0..n as u32).map(|i| i * 2).collect();
+        let mut rng = XorShift::new(n as u32);
+
+        match self {
+            Pattern::Sorted => {}
+            Pattern::Reversed => values.reverse(),
+            Pattern::Random => rng.shuffle(&mut values),
+
+            Pattern::Clustered => {
+                let mut runs: Vec<&[u32]> = values.chunks(RUN).collect();
+                rng.shuffle(&mut runs);
+                values = runs.concat();
+            }
+        }
+
+        values
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Pattern::Sorted => "sorted",
+            Pattern::Reversed => "reversed",
+            Pattern::Random => "random",
+            Pattern::Clustered => "clustered",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// A fixed-seed xorshift generator, so that every run benchmarks the same
+/// inputs.
+pub struct XorShift(u32);
+
+impl XorShift {
+    #[inline]
+    pub fn new(seed: u32) -> Self {
+        // Zero would stay zero forever
+        Self(seed ^ 0x9e37_79b9)
+    }
+
+    #[inline]
+    pub fn next_u32(&mut self) -> u32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0
+    }
+
+    /// Fisher-Yates.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.next_u32() as usize % (i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generating_inputs() {
+        for pattern in Pattern::ALL {
+            let mut values = pattern.generate(1_000);
+            assert_eq!(pattern.generate(1_000), values, "{}", pattern);
+
+            values.sort_unstable();
+            assert!(values.into_iter().eq((0..1_000).map(|i| i * 2)));
+        }
+
+        assert!(Pattern::Reversed.generate(3).into_iter().eq([4, 2, 0]));
+        assert_ne!(Pattern::Random.generate(100), Pattern::Sorted.generate(100));
+
+        // Every run full, so that they line up with chunks of the output
+        let clustered = Pattern::Clustered.generate(1_024);
+        assert!(clustered
+            .chunks(RUN)
+            .all(|run| run.windows(2).all(|pair| pair[1] == pair[0] + 2)));
+    }
+}
@@ -0,0 +1,129 @@
+//! Graphviz output, to render with e.g. `dot -Tsvg`. Every node shows its
+//! item over its notes, and is filled in with its colour if it has one.
+//! Missing children are drawn as points, so that an only child still shows
+//! which side it hangs on:
+//!
+//! ```text
+//! digraph tree {
+//!     node [shape=ellipse, fontname="monospace"];
+//!     n0 [label="2\nh=2 bf=-1"];
+//!     n1 [label="1\nh=1 bf=0"];
+//!     n0:sw -> n1;
+//!     n2 [shape=point];
+//!     n1:sw -> n2;
+//!     ...
+//! }
+//! ```
+
+use std::fmt::Write;
+
+use crate::{Color, Shape, Side};
+
+impl Shape {
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph tree {\n");
+        dot.push_str("    node [shape=ellipse, fontname=\"monospace\"];\n");
+
+        for slot in self.slots() {
+            let id = slot.index;
+
+            let attributes = match slot.node {
+                Some(node) => {
+                    let mut label = escape(&node.label);
+                    if !node.notes.is_empty() {
+                        label.push_str("\\n");
+                        label.push_str(&escape(&node.notes_line()));
+                    }
+
+                    let fill = match node.color {
+                        Some(Color::Red) => ", style=filled, fillcolor=red, fontcolor=white",
+                        Some(Color::Black) => ", style=filled, fillcolor=black, fontcolor=white",
+                        None => "",
+                    };
+
+                    format!("label=\"{}\"{}", label, fill)
+                }
+
+                None => String::from("shape=point"),
+            };
+
+            // Writing to a String can't fail
+            _ = writeln!(dot, "    n{} [{}];", id, attributes);
+
+            if let Some((parent, side)) = slot.parent {
+                let port = match side {
+                    Side::Left => "sw",
+                    Side::Right => "se",
+                };
+
+                _ = writeln!(dot, "    n{}:{} -> n{};", parent, port, id);
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+// Quotes and backslashes would end or mangle the label's string
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::shape;
+    use crate::Node;
+
+    #[test]
+    fn drawing_nodes_and_edges() {
+        //   7
+        //    \
+        //     9
+        let dot = shape(&[(7, None, Some(1)), (9, None, None)]).to_dot();
+
+        assert_eq!(
+            dot,
+            "digraph tree {
+    node [shape=ellipse, fontname=\"monospace\"];
+    n0 [label=\"7\\ni=0\"];
+    n1 [shape=point];
+    n0:sw -> n1;
+    n2 [label=\"9\\ni=1\"];
+    n0:se -> n2;
+    n3 [shape=point];
+    n2:sw -> n3;
+    n4 [shape=point];
+    n2:se -> n4;
+}
+"
+        );
+    }
+
+    #[test]
+    fn empty_trees() {
+        assert!(shape(&[]).to_dot().ends_with("    n0 [shape=point];\n}\n"));
+    }
+
+    #[test]
+    fn colours_and_escaping() {
+        let nodes = ["a\"b\\c", "d"];
+        let dot = Shape::new(
+            Some(0),
+            |&i: &usize| [(i == 0).then_some(1), None],
+            |&i| match i {
+                0 => Node::new(nodes[i]).color(Color::Black),
+                _ => Node::new(nodes[i]).color(Color::Red),
+            },
+        )
+        .to_dot();
+
+        assert!(dot.contains(
+            r#"n0 [label="\"a\\\"b\\\\c\"", style=filled, fillcolor=black, fontcolor=white];"#
+        ));
+        assert!(
+            dot.contains(r#"n1 [label="\"d\"", style=filled, fillcolor=red, fontcolor=white];"#)
+        );
+    }
+}
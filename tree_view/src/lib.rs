@@ -0,0 +1,270 @@
+//! How the workspace's trees describe their layout, so that it can be drawn
+//! the same way for all of them, e.g. as a Graphviz graph to render in a
+//! failing test's output, or as text:
+//!
+//! ```
+//! use tree_view::{Node, Shape, TreeView};
+//!
+//! struct TreeNode {
+//!     item: u32,
+//!     left: Option<Box<TreeNode>>,
+//!     right: Option<Box<TreeNode>>,
+//! }
+//!
+//! struct Tree(Option<Box<TreeNode>>);
+//!
+//! impl TreeView for Tree {
+//!     fn shape(&self) -> Shape {
+//!         Shape::new(
+//!             self.0.as_deref(),
+//!             |node| [node.left.as_deref(), node.right.as_deref()],
+//!             |node| Node::new(&node.item),
+//!         )
+//!     }
+//! }
+//!
+//! let leaf = |item| Some(Box::new(TreeNode { item, left: None, right: None }));
+//! let tree = Tree(Some(Box::new(TreeNode { item: 1, left: None, right: leaf(2) })));
+//!
+//! assert_eq!(tree.shape().len(), 2);
+//! assert!(tree.to_dot().contains("n0:se -> n2;"));
+//! assert_eq!(tree.pretty().to_string(), "1\n├── ·\n└── 2");
+//! ```
+
+pub mod dot;
+pub mod pretty;
+
+pub use pretty::{Layout, Pretty};
+
+use std::fmt::{Debug, Display};
+
+/// What a node's colour is, for trees that keep one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Red,
+    Black,
+}
+
+/// How a node is drawn: its item, then whatever the tree keeps about it,
+/// e.g. a cached height.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    label: String,
+    notes: Vec<(&'static str, String)>,
+    color: Option<Color>,
+}
+
+impl Node {
+    /// Labelled with the item's `Debug` output.
+    #[inline]
+    pub fn new<T: Debug + ?Sized>(item: &T) -> Self {
+        Self {
+            label: format!("{:?}", item),
+            notes: Vec::new(),
+            color: None,
+        }
+    }
+
+    /// Adds `name=value` to the notes shown after the label, in order.
+    #[inline]
+    pub fn note(mut self, name: &'static str, value: impl Display) -> Self {
+        self.notes.push((name, value.to_string()));
+        self
+    }
+
+    #[inline]
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    // The notes as one line, e.g. `h=2 bf=-1`
+    fn notes_line(&self) -> String {
+        let notes: Vec<String> = self
+            .notes
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+
+        notes.join(" ")
+    }
+}
+
+/// Which of its parent's children a node is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Left,
+    Right,
+}
+
+// One of a shape's entries, along with where it hangs in the tree
+struct Slot<'shape> {
+    index: usize,
+    parent: Option<(usize, Side)>,
+    node: Option<&'shape Node>,
+}
+
+/// A tree's layout: every node in pre-order, with a `None` standing in for
+/// each missing child, which is enough to tell where every node hangs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shape {
+    entries: Vec<Option<Node>>,
+}
+
+impl Shape {
+    /// Walks down from `root`, getting each node's left and right children
+    /// from `children` and its description from `describe`. Walks with a
+    /// stack rather than recursing, so that degenerate trees are fine too.
+    pub fn new<N>(
+        root: Option<N>,
+        mut children: impl FnMut(&N) -> [Option<N>; 2],
+        mut describe: impl FnMut(&N) -> Node,
+    ) -> Self {
+        let mut entries = Vec::new();
+        let mut stack = vec![root];
+
+        while let Some(next) = stack.pop() {
+            let Some(node) = next else {
+                entries.push(None);
+                continue;
+            };
+
+            let [left, right] = children(&node);
+            entries.push(Some(describe(&node)));
+            stack.push(right);
+            stack.push(left);
+        }
+
+        Shape { entries }
+    }
+
+    /// How many nodes there are, leaving out missing children.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.iter().flatten().count()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Every entry in order, with its parent's index and side
+    fn slots(&self) -> impl Iterator<Item = Slot<'_>> {
+        let mut pending = vec![None];
+
+        self.entries.iter().enumerate().map(move |(index, entry)| {
+            let parent = pending
+                .pop()
+                .expect("a pre-order shape has a slot per entry");
+
+            if entry.is_some() {
+                pending.push(Some((index, Side::Right)));
+                pending.push(Some((index, Side::Left)));
+            }
+
+            Slot {
+                index,
+                parent,
+                node: entry.as_ref(),
+            }
+        })
+    }
+}
+
+/// Implemented by every tree in the workspace.
+pub trait TreeView {
+    fn shape(&self) -> Shape;
+
+    /// The tree as a Graphviz graph, see [`dot`].
+    #[inline]
+    fn to_dot(&self) -> String {
+        self.shape().to_dot()
+    }
+
+    /// The tree drawn as text, see [`pretty`].
+    #[inline]
+    fn pretty(&self) -> Pretty {
+        self.shape().pretty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Trees as vectors of (item, left, right), the root first
+    pub(crate) fn shape(nodes: &[(u32, Option<usize>, Option<usize>)]) -> Shape {
+        Shape::new(
+            (!nodes.is_empty()).then_some(0),
+            |&i| [nodes[i].1, nodes[i].2],
+            |&i| Node::new(&nodes[i].0).note("i", i),
+        )
+    }
+
+    #[test]
+    fn walking_in_preorder() {
+        //     1
+        //    / \
+        //   2   3
+        //    \
+        //     4
+        let tree = shape(&[
+            (1, Some(1), Some(2)),
+            (2, None, Some(3)),
+            (3, None, None),
+            (4, None, None),
+        ]);
+        let labels: Vec<Option<&str>> = tree
+            .entries
+            .iter()
+            .map(|entry| entry.as_ref().map(|node| node.label.as_str()))
+            .collect();
+
+        assert_eq!(
+            labels,
+            [
+                Some("1"),
+                Some("2"),
+                None,
+                Some("4"),
+                None,
+                None,
+                Some("3"),
+                None,
+                None
+            ]
+        );
+        assert_eq!(tree.len(), 4);
+
+        let parents: Vec<Option<(usize, Side)>> = tree.slots().map(|slot| slot.parent).collect();
+        assert_eq!(parents[0], None);
+        assert_eq!(parents[1], Some((0, Side::Left)));
+        assert_eq!(parents[3], Some((1, Side::Right)));
+        assert_eq!(parents[6], Some((0, Side::Right)));
+        assert_eq!(parents[8], Some((6, Side::Right)));
+    }
+
+    #[test]
+    fn empty_and_degenerate_trees() {
+        let empty = shape(&[]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.entries, [None]);
+
+        // A long chain down the right, which recursing would overflow on
+        let chain: Vec<_> = (0..100_000)
+            .map(|i| (i as u32, None, (i + 1 < 100_000).then_some(i + 1)))
+            .collect();
+        assert_eq!(shape(&chain).len(), 100_000);
+    }
+
+    #[test]
+    fn describing_nodes() {
+        let node = Node::new("a").note("h", 2).note("bf", -1).color(Color::Red);
+
+        assert_eq!(node.label, "\"a\"");
+        assert_eq!(node.notes_line(), "h=2 bf=-1");
+        assert_eq!(node.color, Some(Color::Red));
+        assert_eq!(Node::new(&1).notes_line(), "");
+    }
+}
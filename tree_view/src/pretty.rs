@@ -0,0 +1,548 @@
+//! Text drawings, for terminals and test failures. Sideways, which is the
+//! default, each node comes on its own line with its children below it,
+//! left then right, and a `·` standing in for a missing one:
+//!
+//! ```text
+//! 5 (h=3 bf=1)
+//! ├── 3 (h=1 bf=0)
+//! └── 8 (h=2 bf=-1)
+//!     ├── 7 (h=1 bf=0)
+//!     └── ·
+//! ```
+//!
+//! Top down, which `{:#}` asks for too, children hang below their parent:
+//!
+//! ```text
+//!  5
+//! ┌┴─┐
+//! 3  8
+//!   ┌┘
+//!   7
+//! ```
+//!
+//! A width, e.g. `{:40}`, cuts every line at that many characters, and
+//! [`Pretty::depth`] cuts off deep subtrees, drawing a `…` in their place.
+
+use std::fmt::{self, Display, Write};
+
+use crate::{Color, Node, Shape};
+
+/// Which way a [`Pretty`] drawing grows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Layout {
+    #[default]
+    Sideways,
+    TopDown,
+}
+
+/// A shape drawn as text, see [`Shape::pretty`].
+#[derive(Debug, Clone)]
+pub struct Pretty {
+    shape: Shape,
+    layout: Layout,
+    notes: bool,
+    width: Option<usize>,
+    depth: Option<usize>,
+}
+
+impl Shape {
+    #[inline]
+    pub fn pretty(self) -> Pretty {
+        Pretty {
+            shape: self,
+            layout: Layout::default(),
+            notes: true,
+            width: None,
+            depth: None,
+        }
+    }
+}
+
+impl Pretty {
+    #[inline]
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Whether to show each node's colour and notes after its item, which
+    /// it does unless told otherwise.
+    #[inline]
+    pub fn notes(mut self, notes: bool) -> Self {
+        self.notes = notes;
+        self
+    }
+
+    /// Cuts every line at `width` characters, ending it with a `…`.
+    #[inline]
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Leaves out every node more than `depth` levels below the root.
+    #[inline]
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    fn label(&self, node: &Node) -> String {
+        let mut annotations = Vec::new();
+
+        if self.notes {
+            match node.color {
+                Some(Color::Red) => annotations.push("red".to_string()),
+                Some(Color::Black) => annotations.push("black".to_string()),
+                None => {}
+            }
+
+            if !node.notes.is_empty() {
+                annotations.push(node.notes_line());
+            }
+        }
+
+        match annotations.is_empty() {
+            true => node.label.clone(),
+            false => format!("{} ({})", node.label, annotations.join(" ")),
+        }
+    }
+
+    // Every entry to draw, by its index in the shape
+    fn drawn(&self) -> Vec<Option<Drawn>> {
+        let mut drawn: Vec<Option<Drawn>> = Vec::new();
+        let mut depths: Vec<usize> = Vec::new();
+
+        for slot in self.shape.slots() {
+            let depth = slot.parent.map_or(0, |(parent, _)| depths[parent] + 1);
+            depths.push(depth);
+            drawn.push(None);
+
+            let Some((parent, side)) = slot.parent else {
+                drawn[slot.index] = slot.node.map(|node| Drawn::new(self.label(node)));
+                continue;
+            };
+
+            let (Some(node), Some(above)) = (slot.node, &mut drawn[parent]) else {
+                continue;
+            };
+
+            if self.depth.is_some_and(|limit| depth > limit) {
+                above.below = Below::Cut;
+                continue;
+            }
+
+            if let Below::Children(children) = &mut above.below {
+                children[side as usize] = Some(slot.index);
+                drawn[slot.index] = Some(Drawn::new(self.label(node)));
+            }
+        }
+
+        drawn
+    }
+}
+
+// A node as drawn, with what hangs below it
+struct Drawn {
+    label: String,
+    below: Below,
+}
+
+enum Below {
+    Children([Option<usize>; 2]),
+    // Deeper than the drawing goes
+    Cut,
+}
+
+impl Drawn {
+    fn new(label: String) -> Self {
+        Self {
+            label,
+            below: Below::Children([None, None]),
+        }
+    }
+}
+
+fn sideways(drawn: &[Option<Drawn>]) -> Vec<String> {
+    let Some(root) = &drawn[0] else {
+        return vec!["·".to_string()];
+    };
+
+    let mut lines = vec![root.label.clone()];
+    // What's left to draw, each with its line's indent and whether it's
+    // the last of its parent's children
+    let mut stack: Vec<(Entry, String, bool)> = Vec::new();
+    push_children(&mut stack, root, "");
+
+    while let Some((entry, indent, last)) = stack.pop() {
+        let (branch, under) = match last {
+            true => ("└── ", "    "),
+            false => ("├── ", "│   "),
+        };
+
+        let node = match entry {
+            Entry::Node(index) => drawn[index].as_ref().expect("only drawn nodes are pushed"),
+            Entry::Missing => {
+                lines.push(format!("{}{}·", indent, branch));
+                continue;
+            }
+            Entry::Cut => {
+                lines.push(format!("{}{}…", indent, branch));
+                continue;
+            }
+        };
+
+        lines.push(format!("{}{}{}", indent, branch, node.label));
+        push_children(&mut stack, node, &(indent + under));
+    }
+
+    lines
+}
+
+enum Entry {
+    Node(usize),
+    Missing,
+    Cut,
+}
+
+// Leaves have nothing below them, but an only child is drawn along with
+// its missing sibling, so that it's clear which side it's on
+fn push_children(stack: &mut Vec<(Entry, String, bool)>, node: &Drawn, indent: &str) {
+    let entry = |child: Option<usize>| child.map_or(Entry::Missing, Entry::Node);
+
+    match node.below {
+        Below::Children([None, None]) => {}
+        Below::Children([left, right]) => {
+            stack.push((entry(right), indent.to_string(), true));
+            stack.push((entry(left), indent.to_string(), false));
+        }
+        Below::Cut => stack.push((Entry::Cut, indent.to_string(), true)),
+    }
+}
+
+// Where a subtree goes, relative to the left edge of the columns it takes
+struct Placed {
+    label: String,
+    label_at: usize,
+    // The corners joining it up to its children, and where they start
+    connector: String,
+    connector_at: usize,
+    width: usize,
+    // The column its parent's line comes down to
+    root: usize,
+    // Each child, with how far right of this subtree's edge it starts
+    children: Vec<(usize, usize)>,
+}
+
+impl Placed {
+    fn leaf(label: String) -> Self {
+        let width = label.chars().count();
+
+        Self {
+            label,
+            label_at: 0,
+            connector: String::new(),
+            connector_at: 0,
+            width,
+            root: (width.max(1) - 1) / 2,
+            children: Vec::new(),
+        }
+    }
+}
+
+// Lays every subtree out first, then writes each label and connector
+// straight into its line, so that no line is copied as subtrees are put
+// together
+fn top_down(mut drawn: Vec<Option<Drawn>>) -> Vec<String> {
+    if drawn[0].is_none() {
+        return vec!["·".to_string()];
+    }
+
+    let mut placed: Vec<Option<Placed>> = (0..drawn.len()).map(|_| None).collect();
+
+    // In pre-order, children come after their parents, so going backwards
+    // every child is placed before its parent
+    for index in (0..drawn.len()).rev() {
+        let Some(node) = drawn[index].take() else {
+            continue;
+        };
+
+        let (children, cut) = match node.below {
+            Below::Children(children) => (children, false),
+            Below::Cut => {
+                placed.push(Some(Placed::leaf("…".to_string())));
+                ([Some(placed.len() - 1), None], true)
+            }
+        };
+        let below = children.map(|child| {
+            child.map(|child| {
+                (
+                    child,
+                    placed[child].as_ref().expect("children are placed first"),
+                )
+            })
+        });
+
+        placed[index] = Some(place(node.label, below, cut));
+    }
+
+    let mut lines = Lines::default();
+    // What's left to write, each with its depth and leftmost column
+    let mut stack = vec![(0, 0, 0)];
+
+    while let Some((index, depth, left)) = stack.pop() {
+        let node = placed[index].take().expect("every drawn node is placed");
+
+        lines.write(2 * depth, left + node.label_at, &node.label);
+        if !node.connector.is_empty() {
+            lines.write(2 * depth + 1, left + node.connector_at, &node.connector);
+        }
+
+        // Left first, so that each line is written left to right
+        for &(child, offset) in node.children.iter().rev() {
+            stack.push((child, depth + 1, left + offset));
+        }
+    }
+
+    lines.0.into_iter().map(|(line, _)| line).collect()
+}
+
+// Lines written left to right, each with how many characters it has
+#[derive(Default)]
+struct Lines(Vec<(String, usize)>);
+
+impl Lines {
+    fn write(&mut self, row: usize, column: usize, text: &str) {
+        if self.0.len() <= row {
+            self.0.resize_with(row + 1, Default::default);
+        }
+
+        let (line, count) = &mut self.0[row];
+        debug_assert!(*count <= column, "subtrees don't overlap");
+        line.extend(std::iter::repeat_n(' ', column.saturating_sub(*count)));
+        line.push_str(text);
+        *count = column.max(*count) + text.chars().count();
+    }
+}
+
+// Places `label` above its children's subtrees, side by side with a column
+// between them, and the lines joining them up
+fn place(label: String, [left, right]: [Option<(usize, &Placed)>; 2], cut: bool) -> Placed {
+    // The children with their offsets, how wide they are together, the
+    // column the parent's line comes down to, and the corners joining them
+    let (mut children, below_width, parent, connections) = match (left, right) {
+        (None, None) => return Placed::leaf(label),
+
+        (Some((only, place)), None) if cut => {
+            let root = place.root as isize;
+            (vec![(only, 0)], place.width, root, vec![(root, '│')])
+        }
+
+        (Some((left, place)), None) => {
+            let root = place.root as isize;
+            (
+                vec![(left, 0)],
+                place.width,
+                root + 1,
+                vec![(root, '┌'), (root + 1, '┘')],
+            )
+        }
+
+        (None, Some((right, place))) => {
+            let root = place.root as isize;
+            (
+                vec![(right, 0)],
+                place.width,
+                root - 1,
+                vec![(root - 1, '└'), (root, '┐')],
+            )
+        }
+
+        (Some((left, left_place)), Some((right, right_place))) => {
+            let (from, to) = (left_place.root, left_place.width + 1 + right_place.root);
+            let mid = (from + to) / 2;
+
+            let children = vec![(left, 0), (right, left_place.width + 1)];
+            let width = left_place.width + 1 + right_place.width;
+            let (from, mid, to) = (from as isize, mid as isize, to as isize);
+            (
+                children,
+                width,
+                mid,
+                vec![(from, '┌'), (mid, '┴'), (to, '┐')],
+            )
+        }
+    };
+
+    let label_width = label.chars().count() as isize;
+    let start = parent - (label_width.max(1) - 1) / 2;
+    let shift = (-start).max(0);
+    let width = (below_width as isize + shift).max(start + shift + label_width) as usize;
+
+    let (first, last) = (connections[0].0, connections[connections.len() - 1].0);
+    let mut connector = vec!['─'; (last - first + 1) as usize];
+    for &(column, corner) in &connections {
+        connector[(column - first) as usize] = corner;
+    }
+
+    for (_, offset) in &mut children {
+        *offset += shift as usize;
+    }
+
+    Placed {
+        label,
+        label_at: (start + shift) as usize,
+        connector: connector.into_iter().collect(),
+        connector_at: (first + shift) as usize,
+        width,
+        root: (parent + shift) as usize,
+        children,
+    }
+}
+
+// Cuts `line` to `width` characters, marking where with a `…`
+fn clip(line: &str, width: Option<usize>) -> &str {
+    match width {
+        Some(width) if line.chars().count() > width => {
+            let end = line
+                .char_indices()
+                .nth(width.saturating_sub(1))
+                .map_or(line.len(), |(end, _)| end);
+            &line[..end]
+        }
+        _ => line,
+    }
+}
+
+impl Display for Pretty {
+    /// `{:#}` draws top down whichever layout was picked, and a width
+    /// cuts lines unless one was picked already.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let layout = match f.alternate() {
+            true => Layout::TopDown,
+            false => self.layout,
+        };
+        let width = self.width.or(f.width());
+
+        let drawn = self.drawn();
+        let lines = match layout {
+            Layout::Sideways => sideways(&drawn),
+            Layout::TopDown => top_down(drawn),
+        };
+
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                f.write_char('\n')?;
+            }
+
+            let line = line.trim_end();
+            let kept = clip(line, width);
+            f.write_str(kept)?;
+
+            if kept.len() < line.len() && width != Some(0) {
+                f.write_char('…')?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::shape;
+
+    //       5
+    //     /   \
+    //    3     8
+    //   / \   /
+    //  1   4 7
+    fn tree() -> Shape {
+        shape(&[
+            (5, Some(1), Some(4)),
+            (3, Some(2), Some(3)),
+            (1, None, None),
+            (4, None, None),
+            (8, Some(5), None),
+            (7, None, None),
+        ])
+    }
+
+    #[test]
+    fn drawing_sideways() {
+        assert_eq!(
+            tree().pretty().to_string(),
+            "\
+5 (i=0)
+├── 3 (i=1)
+│   ├── 1 (i=2)
+│   └── 4 (i=3)
+└── 8 (i=4)
+    ├── 7 (i=5)
+    └── ·"
+        );
+        assert_eq!(shape(&[]).pretty().to_string(), "·");
+    }
+
+    #[test]
+    fn drawing_top_down() {
+        let drawn = tree().pretty().layout(Layout::TopDown).notes(false);
+        let expected = "   5\n ┌─┴─┐\n 3   8\n┌┴┐ ┌┘\n1 4 7";
+
+        assert_eq!(drawn.to_string(), expected);
+        assert_eq!(format!("{:#}", tree().pretty().notes(false)), expected);
+
+        // Only children hang off to their side, under labels wider than them
+        let chain = shape(&[(100, None, Some(1)), (2, Some(2), None), (3, None, None)]);
+        assert_eq!(
+            format!("{:#}", chain.pretty().notes(false)),
+            "100\n └┐\n  2\n ┌┘\n 3"
+        );
+    }
+
+    #[test]
+    fn cutting_large_trees() {
+        let cut = tree().pretty().notes(false).depth(1);
+        assert_eq!(cut.to_string(), "5\n├── 3\n│   └── …\n└── 8\n    └── …");
+        assert_eq!(format!("{:#}", cut), " 5\n┌┴┐\n3 8\n│ │\n… …");
+
+        // Cut to the width asked for, in either layout
+        assert_eq!(
+            tree().pretty().width(8).to_string().lines().nth(2),
+            Some("│   ├──…")
+        );
+        assert_eq!(
+            format!("{:8}", tree().pretty()).lines().nth(2),
+            Some("│   ├──…")
+        );
+        assert!(format!("{:#4}", tree().pretty())
+            .lines()
+            .all(|line| line.chars().count() <= 4));
+
+        // Deep enough to overflow the stack, if drawing recursed
+        let chain: Vec<_> = (0..100_000)
+            .map(|i| (i as u32, (i + 1 < 100_000).then_some(i + 1), None))
+            .collect();
+        assert_eq!(
+            shape(&chain).pretty().depth(2).to_string().lines().count(),
+            6
+        );
+    }
+
+    #[test]
+    fn drawing_long_chains_top_down() {
+        // Each line written once, rather than copied up through every
+        // node above it
+        let chain: Vec<_> = (0..5_000)
+            .map(|i| (i as u32, (i + 1 < 5_000).then_some(i + 1), None))
+            .collect();
+        let drawn = format!("{:#}", shape(&chain).pretty().notes(false));
+        let lines: Vec<&str> = drawn.lines().collect();
+
+        assert_eq!(lines.len(), 2 * 5_000 - 1);
+        assert_eq!(lines[lines.len() - 2].trim(), "┌┘");
+        assert_eq!(lines[lines.len() - 1], "4999");
+        assert!(lines[0].ends_with('0'));
+    }
+}
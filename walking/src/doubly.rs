@@ -0,0 +1,827 @@
+//! A doubly linked list, whose nodes can be unlinked from anywhere in
+//! constant time through a `CursorMut`, e.g. to evict from an LRU cache.
+//!
+//! Unlike `LinkedList`, nodes are owned through `NonNull` links rather
+//! than boxes. Every pointer to a node is copied from the one made when
+//! it was allocated, so none of them can go stale while the list is
+//! moved around or walked in either direction.
+
+use std::iter::{FromIterator, FusedIterator};
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    val: T,
+    prev: Link<T>,
+    next: Link<T>,
+}
+
+impl<T> Node<T> {
+    #[inline]
+    fn alloc(val: T) -> NonNull<Self> {
+        let node = Box::new(Node {
+            val,
+            prev: None,
+            next: None,
+        });
+
+        NonNull::from(Box::leak(node))
+    }
+
+    // `node` must have come from `alloc`, and no longer be linked to
+    #[inline]
+    unsafe fn free(node: NonNull<Self>) -> T {
+        Box::from_raw(node.as_ptr()).val
+    }
+}
+
+pub struct DoublyLinkedList<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _owns: PhantomData<Box<Node<T>>>,
+}
+
+// SAFETY: the links only ever point into nodes the list owns
+unsafe impl<T: Send> Send for DoublyLinkedList<T> {}
+// SAFETY: as above
+unsafe impl<T: Sync> Sync for DoublyLinkedList<T> {}
+
+impl<T> Default for DoublyLinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> DoublyLinkedList<T> {
+    #[inline]
+    pub fn new() -> Self {
+        DoublyLinkedList {
+            head: None,
+            tail: None,
+            len: 0,
+            _owns: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    #[inline]
+    pub fn append_front(&mut self, val: T) {
+        self.link(Node::alloc(val), None, self.head);
+    }
+
+    #[inline]
+    pub fn append_back(&mut self, val: T) {
+        self.link(Node::alloc(val), self.tail, None);
+    }
+
+    /// Moves every node of `other` onto the end of this list, leaving
+    /// `other` empty.
+    #[inline]
+    pub fn append(&mut self, other: &mut Self) {
+        self.splice(self.tail, None, mem::take(other));
+    }
+
+    #[inline]
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.head.map(|node| self.unlink(node))
+    }
+
+    #[inline]
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.map(|node| self.unlink(node))
+    }
+
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        // SAFETY: `head` and `tail` are null or nodes the list owns, borrowed along
+        // with it
+        self.head.map(|node| unsafe { &(*node.as_ptr()).val })
+    }
+
+    #[inline]
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: as in `peek`
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    #[inline]
+    pub fn back(&self) -> Option<&T> {
+        // SAFETY: as in `peek`
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).val })
+    }
+
+    #[inline]
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: as in `peek`
+        self.tail.map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    /// A cursor on the first value, or on the "ghost" position if the
+    /// list is empty.
+    #[inline]
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.head,
+            index: 0,
+            list: self,
+        }
+    }
+
+    /// A cursor on the last value, or on the "ghost" position if the list
+    /// is empty.
+    #[inline]
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.tail,
+            index: self.len.saturating_sub(1),
+            list: self,
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _list: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _list: PhantomData,
+        }
+    }
+}
+
+// Everything that relinks nodes goes through these. `None` as `prev` or
+// `next` stands for the front or the back of the list.
+impl<T> DoublyLinkedList<T> {
+    // Links a lone `node` in between `prev` and `next`, which must be
+    // next to each other
+    fn link(&mut self, node: NonNull<Node<T>>, prev: Link<T>, next: Link<T>) {
+        // SAFETY: `node` is a fresh allocation and `prev` and `next` belong
+        // to this list, which is borrowed mutably, and no reference into
+        // any of them is held meanwhile
+        unsafe {
+            (*node.as_ptr()).prev = prev;
+            (*node.as_ptr()).next = next;
+
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = Some(node),
+                None => self.head = Some(node),
+            }
+            match next {
+                Some(next) => (*next.as_ptr()).prev = Some(node),
+                None => self.tail = Some(node),
+            }
+        }
+
+        self.len += 1;
+    }
+
+    // `node` must belong to this list
+    fn unlink(&mut self, node: NonNull<Node<T>>) -> T {
+        // SAFETY: `node` and its neighbours belong to this list, which is
+        // borrowed mutably, and once unlinked nothing points at `node`
+        unsafe {
+            let (prev, next) = ((*node.as_ptr()).prev, (*node.as_ptr()).next);
+
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = next,
+                None => self.head = next,
+            }
+            match next {
+                Some(next) => (*next.as_ptr()).prev = prev,
+                None => self.tail = prev,
+            }
+
+            self.len -= 1;
+            Node::free(node)
+        }
+    }
+
+    // Moves every node of `other` in between `prev` and `next`, which
+    // must be next to each other
+    fn splice(&mut self, prev: Link<T>, next: Link<T>, mut other: Self) {
+        let (Some(first), Some(last)) = (other.head.take(), other.tail.take()) else {
+            return;
+        };
+
+        // SAFETY: `first` and `last` were owned by `other`, which is
+        // consumed, and `prev` and `next` belong to this list
+        unsafe {
+            (*first.as_ptr()).prev = prev;
+            (*last.as_ptr()).next = next;
+
+            match prev {
+                Some(prev) => (*prev.as_ptr()).next = Some(first),
+                None => self.head = Some(first),
+            }
+            match next {
+                Some(next) => (*next.as_ptr()).prev = Some(last),
+                None => self.tail = Some(last),
+            }
+        }
+
+        self.len += mem::take(&mut other.len);
+    }
+
+    // Cuts the list after `prev`, keeping the front and returning the
+    // back, which must be `back_len` nodes long
+    fn split_after(&mut self, prev: Link<T>, back_len: usize) -> Self {
+        let first = match prev {
+            // SAFETY: `prev` belongs to this list, which is borrowed mutably
+            Some(prev) => unsafe { (*prev.as_ptr()).next.take() },
+            None => self.head.take(),
+        };
+
+        let Some(first) = first else {
+            return Self::new();
+        };
+
+        // SAFETY: as above, for the node after `prev`
+        unsafe { (*first.as_ptr()).prev = None };
+
+        let back = DoublyLinkedList {
+            head: Some(first),
+            tail: mem::replace(&mut self.tail, prev),
+            len: back_len,
+            _owns: PhantomData,
+        };
+
+        self.len -= back_len;
+        back
+    }
+}
+
+// Unlinking nodes one at a time also keeps dropping long lists iterative
+impl<T> Drop for DoublyLinkedList<T> {
+    fn drop(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+/// A position in a `DoublyLinkedList` that values can be inserted at and
+/// removed from in constant time.
+///
+/// Besides the list's values, the cursor can rest on a "ghost" position
+/// past the back and before the front, at index `len`. Moving past either
+/// end lands on it, and moving on from it wraps around to the other end.
+pub struct CursorMut<'list, T> {
+    // `None` on the ghost
+    current: Link<T>,
+    index: usize,
+    list: &'list mut DoublyLinkedList<T>,
+}
+
+impl<'list, T> CursorMut<'list, T> {
+    /// The index of the current value, or `None` on the ghost.
+    #[inline]
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    #[inline]
+    pub fn current(&mut self) -> Option<&mut T> {
+        // SAFETY: the cursor's nodes all belong to the list it borrows
+        // mutably, and `&mut self` keeps this the only reference into it
+        self.current
+            .map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    #[inline]
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        // SAFETY: as in `current`
+        self.next_link()
+            .map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    #[inline]
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        // SAFETY: as in `current`
+        self.prev_link()
+            .map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    #[inline]
+    pub fn move_next(&mut self) {
+        self.index = match self.current {
+            Some(_) => self.index + 1,
+            None => 0,
+        };
+        self.current = self.next_link();
+    }
+
+    #[inline]
+    pub fn move_prev(&mut self) {
+        self.index = match (self.current, self.prev_link()) {
+            (Some(_), Some(_)) => self.index - 1,
+            (Some(_), None) => self.list.len,
+            (None, _) => self.list.len.saturating_sub(1),
+        };
+        self.current = self.prev_link();
+    }
+
+    /// Inserts `val` before the current value, or at the back of the list
+    /// on the ghost.
+    #[inline]
+    pub fn insert_before(&mut self, val: T) {
+        self.list
+            .link(Node::alloc(val), self.prev_link(), self.current);
+        self.index += 1;
+    }
+
+    /// Inserts `val` after the current value, or at the front of the list
+    /// on the ghost.
+    #[inline]
+    pub fn insert_after(&mut self, val: T) {
+        self.list
+            .link(Node::alloc(val), self.current, self.next_link());
+
+        if self.current.is_none() {
+            self.index = self.list.len;
+        }
+    }
+
+    /// Removes the current value and moves on to the next one. Does
+    /// nothing on the ghost.
+    #[inline]
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+
+        self.current = self.next_link();
+        Some(self.list.unlink(node))
+    }
+
+    /// Splits off everything before the current value, or the whole list
+    /// on the ghost.
+    pub fn split_before(&mut self) -> DoublyLinkedList<T> {
+        let front = match self.current {
+            Some(_) => {
+                let back = self
+                    .list
+                    .split_after(self.prev_link(), self.list.len - self.index);
+                mem::replace(self.list, back)
+            }
+
+            None => mem::take(self.list),
+        };
+
+        self.index = 0;
+        front
+    }
+
+    /// Splits off everything after the current value, or the whole list
+    /// on the ghost.
+    pub fn split_after(&mut self) -> DoublyLinkedList<T> {
+        let back_len = match self.current {
+            Some(_) => self.list.len - self.index - 1,
+            None => self.list.len,
+        };
+
+        let back = self.list.split_after(self.current, back_len);
+
+        if self.current.is_none() {
+            self.index = 0;
+        }
+        back
+    }
+
+    /// Moves every value of `other` in before the current value, or onto
+    /// the back of the list on the ghost.
+    pub fn splice_before(&mut self, other: DoublyLinkedList<T>) {
+        self.index += other.len;
+        self.list.splice(self.prev_link(), self.current, other);
+    }
+
+    /// Moves every value of `other` in after the current value, or onto
+    /// the front of the list on the ghost.
+    pub fn splice_after(&mut self, other: DoublyLinkedList<T>) {
+        self.list.splice(self.current, self.next_link(), other);
+
+        if self.current.is_none() {
+            self.index = self.list.len;
+        }
+    }
+
+    #[inline]
+    fn next_link(&self) -> Link<T> {
+        match self.current {
+            // SAFETY: the current node belongs to the list
+            Some(node) => unsafe { (*node.as_ptr()).next },
+            None => self.list.head,
+        }
+    }
+
+    #[inline]
+    fn prev_link(&self) -> Link<T> {
+        match self.current {
+            // SAFETY: as in `next_link`
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+            None => self.list.tail,
+        }
+    }
+}
+
+// Both ends are tracked so the iterators can also be walked backwards;
+// `len` stops them once they meet
+pub struct Iter<'list, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _list: PhantomData<&'list T>,
+}
+
+pub struct IterMut<'list, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _list: PhantomData<&'list mut T>,
+}
+
+pub struct IntoIter<T>(DoublyLinkedList<T>);
+
+// SAFETY: the iterators only stand for a `&` or a `&mut` borrow of the
+// list's values
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+// SAFETY: as above
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+// SAFETY: as above
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+// SAFETY: as above
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter { ..*self }
+    }
+}
+
+impl<'list, T> Iterator for Iter<'list, T> {
+    type Item = &'list T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        // SAFETY: `head` and `tail` are nodes of the list borrowed for
+        // 'list, and `len` stops them before they pass each other, so no
+        // node is handed out twice
+        self.head.map(|node| unsafe {
+            self.len -= 1;
+            self.head = (*node.as_ptr()).next;
+            &(*node.as_ptr()).val
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        // SAFETY: as in `Iter::next`
+        self.tail.map(|node| unsafe {
+            self.len -= 1;
+            self.tail = (*node.as_ptr()).prev;
+            &(*node.as_ptr()).val
+        })
+    }
+}
+
+impl<'list, T> Iterator for IterMut<'list, T> {
+    type Item = &'list mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        // SAFETY: as in `Iter::next`
+        self.head.map(|node| unsafe {
+            self.len -= 1;
+            self.head = (*node.as_ptr()).next;
+            &mut (*node.as_ptr()).val
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+
+        // SAFETY: as in `Iter::next`
+        self.tail.map(|node| unsafe {
+            self.len -= 1;
+            self.tail = (*node.as_ptr()).prev;
+            &mut (*node.as_ptr()).val
+        })
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+impl<T> ExactSizeIterator for IntoIter<T> {}
+impl<T> FusedIterator for Iter<'_, T> {}
+impl<T> FusedIterator for IterMut<'_, T> {}
+impl<T> FusedIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for DoublyLinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'list, T> IntoIterator for &'list DoublyLinkedList<T> {
+    type Item = &'list T;
+    type IntoIter = Iter<'list, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'list, T> IntoIterator for &'list mut DoublyLinkedList<T> {
+    type Item = &'list mut T;
+    type IntoIter = IterMut<'list, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> FromIterator<T> for DoublyLinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+
+        for val in iter {
+            list.append_back(val);
+        }
+
+        list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl<T: Copy> DoublyLinkedList<T> {
+        fn to_vec(&self) -> Vec<T> {
+            self.iter().copied().collect()
+        }
+
+        // Walks the links both ways, checking they agree with each other
+        fn check_links(&self) {
+            let forwards: Vec<_> = self.iter().map(|val| val as *const T).collect();
+            let mut backwards: Vec<_> = self.iter().rev().map(|val| val as *const T).collect();
+            backwards.reverse();
+
+            assert_eq!(forwards.len(), self.len);
+            assert_eq!(forwards, backwards);
+            assert_eq!(self.head.is_none(), self.tail.is_none());
+        }
+    }
+
+    #[test]
+    fn deque_operations() {
+        let mut list = DoublyLinkedList::new();
+        assert_eq!(list.pop_back(), None);
+
+        list.append_back(2);
+        list.append_front(1);
+        list.append_back(3);
+        *list.back_mut().unwrap() += 1;
+        *list.peek_mut().unwrap() -= 1;
+
+        assert_eq!((list.peek(), list.back()), (Some(&0), Some(&4)));
+        assert_eq!(list.pop_back(), Some(4));
+        assert_eq!(list.pop_front(), Some(0));
+        assert_eq!(list.pop_back(), Some(2));
+        assert!(list.is_empty());
+        list.check_links();
+
+        let mut other: DoublyLinkedList<i32> = (4..=6).collect();
+        list.append(&mut (1..=3).collect());
+        list.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(list.to_vec(), [1, 2, 3, 4, 5, 6]);
+        list.check_links();
+    }
+
+    #[test]
+    fn iterating_both_ways() {
+        let mut list: DoublyLinkedList<i32> = (1..=5).collect();
+
+        for val in list.iter_mut().rev().step_by(2) {
+            *val *= 10;
+        }
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 5);
+        assert_eq!(iter.next(), Some(&10));
+        assert_eq!(iter.next_back(), Some(&50));
+        assert_eq!(iter.clone().count(), 3);
+        assert!(iter.eq(&[2, 30, 4]));
+
+        let mut into_iter = list.into_iter();
+        assert_eq!(into_iter.next_back(), Some(50));
+        assert!(into_iter.eq([10, 2, 30, 4]));
+    }
+
+    #[test]
+    fn moving_the_cursor() {
+        let mut list: DoublyLinkedList<i32> = (0..3).collect();
+        let mut cursor = list.cursor_front_mut();
+
+        assert_eq!(cursor.index(), Some(0));
+        assert_eq!(cursor.peek_prev(), None);
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(
+            (cursor.index(), cursor.current().copied()),
+            (Some(2), Some(2))
+        );
+
+        // Off the back onto the ghost, and round to the front
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.current()), (None, None));
+        assert_eq!(cursor.peek_next().copied(), Some(0));
+        assert_eq!(cursor.peek_prev().copied(), Some(2));
+        cursor.move_next();
+        assert_eq!(cursor.index(), Some(0));
+
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(
+            (cursor.index(), cursor.current().copied()),
+            (Some(2), Some(2))
+        );
+
+        let mut empty: DoublyLinkedList<i32> = DoublyLinkedList::new();
+        let mut cursor = empty.cursor_back_mut();
+        cursor.move_prev();
+        cursor.move_next();
+        assert_eq!((cursor.index(), cursor.current()), (None, None));
+    }
+
+    #[test]
+    fn inserting_and_removing_at_the_cursor() {
+        let mut list: DoublyLinkedList<i32> = [1, 3].into_iter().collect();
+        let mut cursor = list.cursor_front_mut();
+
+        cursor.insert_before(0);
+        cursor.insert_after(2);
+        assert_eq!(cursor.index(), Some(1));
+
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.insert_after(-1);
+        cursor.insert_before(4);
+        assert_eq!(cursor.index(), None);
+
+        cursor.move_next();
+        assert_eq!(cursor.remove_current(), Some(-1));
+        assert_eq!(cursor.index(), Some(0));
+        cursor.move_prev();
+        cursor.move_prev();
+        assert_eq!(cursor.remove_current(), Some(4));
+        assert_eq!(cursor.index(), None);
+        assert_eq!(cursor.remove_current(), None);
+
+        assert_eq!(list.to_vec(), [0, 1, 2, 3]);
+        list.check_links();
+    }
+
+    #[test]
+    fn splitting_and_splicing() {
+        let mut list: DoublyLinkedList<i32> = (0..6).collect();
+        let mut cursor = list.cursor_front_mut();
+
+        cursor.move_next();
+        cursor.move_next();
+        let front = cursor.split_before();
+        assert_eq!(cursor.index(), Some(0));
+        let back = cursor.split_after();
+        assert_eq!(cursor.current().copied(), Some(2));
+
+        assert_eq!((front.to_vec(), back.to_vec()), (vec![0, 1], vec![3, 4, 5]));
+        front.check_links();
+        back.check_links();
+
+        cursor.splice_after(back);
+        cursor.splice_before(front);
+        assert_eq!(cursor.index(), Some(2));
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        cursor.move_next();
+        assert_eq!(cursor.index(), None);
+
+        let pair: DoublyLinkedList<i32> = [-2, -1].into_iter().collect();
+        cursor.splice_after(pair);
+        cursor.splice_before([6].into_iter().collect());
+        cursor.splice_before(DoublyLinkedList::new());
+        assert_eq!(cursor.index(), None);
+
+        assert_eq!(list.to_vec(), [-2, -1, 0, 1, 2, 3, 4, 5, 6]);
+        list.check_links();
+
+        let mut cursor = list.cursor_back_mut();
+        cursor.move_next();
+        let all = cursor.split_after();
+        assert_eq!(all.len(), 9);
+        all.check_links();
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn least_recently_used() {
+        // Most recently used at the front, evicted from the back
+        let mut lru: DoublyLinkedList<char> = DoublyLinkedList::new();
+        let mut touch = |key: char| {
+            let mut cursor = lru.cursor_front_mut();
+
+            while cursor.current().is_some_and(|current| *current != key) {
+                cursor.move_next();
+            }
+            cursor.remove_current();
+
+            lru.append_front(key);
+            if lru.len() > 3 {
+                lru.pop_back();
+            }
+            lru.to_vec()
+        };
+
+        touch('a');
+        touch('b');
+        touch('c');
+        assert_eq!(touch('a'), ['a', 'c', 'b']);
+        assert_eq!(touch('d'), ['d', 'a', 'c']);
+        assert_eq!(touch('c'), ['c', 'd', 'a']);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "too slow under Miri")]
+    fn long_lists() {
+        let list: DoublyLinkedList<u32> = (0..1_000_000).collect();
+        assert_eq!(list.iter().next_back(), Some(&999_999));
+        drop(list);
+    }
+}
@@ -0,0 +1,588 @@
+#![warn(clippy::undocumented_unsafe_blocks)]
+
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr::NonNull;
+
+pub mod doubly;
+mod sort;
+mod tail;
+#[cfg(test)]
+mod unsafe_paths;
+
+pub use doubly::{CursorMut, DoublyLinkedList};
+
+type ListLink<T> = Option<Box<ListNode<T>>>;
+
+struct ListNode<T> {
+    val: T,
+    next: ListLink<T>,
+}
+
+impl<T> ListNode<T> {
+    fn new(val: T, next: ListLink<T>) -> Self {
+        ListNode { val, next }
+    }
+}
+
+impl<T> From<ListNode<T>> for ListLink<T> {
+    fn from(node: ListNode<T>) -> Self {
+        Some(Box::new(node))
+    }
+}
+
+pub struct LinkedList<T> {
+    head: ListLink<T>,
+    // The last node, so appending doesn't have to walk there. `None` until
+    // there are two nodes: the head is reached through `head` alone, since
+    // its box is reborrowed (and any pointer taken from it before then
+    // made stale, as far as Miri is concerned) every time the list moves.
+    // For the same reason nothing else may reborrow the last node through
+    // the box owning it while this is set.
+    tail: Option<NonNull<ListNode<T>>>,
+    len: usize,
+}
+
+// SAFETY: `tail` only points into nodes the list owns
+unsafe impl<T: Send> Send for LinkedList<T> {}
+// SAFETY: as above
+unsafe impl<T: Sync> Sync for LinkedList<T> {}
+
+// Walks a list by raw pointer, side-stepping the borrow checker's
+// reluctance to let a `&mut` cursor be reassigned to its own child. Holds
+// the list mutably borrowed for 'list, so nothing else can touch it.
+struct ListWalker<'list, T> {
+    curr_ptr: NonNull<ListNode<T>>,
+    _phantom: PhantomData<&'list mut ListNode<T>>,
+}
+
+impl<'list, T> ListWalker<'list, T> {
+    /// Starts at the first node, if there is one.
+    #[inline]
+    fn new(start_ref: &'list mut ListLink<T>) -> Option<Self> {
+        let start = start_ref.as_deref_mut()?;
+
+        Some(Self {
+            curr_ptr: NonNull::from(start),
+            _phantom: PhantomData,
+        })
+    }
+
+    #[inline]
+    fn move_forward(&mut self) -> Result<(), ()> {
+        // SAFETY: `curr_ptr` was derived from the `&'list mut` the walker
+        // was made from, and only the walker uses it until
+        // `into_inner_ref`. The `&mut` made here ends before the next step.
+        let curr_node = unsafe { self.curr_ptr.as_mut() };
+
+        match curr_node.next.as_deref_mut() {
+            Some(next_node) => {
+                self.curr_ptr = NonNull::from(next_node);
+                Ok(())
+            }
+
+            None => Err(()),
+        }
+    }
+
+    #[inline]
+    fn peek_next(&self) -> Option<&ListNode<T>> {
+        // SAFETY: as in `move_forward`, and the shared reference keeps the
+        // walker from moving while it's held
+        unsafe { self.curr_ptr.as_ref() }.next.as_deref()
+    }
+
+    #[inline]
+    fn into_inner_ref(mut self) -> &'list mut ListNode<T> {
+        // SAFETY: consuming the walker hands its exclusive access back as
+        // the one reference left for the rest of 'list
+        unsafe { self.curr_ptr.as_mut() }
+    }
+}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> LinkedList<T> {
+    #[inline]
+    pub fn new() -> Self {
+        LinkedList {
+            head: None,
+            tail: None,
+            len: 0,
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn append_front(&mut self, val: T) {
+        let new_node = ListNode::new(val, self.head.take()).into();
+        self.head = new_node;
+        self.len += 1;
+
+        // The old head just became the last node that isn't the head
+        if self.len == 2 {
+            self.tail = self
+                .head
+                .as_mut()
+                .and_then(|head| head.next.as_deref_mut())
+                .map(NonNull::from);
+        }
+    }
+
+    /// Runs in constant time.
+    pub fn append_back(&mut self, val: T) {
+        let link = self.get_tail_mut();
+        let new_node = link.insert(Box::new(ListNode::new(val, None)));
+        let new_tail = NonNull::from(&mut **new_node);
+
+        self.len += 1;
+        if self.len >= 2 {
+            self.tail = Some(new_tail);
+        }
+    }
+
+    /// Moves every node of `other` onto the end of this list, in constant
+    /// time, leaving `other` empty.
+    pub fn append(&mut self, other: &mut Self) {
+        if other.is_empty() {
+            return;
+        }
+
+        let other_tail = other.tail.take();
+        let len = self.len + mem::take(&mut other.len);
+
+        let link = self.get_tail_mut();
+        *link = other.head.take();
+
+        // A single node moved over has no `tail` to bring with it
+        let tail = match other_tail {
+            Some(tail) => Some(tail),
+            None if len >= 2 => link.as_deref_mut().map(NonNull::from),
+            None => None,
+        };
+
+        self.tail = tail;
+        self.len = len;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        let node = self.head.take()?;
+
+        self.head = node.next;
+        self.len -= 1;
+
+        if self.len < 2 {
+            self.tail = None;
+        }
+        Some(node.val)
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.head.as_ref()?.next.is_none() {
+            return self.pop_front();
+        }
+
+        // Stop on the second to last node, which owns the last one
+        let mut walker = ListWalker::new(&mut self.head)?;
+
+        while walker.peek_next().is_some_and(|next| next.next.is_some()) {
+            _ = walker.move_forward();
+        }
+
+        let new_last = walker.into_inner_ref();
+        let last = new_last.next.take()?;
+
+        self.len -= 1;
+        self.tail = (self.len >= 2).then(|| NonNull::from(new_last));
+        Some(last.val)
+    }
+
+    #[inline]
+    pub fn peek(&self) -> Option<&T> {
+        self.head.as_deref().map(|node| &node.val)
+    }
+
+    #[inline]
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.head.as_deref_mut().map(|node| &mut node.val)
+    }
+
+    #[inline]
+    pub fn back(&self) -> Option<&T> {
+        match self.tail {
+            // SAFETY: `tail` points at the last node, which nothing else
+            // reborrows while it's set, and `&self` keeps the list as is
+            Some(tail) => Some(unsafe { &tail.as_ref().val }),
+            None => self.peek(),
+        }
+    }
+
+    #[inline]
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        match self.tail {
+            // SAFETY: as in `back`, with `&mut self` making this the only
+            // reference into the list
+            Some(mut tail) => Some(unsafe { &mut tail.as_mut().val }),
+            None => self.peek_mut(),
+        }
+    }
+
+    /// Reverses the list in place, without moving any values.
+    pub fn reverse(&mut self) {
+        let mut reversed: ListLink<T> = None;
+        let mut rest = self.head.take();
+
+        while let Some(mut node) = rest {
+            rest = node.next.take();
+            node.next = reversed;
+            reversed = Some(node);
+        }
+
+        self.head = reversed;
+        self.reset_tail();
+    }
+
+    /// Prints a line per node, for debugging.
+    pub fn walk(&mut self) {
+        let Some(mut walker) = ListWalker::new(&mut self.head) else {
+            println!("empty list, nothing to walk");
+            return;
+        };
+
+        println!("starting walk @ head...");
+
+        while let Ok(()) = walker.move_forward() {
+            println!("Walked to another node");
+        }
+
+        self.reset_tail();
+    }
+}
+
+impl<T> LinkedList<T> {
+    // The empty link past the last node. Only short lists, with no `tail`
+    // yet, have to go looking for it, with whichever strategy the crate's
+    // features select.
+    #[inline]
+    fn get_tail_mut(&mut self) -> &mut ListLink<T> {
+        match self.tail {
+            // SAFETY: as in `back_mut`
+            Some(mut tail) => unsafe { &mut tail.as_mut().next },
+            None => tail::selected(&mut self.head),
+        }
+    }
+
+    // Walks to the last node to point `tail` at it afresh, after moving
+    // every node around or reborrowing them all through their boxes
+    fn reset_tail(&mut self) {
+        self.tail = match ListWalker::new(&mut self.head) {
+            Some(mut walker) if self.len >= 2 => {
+                while let Ok(()) = walker.move_forward() {}
+                Some(NonNull::from(walker.into_inner_ref()))
+            }
+
+            _ => None,
+        };
+    }
+}
+
+// Dropping the head would drop its successor, and so on, recursing once
+// per node. Unlinking them one at a time keeps long lists off the stack.
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        let mut curr = self.head.take();
+
+        while let Some(mut node) = curr {
+            curr = node.next.take();
+        }
+    }
+}
+
+pub struct Iter<'list, T> {
+    curr_node: Option<&'list ListNode<T>>,
+}
+
+pub struct IterMut<'list, T> {
+    curr_node: Option<&'list mut ListNode<T>>,
+    // The last node is reached through the list's `tail`, never its box
+    tail: Option<NonNull<ListNode<T>>>,
+    remaining: usize,
+}
+
+pub struct IntoIter<T>(LinkedList<T>);
+
+// SAFETY: `tail` is just the last of the nodes `curr_node` borrows from
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+// SAFETY: as above
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+impl<'list, T> LinkedList<T> {
+    pub fn iter(&'list self) -> Iter<'list, T> {
+        Iter {
+            curr_node: self.head.as_deref(),
+        }
+    }
+
+    pub fn iter_mut(&'list mut self) -> IterMut<'list, T> {
+        IterMut {
+            curr_node: self.head.as_deref_mut(),
+            tail: self.tail,
+            remaining: self.len,
+        }
+    }
+}
+
+impl<'list, T> Iterator for Iter<'list, T> {
+    type Item = &'list T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.curr_node.map(|node| {
+            self.curr_node = node.next.as_deref();
+            &node.val
+        })
+    }
+}
+
+impl<'list, T> Iterator for IterMut<'list, T> {
+    type Item = &'list mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.curr_node.take()?;
+        self.remaining -= 1;
+
+        self.curr_node = match (self.remaining, self.tail) {
+            // SAFETY: the last node is never reborrowed through its box
+            // while `tail` is set, and each node is handed out only once
+            (1, Some(mut tail)) => Some(unsafe { tail.as_mut() }),
+            _ => node.next.as_deref_mut(),
+        };
+        Some(&mut node.val)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'list, T> IntoIterator for &'list LinkedList<T> {
+    type Item = &'list T;
+    type IntoIter = Iter<'list, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'list, T> IntoIterator for &'list mut LinkedList<T> {
+    type Item = &'list mut T;
+    type IntoIter = IterMut<'list, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+
+        for val in iter {
+            list.append_back(val);
+        }
+
+        list
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    impl<T: Copy> LinkedList<T> {
+        fn to_vec(&self) -> Vec<T> {
+            self.iter().copied().collect()
+        }
+    }
+
+    #[test]
+    fn appending() {
+        let mut list = LinkedList::new();
+        assert!(list.is_empty());
+
+        list.append_back(2);
+        list.append_front(1);
+        list.append_back(3);
+
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.to_vec(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn popping() {
+        let mut list: LinkedList<u8> = (1..=4).collect();
+
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pop_back(), Some(4));
+        assert_eq!(list.pop_back(), Some(3));
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.pop_back(), Some(2));
+
+        assert_eq!(list.pop_back(), None);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.len(), 0);
+
+        list.append_back(5);
+        assert_eq!(list.pop_front(), Some(5));
+    }
+
+    #[test]
+    fn peeking() {
+        let mut list = LinkedList::new();
+        assert_eq!(list.peek(), None);
+        assert_eq!(list.peek_mut(), None);
+
+        list.append_front(String::from("a"));
+        list.peek_mut().unwrap().push('b');
+        assert_eq!(list.peek().map(String::as_str), Some("ab"));
+    }
+
+    #[test]
+    fn iterating() {
+        let mut list: LinkedList<i32> = (1..=3).collect();
+
+        for val in list.iter_mut() {
+            *val *= 10;
+        }
+        for val in &mut list {
+            *val += 1;
+        }
+        assert!((&list).into_iter().eq(&[11, 21, 31]));
+
+        let into_iter = list.into_iter();
+        assert_eq!(into_iter.size_hint(), (3, Some(3)));
+        assert!(into_iter.eq([11, 21, 31]));
+
+        let mut empty: LinkedList<i32> = LinkedList::new();
+        assert_eq!(empty.iter().next(), None);
+        assert_eq!(empty.iter_mut().next(), None);
+    }
+
+    #[test]
+    fn reversing_and_clearing() {
+        let mut list: LinkedList<i32> = (1..=5).collect();
+
+        list.reverse();
+        assert_eq!(list.to_vec(), [5, 4, 3, 2, 1]);
+        assert_eq!(list.len(), 5);
+
+        list.clear();
+        assert!(list.is_empty());
+        list.reverse();
+        assert_eq!(list.to_vec(), []);
+
+        // Walking an empty list is fine too
+        list.walk();
+    }
+
+    #[test]
+    fn keeping_the_tail() {
+        let mut list = LinkedList::new();
+        assert_eq!(list.back(), None);
+
+        list.append_front(2);
+        list.append_front(1);
+        list.append_back(3);
+        assert_eq!(list.back(), Some(&3));
+
+        for val in list.iter_mut() {
+            *val *= 10;
+        }
+        list.append_back(40);
+        assert_eq!(list.to_vec(), [10, 20, 30, 40]);
+
+        list.reverse();
+        *list.back_mut().unwrap() += 1;
+        assert_eq!(list.pop_back(), Some(11));
+        assert_eq!(list.pop_back(), Some(20));
+        assert_eq!(list.back(), Some(&30));
+
+        list.append_back(5);
+        list.walk();
+        list.append_back(6);
+        assert_eq!(list.to_vec(), [40, 30, 5, 6]);
+
+        // Moving short lists around keeps them usable
+        let mut single: LinkedList<i32> = [1].into_iter().collect();
+        *single.back_mut().unwrap() = 7;
+        single.append_back(8);
+        assert_eq!(std::mem::take(&mut single).back(), Some(&8));
+    }
+
+    #[test]
+    fn appending_lists() {
+        let mut list: LinkedList<i32> = (1..=3).collect();
+        let mut other: LinkedList<i32> = (4..=5).collect();
+
+        list.append(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(other.back(), None);
+        assert_eq!(list.len(), 5);
+
+        list.append_back(6);
+        assert_eq!(list.to_vec(), [1, 2, 3, 4, 5, 6]);
+
+        let (mut empty, mut single) = (LinkedList::new(), LinkedList::new());
+        single.append_back(7);
+        empty.append(&mut single);
+        empty.append(&mut LinkedList::new());
+        assert_eq!(empty.back(), Some(&7));
+
+        list.append(&mut empty);
+        *list.back_mut().unwrap() += 1;
+        assert_eq!(list.to_vec(), [1, 2, 3, 4, 5, 6, 8]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore = "too slow under Miri")]
+    fn long_lists() {
+        let list: LinkedList<u32> = (0..1_000_000).collect();
+        assert_eq!(list.len(), 1_000_000);
+        assert_eq!(list.back(), Some(&999_999));
+        drop(list);
+    }
+}
@@ -1,244 +1,421 @@
-use std::{marker::PhantomData, ptr::{self, NonNull}, cell::Cell};
-use ::polonius_the_crab::prelude::*;
-use recursive_reference::*;
+//! Applies a script of operations to one of the workspace's structures,
+//! printing what each one does, e.g. to replay a bug report:
+//!
+//! ```text
+//! walking avl ops.txt
+//! echo "insert 3 1 2\nprint" | walking rc
+//! ```
+//!
+//! Commands, one per line (`#` starts a comment):
+//!
+//! | command          | does                                        |
+//! |------------------|---------------------------------------------|
+//! | `insert V...`    | inserts every value, in order               |
+//! | `remove V`       | removes one `V`                             |
+//! | `search V`       | looks for `V`                               |
+//! | `print`          | prints the values in order, and the shape   |
+//! | `range LO HI`    | prints the values from `LO` to `HI`, both included |
+
+use std::env;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::process::ExitCode;
+
+use walking::LinkedList;
+
+type Value = i64;
+
+const USAGE: &str = "usage: walking <list|avl|raw|rc> [SCRIPT]";
+
+// What the commands need from each structure
+trait Structure {
+    fn insert(&mut self, val: Value);
+    fn remove(&mut self, val: Value) -> Option<Value>;
+    fn contains(&self, val: Value) -> bool;
+    // Every value, in the structure's own order
+    fn values(&self) -> Vec<Value>;
+    // How the structure lays its values out, drawn over however many lines
+    fn shape(&self) -> String;
+}
 
-type ListLink<T> = Option<Box<ListNode<T>>>;
+impl Structure for LinkedList<Value> {
+    fn insert(&mut self, val: Value) {
+        self.append_back(val);
+    }
 
-struct ListNode<T> {
-    val: T,
-    next: ListLink<T>,
-}
+    fn remove(&mut self, val: Value) -> Option<Value> {
+        let mut removed = None;
 
-impl<T> ListNode<T> {
-    fn new(val: T, next: ListLink<T>) -> Self {
-        ListNode { val, next }
+        self.retain(|&item| match removed {
+            None if item == val => {
+                removed = Some(item);
+                false
+            }
+            _ => true,
+        });
+        removed
     }
-}
 
-impl<T> Into<ListLink<T>> for ListNode<T> {
-    fn into(self) -> ListLink<T> {
-        Some(Box::new(self))
+    fn contains(&self, val: Value) -> bool {
+        self.iter().any(|&item| item == val)
     }
-}
 
-pub struct LinkedList<T> {
-    head: ListLink<T>,
-}
+    fn values(&self) -> Vec<Value> {
+        self.iter().copied().collect()
+    }
 
-struct ListWalker<'list, T> {
-    curr_ptr: NonNull<ListNode<T>>,
-    _phantom: PhantomData<&'list mut ListNode<T>>
+    fn shape(&self) -> String {
+        let nodes: Vec<String> = self.iter().map(Value::to_string).collect();
+        format!(
+            "head -> {}",
+            [&nodes[..], &["None".into()]].concat().join(" -> ")
+        )
+    }
 }
 
-impl<'list, T> ListWalker<'list, T> {
-    #[inline]
-    fn new(start_ref: &'list mut ListLink<T>) -> Self {
-        let raw = match start_ref.as_deref_mut() {
-            Some(node) => node as *mut _,
-            None => panic!(),
-        };
-
-        Self {
-            curr_ptr: NonNull::new(raw).unwrap(),
-            _phantom: PhantomData,
-        }
+impl Structure for box_bintree::BinTree<Value> {
+    fn insert(&mut self, val: Value) {
+        self.insert(val);
     }
 
-    #[inline]
-    fn move_forward(&mut self) -> Result<(), ()> {
-        let curr_node = unsafe { self.curr_ptr.as_mut() };
+    fn remove(&mut self, val: Value) -> Option<Value> {
+        self.remove(&val)
+    }
 
-        match curr_node.next.as_deref_mut() {
-            Some(next_node) => {
-                self.curr_ptr = NonNull::from(next_node);
-                Ok(())
-            }
+    fn contains(&self, val: Value) -> bool {
+        self.search(&val).is_some()
+    }
 
-            None => Err(()),
-        }
+    fn values(&self) -> Vec<Value> {
+        self.iter().copied().collect()
     }
 
-    #[inline]
-    fn into_inner_ref(mut self) -> &'list mut ListNode<T> {
-        unsafe { self.curr_ptr.as_mut() }
+    fn shape(&self) -> String {
+        self.to_string()
     }
 }
 
-impl<T> LinkedList<T> {
-    pub fn new() -> Self {
-        LinkedList { head: None }
+impl Structure for raw_bintree::BinaryTree<Value> {
+    fn insert(&mut self, val: Value) {
+        self.insert(val);
     }
 
-    pub fn append_front(&mut self, val: T) {
-        let new_node = ListNode::new(val, self.head.take()).into();
-        let _ = self.head.insert(new_node);
+    fn remove(&mut self, val: Value) -> Option<Value> {
+        self.remove(&val)
     }
 
-    pub fn append_back(&mut self, val: T) {
-        let new_node = ListNode::new(val, None).into();
-
-        let tail = match self.head.as_deref_mut() {
-            None => &mut self.head,
-
-            head @ Some(_) => {
-                let mut curr_node: Option<&mut ListNode<T>> = head;
-
-                while let Some(ListNode{ next: n @ Some(_), .. }) = curr_node {
-                    curr_node = n.as_deref_mut();
-                }
-        
-                &mut curr_node.unwrap().next
-            }
-        };
-
-        *tail = new_node;
+    fn contains(&self, val: Value) -> bool {
+        self.search(&val).is_some()
     }
 
-    pub fn walk(&mut self) {
-        let mut walker = ListWalker::new(&mut self.head);
+    fn values(&self) -> Vec<Value> {
+        self.iter().copied().collect()
+    }
 
-        println!("starting walk @ head...");
+    fn shape(&self) -> String {
+        self.to_string()
+    }
+}
 
-        while let Ok(()) = walker.move_forward() {
-            println!("Walked to another node");
-            // println! ("Node here, next pointer {:#}", &node_ref.next as *const _ as usize);
-        }
+impl Structure for leetcode_bintree::BinaryTree<Value> {
+    fn insert(&mut self, val: Value) {
+        self.insert(val);
     }
 
-    fn calculate_size(&mut self) -> usize {
-        let mut cnt = 1_usize;
+    fn remove(&mut self, val: Value) -> Option<Value> {
+        self.remove(&val)
+    }
 
-        let mut walker = ListWalker::new(&mut self.head);
+    fn contains(&self, val: Value) -> bool {
+        self.search(&val).is_some()
+    }
 
-        while let Ok(()) = walker.move_forward() {
-            cnt += 1;
-        }
+    fn values(&self) -> Vec<Value> {
+        self.iter().map(|item| *item).collect()
+    }
 
-        cnt
+    fn shape(&self) -> String {
+        self.to_string()
     }
 }
 
-impl<'list, T> LinkedList<T> {
-    fn get_tail_mut(&'list mut self) -> &'list mut ListLink<T> {
+fn structure(name: &str) -> Option<Box<dyn Structure>> {
+    Some(match name {
+        "list" => Box::new(LinkedList::new()),
+        "avl" => Box::new(box_bintree::BinTree::new()),
+        "raw" => Box::new(raw_bintree::BinaryTree::new()),
+        "rc" => Box::new(leetcode_bintree::BinaryTree::new()),
+        _ => return None,
+    })
+}
 
-        // The crux of the issue, this situation:
-        match self.head.as_deref_mut() {
-            head @ None => &mut self.head,
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+    Insert(Vec<Value>),
+    Remove(Value),
+    Search(Value),
+    Print,
+    Range(Value, Value),
+}
 
-            head @ Some(_) => {
-                let mut curr_node: Option<&mut ListNode<T>> = self.head.as_deref_mut();
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum CommandError {
+    Unknown(String),
+    Arguments(&'static str),
+    InvalidValue(String),
+}
 
-                while let Some(ListNode{ next: n @ Some(_), .. }) = curr_node {
-                    curr_node = n.as_deref_mut();
-                }
-        
-                &mut curr_node.unwrap().next
-            }
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(name) => write!(f, "unknown command {:?}", name),
+            Self::Arguments(usage) => write!(f, "expected `{}`", usage),
+            Self::InvalidValue(token) => write!(f, "invalid value {:?}", token),
         }
+    }
+}
 
-        // let mut curr_node: Option<&mut ListNode<T>> = self.head.as_deref_mut();
-
-        // while let Some(ListNode{ next: n @ Some(_), .. }) = curr_node {
-        //     curr_node = n.as_deref_mut();
-        // }
-
-        // &mut curr_node.unwrap().next
-
-        // Using unsafe Rust, raw pointers
-
-        // match self.head.as_deref_mut() {
-        //     None => &mut self.head,
-
-        //     Some(node) => {
-        //         let mut curr_ptr = node as *mut ListNode<T>;
-
-        //         while let Some(n) = unsafe{ curr_ptr.as_mut() }.map(|n| n.next.as_deref_mut()).flatten() {
-        //             curr_ptr = n as *mut ListNode<T>;
-        //         }
-        
-        //         unsafe{ &mut curr_ptr.as_mut().unwrap().next }
-        //     }
-        // }
-
-
-
-        // Using Polonius, a new take on borrow checker to bypass NLL false-positives
-        // let mut curr_node: &mut ListLink<T> = &mut self.head;
+impl Command {
+    // `None` for blank lines and comments
+    fn parse(line: &str) -> Option<Result<Self, CommandError>> {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut tokens = line.split_whitespace();
+        let name = tokens.next()?;
+
+        let values: Result<Vec<Value>, _> = tokens
+            .map(|token| {
+                token
+                    .parse()
+                    .map_err(|_| CommandError::InvalidValue(token.into()))
+            })
+            .collect();
+
+        Some(values.and_then(|values| match (name, &values[..]) {
+            ("insert", [_, ..]) => Ok(Self::Insert(values)),
+            ("insert", _) => Err(CommandError::Arguments("insert V...")),
+            ("remove", &[val]) => Ok(Self::Remove(val)),
+            ("remove", _) => Err(CommandError::Arguments("remove V")),
+            ("search", &[val]) => Ok(Self::Search(val)),
+            ("search", _) => Err(CommandError::Arguments("search V")),
+            ("print", []) => Ok(Self::Print),
+            ("print", _) => Err(CommandError::Arguments("print")),
+            ("range", &[lo, hi]) => Ok(Self::Range(lo, hi)),
+            ("range", _) => Err(CommandError::Arguments("range LO HI")),
+            _ => Err(CommandError::Unknown(name.into())),
+        }))
+    }
 
-        // polonius_loop!(
-        //     |curr_node| -> &'polonius mut ListLink<T> {
-        //         match curr_node.as_deref_mut() {
-        //             None => polonius_return!(),
+    fn apply(self, structure: &mut dyn Structure, out: &mut impl Write) -> io::Result<()> {
+        match self {
+            Self::Insert(values) => values.into_iter().for_each(|val| structure.insert(val)),
 
-        //             Some(n) => {
-        //                 curr_node = &mut n.next;
-        //                 polonius_continue!();
-        //             }
-        //         }
-        //     }
-        // );
-        
-        // curr_node
-        
+            Self::Remove(val) => match structure.remove(val) {
+                Some(removed) => writeln!(out, "removed {}", removed)?,
+                None => writeln!(out, "{} not found", val)?,
+            },
 
-        // using recursive_reference
-        // let mut recref = RecRef::new(&mut self.head);
+            Self::Search(val) => match structure.contains(val) {
+                true => writeln!(out, "found {}", val)?,
+                false => writeln!(out, "{} not found", val)?,
+            },
 
-        // while let Ok(()) = RecRef::map_result(&mut recref, |node| match node.as_deref_mut() {
-        //     Some(ListNode{ next: n @ Some(_), .. }) => Ok(n),
-        //     _ => Err(()),
-        // }) {}
+            Self::Print => {
+                writeln!(out, "{:?}", structure.values())?;
+                writeln!(out, "shape:\n{}", structure.shape())?;
+            }
 
-        // RecRef::into_ref(recref)
+            Self::Range(lo, hi) => {
+                let in_range: Vec<Value> = structure
+                    .values()
+                    .into_iter()
+                    .filter(|val| (lo..=hi).contains(val))
+                    .collect();
 
+                writeln!(out, "{:?}", in_range)?;
+            }
+        }
 
-        // Using my ListWalker, a wrapping around unsafe code
-        // let mut walker = ListWalker::new(&mut self.head);
+        Ok(())
+    }
+}
 
-        // while let Ok(()) = walker.move_forward() {}
+// Runs every line of `input`, reporting bad ones to `err` and carrying on.
+// Returns whether every line ran.
+fn run(
+    structure: &mut dyn Structure,
+    input: impl BufRead,
+    out: &mut impl Write,
+    err: &mut impl Write,
+    prompt: bool,
+) -> io::Result<bool> {
+    let mut all_ran = true;
+
+    if prompt {
+        write!(out, "> ")?;
+        out.flush()?;
+    }
 
-        // &mut walker.into_inner_ref().next
+    for (index, line) in input.lines().enumerate() {
+        let result = match Command::parse(&line?) {
+            Some(Ok(command)) => Ok(command.apply(structure, out)?),
+            Some(Err(error)) => Err(error),
+            None => Ok(()),
+        };
 
+        if let Err(error) = result {
+            writeln!(err, "line {}: {}", index + 1, error)?;
+            all_ran = false;
+        }
 
+        if prompt {
+            write!(out, "> ")?;
+            out.flush()?;
+        }
     }
-}
 
-pub struct Iter<'list, T> {
-    curr_node: Option<&'list ListNode<T>>,
+    Ok(all_ran)
 }
 
-impl<'list, T> LinkedList<T> {
-    pub fn iter(&'list self) -> Iter<'list, T> {
-        Iter {
-            curr_node: self.head.as_deref(),
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let (Some(mut structure), script) = (match &args[..] {
+        [name] => (structure(name), None),
+        [name, script] => (structure(name), Some(script)),
+        _ => (None, None),
+    }) else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+
+    let (mut out, mut err) = (io::stdout().lock(), io::stderr().lock());
+
+    let ran = match script {
+        Some(path) => match File::open(path) {
+            Ok(file) => run(
+                &mut *structure,
+                BufReader::new(file),
+                &mut out,
+                &mut err,
+                false,
+            ),
+            Err(error) => {
+                eprintln!("can't open {}: {}", path, error);
+                return ExitCode::FAILURE;
+            }
+        },
+
+        None => {
+            let stdin = io::stdin();
+            let prompt = stdin.is_terminal();
+            run(&mut *structure, stdin.lock(), &mut out, &mut err, prompt)
+        }
+    };
+
+    match ran {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
         }
     }
 }
 
-impl<'list, T> Iterator for Iter<'list, T> {
-    type Item = &'list T;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = "
+        # Comments and blank lines are skipped
+        insert 5 3 8 1 4
+        insert 7
+
+        search 4
+        search 6
+        remove 3
+        remove 3
+        range 2 7
+        print
+    ";
+
+    fn run_script(name: &str, script: &str) -> (String, String, bool) {
+        let mut structure = structure(name).unwrap();
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+
+        let ran = run(
+            &mut *structure,
+            script.as_bytes(),
+            &mut out,
+            &mut err,
+            false,
+        )
+        .unwrap();
+        (
+            String::from_utf8(out).unwrap(),
+            String::from_utf8(err).unwrap(),
+            ran,
+        )
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.curr_node.map(|node| {
-            self.curr_node = node.next.as_deref();
-            &node.val
-        })
+    #[test]
+    fn parsing_commands() {
+        assert_eq!(Command::parse("  # nothing"), None);
+        assert_eq!(
+            Command::parse("insert 1 -2"),
+            Some(Ok(Command::Insert(vec![1, -2])))
+        );
+        assert_eq!(
+            Command::parse("range 1 2 # inclusive"),
+            Some(Ok(Command::Range(1, 2)))
+        );
+        assert_eq!(
+            Command::parse("insert"),
+            Some(Err(CommandError::Arguments("insert V...")))
+        );
+        assert_eq!(
+            Command::parse("remove x"),
+            Some(Err(CommandError::InvalidValue("x".into())))
+        );
+        assert_eq!(
+            Command::parse("pop"),
+            Some(Err(CommandError::Unknown("pop".into())))
+        );
     }
-}
-fn main() {
-    let mut ll = LinkedList::new();
-    // ll.append_front(1);
-    ll.append_back(2);
-    ll.append_front(3);
-    ll.append_back(4);
-    // ll.append_back(5);
-    // ll.append_back(7);
 
-    ll.walk();
+    #[test]
+    fn running_scripts() {
+        let expected = "found 4\n6 not found\nremoved 3\n3 not found\n[4, 5, 7]\n[1, 4, 5, 7, 8]\n";
+
+        for name in ["avl", "raw", "rc"] {
+            let (out, err, ran) = run_script(name, SCRIPT);
+            assert!(out.starts_with(expected), "{}: {}", name, out);
+            assert_eq!((err.as_str(), ran), ("", true));
+        }
+
+        let (out, _, _) = run_script("rc", SCRIPT);
+        assert!(
+            out.ends_with("shape:\n5\n├── 4\n│   ├── 1\n│   └── ·\n└── 8\n    ├── 7\n    └── ·\n")
+        );
+
+        // The list keeps insertion order
+        let (out, _, ran) = run_script("list", SCRIPT);
+        assert!(out.contains(
+            "[5, 4, 7]\n[5, 8, 1, 4, 7]\nshape:\nhead -> 5 -> 8 -> 1 -> 4 -> 7 -> None\n"
+        ));
+        assert!(ran);
+    }
 
-    println!("size is {}", ll.calculate_size());
+    #[test]
+    fn reporting_bad_lines() {
+        let (out, err, ran) = run_script("avl", "insert 2 1\nremove x\nfrobnicate\nsearch 1");
 
-    for (idx, val) in ll.iter().enumerate() {
-        println!("element {} - {}", idx, *val);
+        assert_eq!(out, "found 1\n");
+        assert_eq!(
+            err,
+            "line 2: invalid value \"x\"\nline 3: unknown command \"frobnicate\"\n"
+        );
+        assert!(!ran);
     }
 }
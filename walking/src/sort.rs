@@ -0,0 +1,232 @@
+//! Sorting, merging and filtering lists in place, by relinking their nodes
+//! rather than moving any values.
+//!
+//! Each of these works on the nodes after taking them out of the list, so
+//! that should a comparison or predicate panic, the list is left empty
+//! rather than with a `tail` pointing who knows where.
+
+use std::cmp::Ordering;
+use std::mem;
+
+use crate::{LinkedList, ListLink};
+
+impl<T> LinkedList<T> {
+    /// Sorts the list with a stable merge sort, in O(n log n) time.
+    #[inline]
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_by(T::cmp);
+    }
+
+    #[inline]
+    pub fn sort_by_key<K, F>(&mut self, mut key: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_by(|a, b| key(a).cmp(&key(b)));
+    }
+
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut list = mem::take(self);
+
+        list.head = merge_sort(list.head.take(), list.len, &mut compare);
+        list.reset_tail();
+        *self = list;
+    }
+
+    /// Merges the sorted `other` into this sorted list, leaving `other`
+    /// empty. Of any equal values, those from this list come first.
+    pub fn merge(&mut self, other: &mut Self)
+    where
+        T: Ord,
+    {
+        let mut list = mem::take(self);
+        let mut other = mem::take(other);
+
+        list.head = merge(list.head.take(), other.head.take(), &mut T::cmp);
+        list.len += mem::take(&mut other.len);
+        list.reset_tail();
+        *self = list;
+    }
+
+    /// Removes every value equal to the one before it.
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        let mut list = mem::take(self);
+        let mut curr = list.head.as_deref_mut();
+
+        while let Some(node) = curr {
+            while node.next.as_ref().is_some_and(|next| next.val == node.val) {
+                let removed = node.next.take().expect("checked above");
+                node.next = removed.next;
+                list.len -= 1;
+            }
+
+            curr = node.next.as_deref_mut();
+        }
+
+        list.reset_tail();
+        *self = list;
+    }
+
+    /// Keeps only the values `keep` returns true for, in order.
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut list = mem::take(self);
+        let mut link = &mut list.head;
+
+        while let Some(node) = link.as_deref() {
+            if keep(&node.val) {
+                link = &mut link.as_mut().expect("checked above").next;
+            } else {
+                let removed = link.take().expect("checked above");
+                *link = removed.next;
+                list.len -= 1;
+            }
+        }
+
+        list.reset_tail();
+        *self = list;
+    }
+}
+
+// Sorts the `len` nodes starting at `head`. Splitting by length keeps the
+// recursion O(log n) deep.
+fn merge_sort<T, F>(mut head: ListLink<T>, len: usize, compare: &mut F) -> ListLink<T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if len < 2 {
+        return head;
+    }
+
+    let mut link = &mut head;
+    for _ in 0..len / 2 {
+        link = &mut link.as_mut().expect("list holds len nodes").next;
+    }
+    let back = link.take();
+
+    let front = merge_sort(head, len / 2, compare);
+    let back = merge_sort(back, len - len / 2, compare);
+    merge(front, back, compare)
+}
+
+// Merges two sorted chains of nodes, preferring `front` on ties so that
+// sorting stays stable
+fn merge<T, F>(mut front: ListLink<T>, mut back: ListLink<T>, compare: &mut F) -> ListLink<T>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut merged = None;
+    let mut tail = &mut merged;
+
+    while let (Some(a), Some(b)) = (front.as_deref(), back.as_deref()) {
+        let from = match compare(&b.val, &a.val) {
+            Ordering::Less => &mut back,
+            _ => &mut front,
+        };
+
+        let mut node = from.take().expect("checked above");
+        *from = node.next.take();
+        tail = &mut tail.insert(node).next;
+    }
+
+    *tail = front.or(back);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::LinkedList;
+
+    // A fixed permutation of 0..modulus
+    fn shuffled(modulus: u32) -> impl Iterator<Item = u32> {
+        (0..modulus).map(move |i| (i * 7_919 + 13) % modulus)
+    }
+
+    #[test]
+    fn sorting() {
+        let mut list: LinkedList<u32> = shuffled(1_000).collect();
+
+        list.sort();
+        assert!(list.iter().copied().eq(0..1_000));
+        assert_eq!(list.back(), Some(&999));
+
+        list.sort_by(|a, b| b.cmp(a));
+        assert!(list.iter().copied().eq((0..1_000).rev()));
+
+        let mut short: LinkedList<u32> = [2, 1].into_iter().collect();
+        short.sort();
+        short.append_back(3);
+        assert!(short.into_iter().eq(1..=3));
+
+        let mut empty: LinkedList<u32> = LinkedList::new();
+        empty.sort();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn sorting_is_stable() {
+        let mut list: LinkedList<(u32, u32)> = shuffled(200).map(|i| (i % 7, i)).collect();
+
+        list.sort_by_key(|&(key, _)| key);
+
+        let sorted: Vec<_> = list.iter().copied().collect();
+        let mut expected: Vec<_> = shuffled(200).map(|i| (i % 7, i)).collect();
+        expected.sort_by_key(|&(key, _)| key);
+        assert_eq!(sorted, expected);
+    }
+
+    #[test]
+    fn merging() {
+        let mut list: LinkedList<(u32, char)> =
+            [(1, 'a'), (3, 'a'), (5, 'a')].into_iter().collect();
+        let mut other: LinkedList<(u32, char)> = [(0, 'b'), (3, 'b'), (6, 'b'), (7, 'b')]
+            .into_iter()
+            .collect();
+
+        list.merge(&mut other);
+        assert!(other.is_empty());
+        assert_eq!(list.len(), 7);
+
+        list.append_back((8, 'a'));
+        let merged: Vec<_> = list.iter().map(|&(val, _)| val).collect();
+        assert_eq!(merged, [0, 1, 3, 3, 5, 6, 7, 8]);
+        assert!(list
+            .iter()
+            .filter(|(val, _)| *val == 3)
+            .map(|&(_, from)| from)
+            .eq(['a', 'b']));
+    }
+
+    #[test]
+    fn deduplicating_and_retaining() {
+        let mut list: LinkedList<u32> = [1, 1, 2, 3, 3, 3, 1, 4, 4].into_iter().collect();
+
+        list.dedup();
+        assert!(list.iter().eq(&[1, 2, 3, 1, 4]));
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.back(), Some(&4));
+
+        list.retain(|val| val % 2 == 1);
+        assert!(list.iter().eq(&[1, 3, 1]));
+        assert_eq!(list.len(), 3);
+
+        list.append_back(5);
+        assert_eq!(list.back(), Some(&5));
+
+        list.retain(|_| false);
+        assert!(list.is_empty());
+        assert_eq!(list.back(), None);
+    }
+}
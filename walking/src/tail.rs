@@ -0,0 +1,184 @@
+//! Ways of reaching the empty link past a list's last node.
+//!
+//! The obvious loop, moving a `&mut` cursor from each link to the next
+//! node's `next`, is rejected by NLL because the cursor is conditionally
+//! returned out of the loop. Each function here gets around that in a
+//! different way, and is compiled only with its cargo feature:
+//!
+//! | feature         | function       | how                                       |
+//! |-----------------|----------------|-------------------------------------------|
+//! | `tail-unsafe`   | `raw_pointers` | raw pointers, dereferenced by hand        |
+//! | `tail-polonius` | `polonius`     | `polonius_the_crab`'s borrow-checker shim |
+//! | `tail-recref`   | `rec_ref`      | `recursive_reference::RecRef`             |
+//! | `tail-walker`   | `walker`       | this crate's `ListWalker`                 |
+//!
+//! `selected` is whichever of them comes first in that table. All of them
+//! are expected to pass the same tests, under Miri too:
+//!
+//! ```sh
+//! cargo test -p walking --all-features
+//! cargo +nightly miri test -p walking --all-features
+//! ```
+
+// Outside of tests, only `selected` is ever called
+#![cfg_attr(not(test), allow(dead_code))]
+
+use crate::ListLink;
+
+#[cfg(not(any(
+    feature = "tail-unsafe",
+    feature = "tail-polonius",
+    feature = "tail-recref",
+    feature = "tail-walker"
+)))]
+compile_error!(
+    "walking needs one of the tail-unsafe, tail-polonius, tail-recref or tail-walker features"
+);
+
+#[cfg(feature = "tail-unsafe")]
+pub(crate) use raw_pointers as selected;
+
+#[cfg(all(feature = "tail-polonius", not(feature = "tail-unsafe")))]
+pub(crate) use polonius as selected;
+
+#[cfg(all(
+    feature = "tail-recref",
+    not(any(feature = "tail-unsafe", feature = "tail-polonius"))
+))]
+pub(crate) use rec_ref as selected;
+
+#[cfg(all(
+    feature = "tail-walker",
+    not(any(
+        feature = "tail-unsafe",
+        feature = "tail-polonius",
+        feature = "tail-recref"
+    ))
+))]
+pub(crate) use walker as selected;
+
+#[cfg(feature = "tail-unsafe")]
+pub(crate) fn raw_pointers<T>(head: &mut ListLink<T>) -> &mut ListLink<T> {
+    let mut curr_ptr: *mut ListLink<T> = head;
+
+    // SAFETY: every pointer is derived from `head`, which is borrowed
+    // mutably for the whole walk, and is only turned back into a
+    // reference once
+    unsafe {
+        while let Some(node) = (*curr_ptr).as_deref_mut() {
+            curr_ptr = &mut node.next;
+        }
+
+        &mut *curr_ptr
+    }
+}
+
+#[cfg(feature = "tail-polonius")]
+pub(crate) fn polonius<T>(mut curr: &mut ListLink<T>) -> &mut ListLink<T> {
+    use ::polonius_the_crab::prelude::*;
+
+    if curr.is_none() {
+        return curr;
+    }
+
+    loop {
+        // Returning a borrow of `curr` on one branch is what NLL can't see
+        // past; polonius_the_crab hands `curr` back on the other
+        polonius!(|curr| -> &'polonius mut ListLink<T> {
+            if let Some(node) = curr.as_deref_mut() {
+                if node.next.is_none() {
+                    polonius_return!(&mut node.next);
+                }
+            }
+        });
+
+        curr = &mut curr.as_deref_mut().unwrap().next;
+    }
+}
+
+#[cfg(feature = "tail-recref")]
+pub(crate) fn rec_ref<T>(head: &mut ListLink<T>) -> &mut ListLink<T> {
+    use ::recursive_reference::RecRef;
+
+    let mut recref = RecRef::new(head);
+
+    while let Ok(()) = RecRef::map_result(&mut recref, |link| match link.as_deref_mut() {
+        Some(node) => Ok(&mut node.next),
+        None => Err(()),
+    }) {}
+
+    RecRef::into_ref(recref)
+}
+
+#[cfg(feature = "tail-walker")]
+pub(crate) fn walker<T>(head: &mut ListLink<T>) -> &mut ListLink<T> {
+    use crate::ListWalker;
+
+    if head.is_none() {
+        return head;
+    }
+
+    let mut walker = ListWalker::new(head).unwrap();
+    while let Ok(()) = walker.move_forward() {}
+
+    &mut walker.into_inner_ref().next
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{LinkedList, ListLink, ListNode};
+
+    // Every strategy has to pass exactly the same tests
+    macro_rules! tail_suite {
+        ($($feature:literal => $strategy:ident),* $(,)?) => {$(
+            #[cfg(feature = $feature)]
+            mod $strategy {
+                use super::*;
+
+                fn tail<T>(list: &mut LinkedList<T>) -> &mut ListLink<T> {
+                    super::super::$strategy(&mut list.head)
+                }
+
+                #[test]
+                fn empty_list() {
+                    let mut list: LinkedList<u8> = LinkedList::new();
+                    let head: *const ListLink<u8> = &list.head;
+
+                    assert!(std::ptr::eq(tail(&mut list), head));
+                }
+
+                #[test]
+                fn writing_through_the_tail() {
+                    let mut list: LinkedList<u8> = LinkedList::new();
+
+                    for val in 0..5 {
+                        let link = tail(&mut list);
+                        assert!(link.is_none());
+                        *link = ListNode::new(val, None).into();
+                        list.len += 1;
+                    }
+
+                    assert!(list.iter().copied().eq(0..5));
+                }
+
+                #[test]
+                fn long_lists() {
+                    let mut list: LinkedList<u32> = (0..2_000).collect();
+
+                    *tail(&mut list) = ListNode::new(2_000, None).into();
+                    list.len += 1;
+
+                    assert_eq!(list.pop_back(), Some(2_000));
+                    assert_eq!(list.pop_back(), Some(1_999));
+                }
+            }
+        )*};
+    }
+
+    tail_suite! {
+        "tail-unsafe" => raw_pointers,
+        "tail-polonius" => polonius,
+        "tail-recref" => rec_ref,
+        "tail-walker" => walker,
+    }
+}
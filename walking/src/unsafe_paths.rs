@@ -0,0 +1,236 @@
+//! Tests walking every path through the crate's unsafe code: `ListWalker`
+//! and the `tail` pointer `LinkedList` keeps, and `DoublyLinkedList`'s
+//! links, cursor and iterators. Values are `String`s, so that a node freed
+//! too early or never is caught. They're kept small enough for Miri, under
+//! both of its aliasing models, and are meant for the address and leak
+//! sanitizers too:
+//!
+//! ```sh
+//! cargo +nightly miri test -p walking --all-features unsafe_paths
+//! MIRIFLAGS=-Zmiri-tree-borrows cargo +nightly miri test -p walking --all-features unsafe_paths
+//! RUSTFLAGS=-Zsanitizer=address cargo +nightly test -p walking --all-features --lib \
+//!     --target x86_64-unknown-linux-gnu unsafe_paths
+//! RUSTFLAGS=-Zsanitizer=leak cargo +nightly test -p walking --all-features --lib \
+//!     --target x86_64-unknown-linux-gnu unsafe_paths
+//! ```
+
+use super::*;
+
+fn strings(vals: impl IntoIterator<Item = u32>) -> Vec<String> {
+    vals.into_iter().map(|val| val.to_string()).collect()
+}
+
+fn list(vals: impl IntoIterator<Item = u32>) -> LinkedList<String> {
+    strings(vals).into_iter().collect()
+}
+
+fn doubly(vals: impl IntoIterator<Item = u32>) -> DoublyLinkedList<String> {
+    strings(vals).into_iter().collect()
+}
+
+#[test]
+fn walking_lists() {
+    let mut empty: LinkedList<String> = LinkedList::new();
+    assert!(ListWalker::new(&mut empty.head).is_none());
+
+    // A single node is both where the walk starts and where it ends
+    let mut single = list([1]);
+    let mut walker = ListWalker::new(&mut single.head).unwrap();
+    assert!(walker.peek_next().is_none());
+    assert!(walker.move_forward().is_err());
+    walker.into_inner_ref().val.push('!');
+    assert_eq!(single.peek().map(String::as_str), Some("1!"));
+
+    // Written through once handed back, then the list used as usual
+    let mut three = list(1..=3);
+    let mut walker = ListWalker::new(&mut three.head).unwrap();
+    assert_eq!(walker.peek_next().map(|next| next.val.as_str()), Some("2"));
+    assert!(walker.move_forward().is_ok());
+    assert!(walker.move_forward().is_ok());
+    assert!(walker.move_forward().is_err());
+
+    let last = walker.into_inner_ref();
+    last.next = ListNode::new("4".to_string(), None).into();
+    three.len += 1;
+    three.reset_tail();
+    assert_eq!(three.back().map(String::as_str), Some("4"));
+
+    // Dropped part of the way along
+    {
+        let mut walker = ListWalker::new(&mut three.head).unwrap();
+        _ = walker.move_forward();
+    }
+    three.append_back("5".to_string());
+    assert!(three.iter().eq(&strings(1..=5)));
+}
+
+#[test]
+fn keeping_the_tail_through_every_operation() {
+    let mut list = list([]);
+
+    // No `tail` until there are two nodes, then one to append and write
+    // through, kept or reset by everything that relinks the list
+    for val in [3, 1, 2] {
+        list.append_back(val.to_string());
+        list.back_mut().unwrap().push('.');
+    }
+    list.append_front("4.".to_string());
+    assert_eq!(list.back().map(String::as_str), Some("2."));
+
+    list.sort();
+    list.append_back("5.".to_string());
+    list.reverse();
+    list.append_back("0.".to_string());
+    list.dedup();
+    list.retain(|val| val != "3.");
+    list.append_back("6.".to_string());
+    assert!(list
+        .iter()
+        .map(String::as_str)
+        .eq(["5.", "4.", "2.", "1.", "0.", "6."]));
+
+    let mut other = self::list([9]);
+    list.merge(&mut other);
+    assert_eq!(list.back().map(String::as_str), Some("9"));
+    list.walk();
+    *list.back_mut().unwrap() += ".";
+
+    // Popping from the back walks to the node before the last
+    while list.len() > 1 {
+        let back = list.back().cloned();
+        assert_eq!(list.pop_back(), back);
+    }
+    list.append_back("7".to_string());
+    assert_eq!(list.pop_front().as_deref(), Some("5."));
+    assert_eq!(list.back().map(String::as_str), Some("7"));
+}
+
+#[test]
+fn iterating_onto_the_tail() {
+    // The last value is reached through `tail` rather than its box, which
+    // has to be fine with values before it still borrowed
+    for len in 0..4 {
+        let mut list = list(0..len);
+        let vals: Vec<&mut String> = list.iter_mut().collect();
+        assert_eq!(vals.len(), len as usize);
+
+        for val in vals {
+            val.push('!');
+        }
+
+        list.append_back("end".to_string());
+        assert_eq!(
+            list.iter().filter(|val| val.ends_with('!')).count(),
+            len as usize
+        );
+    }
+
+    // Dropped with values left, every one of them freed
+    let mut into_iter = list(0..4).into_iter();
+    assert_eq!(into_iter.next().as_deref(), Some("0"));
+    drop(into_iter);
+}
+
+#[test]
+fn linking_doubly_linked_lists() {
+    let mut list = doubly([]);
+    assert!(list.peek().is_none() && list.back_mut().is_none());
+
+    // Into the empty list, then at either end
+    list.append_back("2".to_string());
+    list.append_front("1".to_string());
+    list.append_back("3".to_string());
+    list.peek_mut().unwrap().push('<');
+    list.back_mut().unwrap().push('>');
+    assert!(list.iter().eq(&["1<", "2", "3>"]));
+
+    // Spliced onto the back, and from and onto empty lists
+    list.append(&mut doubly([4, 5]));
+    list.append(&mut doubly([]));
+    let mut empty = doubly([]);
+    empty.append(&mut list);
+    assert!(list.is_empty());
+    list = empty;
+
+    // Unlinked from both ends, down to nothing and back
+    assert_eq!(list.pop_back().as_deref(), Some("5"));
+    assert_eq!(list.pop_front().as_deref(), Some("1<"));
+    assert_eq!(list.len(), 3);
+    while list.pop_back().is_some() {}
+    assert!(list.peek().is_none() && list.back().is_none());
+    list.append_front("6".to_string());
+    assert_eq!(list.back().map(String::as_str), Some("6"));
+}
+
+#[test]
+fn cursors() {
+    let mut list = doubly(1..=4);
+    let mut cursor = list.cursor_front_mut();
+
+    // Inserting and removing around the current value and the ghost
+    cursor.insert_before("0".to_string());
+    cursor.insert_after("1.5".to_string());
+    cursor.peek_next().unwrap().push('!');
+    cursor.move_prev();
+    cursor.move_prev();
+    assert_eq!(cursor.index(), None);
+    cursor.insert_after("-1".to_string());
+    cursor.insert_before("5".to_string());
+    assert_eq!(cursor.peek_prev().map(|val| val.as_str()), Some("5"));
+    cursor.move_next();
+    assert_eq!(cursor.remove_current().as_deref(), Some("-1"));
+    assert_eq!(cursor.current().map(|val| val.as_str()), Some("0"));
+
+    // Splitting either side, then splicing it all back
+    cursor.move_next();
+    cursor.move_next();
+    let front = cursor.split_before();
+    let back = cursor.split_after();
+    assert!(front.iter().eq(&["0", "1"]));
+    assert!(back.iter().eq(&["2", "3", "4", "5"]));
+
+    cursor.splice_before(front);
+    cursor.splice_after(back);
+    assert_eq!(cursor.index(), Some(2));
+    cursor.move_prev();
+    cursor.move_prev();
+    cursor.move_prev();
+    cursor.splice_after(doubly([7]));
+    cursor.splice_before(doubly([8]));
+    assert_eq!(cursor.split_after().len(), 9);
+    assert_eq!(cursor.split_before().len(), 0);
+    assert!(list.is_empty());
+
+    // Removing everything from the back
+    let mut list = doubly(1..=3);
+    let mut cursor = list.cursor_back_mut();
+    while cursor.remove_current().is_some() {
+        cursor.move_prev();
+    }
+    assert_eq!(list.len(), 0);
+}
+
+#[test]
+fn iterating_doubly_linked_lists() {
+    let mut list = doubly(1..=5);
+
+    // From both ends until they meet, with every value borrowed at once
+    let mut iter = list.iter_mut();
+    let (first, last) = (iter.next().unwrap(), iter.next_back().unwrap());
+    let middle: Vec<&mut String> = iter.by_ref().collect();
+    assert!(iter.next().is_none() && iter.next_back().is_none());
+    first.push('<');
+    last.push('>');
+    middle.into_iter().for_each(|val| val.push('.'));
+
+    let mut iter = list.iter();
+    assert_eq!(iter.next_back().map(String::as_str), Some("5>"));
+    assert!(iter.clone().eq(&["1<", "2.", "3.", "4."]));
+    assert!(iter.rev().eq(&["4.", "3.", "2.", "1<"]));
+
+    // Consumed from both ends, then dropped with values left
+    let mut into_iter = list.into_iter();
+    assert_eq!(into_iter.next_back().as_deref(), Some("5>"));
+    assert_eq!(into_iter.next().as_deref(), Some("1<"));
+    drop(into_iter);
+}